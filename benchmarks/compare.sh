@@ -0,0 +1,40 @@
+#!/bin/sh
+# Compare evaluation time of the benchmarks between two revisions:
+#   benchmarks/compare.sh <old revision> <new revision> [runs]
+# Each revision is built in release mode in a temporary worktree. Every benchmark runs `runs` times
+# (5 by default), the median evaluation time in seconds is printed
+set -e
+
+old=$1
+new=$2
+runs=${3:-5}
+root=$(git rev-parse --show-toplevel)
+workdir=$(mktemp -d)
+
+cleanup() {
+    for revision in old new; do
+        git -C "$root" worktree remove --force "$workdir/$revision" 2>/dev/null || true
+    done
+    rm -rf "$workdir"
+}
+trap cleanup EXIT
+
+git -C "$root" worktree add -q --detach "$workdir/old" "$old"
+git -C "$root" worktree add -q --detach "$workdir/new" "$new"
+
+for revision in old new; do
+    (cd "$workdir/$revision" && cargo build --release -q)
+done
+
+median() {
+    sort -n | awk '{ times[NR] = $1 } END { print times[int((NR + 1) / 2)] }'
+}
+
+for benchmark in "$root"/benchmarks/*.lua; do
+    for revision in old new; do
+        time=$(for run in $(seq "$runs"); do
+            "$workdir/$revision/target/release/maul" -t "$benchmark" 2>&1 | awk '/^Evaluated/ { print $3 }'
+        done | median)
+        echo "$(basename "$benchmark") $revision: ${time}s"
+    done
+done
@@ -0,0 +1,19 @@
+-- List building: appending with `t[#t + 1]`, filling in reverse order and reading by index
+local size = 200000
+
+local list = {}
+for i = 1, size do
+    list[#list + 1] = i
+end
+
+local reversed = {}
+for i = size, 1, -1 do
+    reversed[i] = list[size - i + 1]
+end
+
+local sum = 0
+for i = 1, #reversed do
+    sum = sum + reversed[i]
+end
+
+assert(sum == size * (size + 1) // 2)
@@ -0,0 +1,31 @@
+-- Matrix workload: building nested tables and multiplying square matrices
+local size = 60
+
+local function matrix(n, value)
+    local rows = {}
+    for i = 1, n do
+        local row = {}
+        for j = 1, n do
+            row[j] = value(i, j)
+        end
+        rows[i] = row
+    end
+    return rows
+end
+
+local function multiply(a, b, n)
+    return matrix(n, function(i, j)
+        local sum = 0
+        local row = a[i]
+        for k = 1, n do
+            sum = sum + row[k] * b[k][j]
+        end
+        return sum
+    end)
+end
+
+local a = matrix(size, function(i, j) return i + j end)
+local b = matrix(size, function(i, j) return i - j end)
+local c = multiply(a, b, size)
+
+assert(#c == size and #c[1] == size)
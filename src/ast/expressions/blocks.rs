@@ -3,8 +3,6 @@ use std::collections::VecDeque;
 use crate::ast::expressions;
 use crate::ast::stack;
 
-use crate::interpreter;
-
 #[derive(Debug)]
 pub struct Block {
     pub statements: VecDeque<Box<dyn expressions::Expression>>,
@@ -142,7 +140,6 @@ pub struct GenericForBlock {
     pub explist: VecDeque<Box<dyn expressions::Expression>>,
     pub block: Box<dyn expressions::Expression>,
 }
-impl interpreter::Eval for GenericForBlock {}
 impl expressions::Expression for GenericForBlock {}
 
 impl GenericForBlock {
@@ -159,14 +156,39 @@ impl GenericForBlock {
     }
 }
 
-#[derive(Debug)]
-pub struct Local(pub Box<dyn expressions::Expression>);
-impl expressions::Expression for Local {}
+pub enum Local {
+    /// `local namelist [‘=’ explist]`
+    Variables(Box<dyn expressions::Expression>),
+    /// `local function Name funcbody`. The name is declared before the closure is created,
+    /// so the function can call itself
+    Function {
+        name: String,
+        closure: Box<dyn expressions::Expression>,
+    },
+}
+impl expressions::Expression for Local {
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for Local {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Local::Variables(assignment) => f.debug_tuple("Local").field(assignment).finish(),
+            Local::Function { name, closure } => f
+                .debug_struct("LocalFunction")
+                .field("name", name)
+                .field("closure", closure)
+                .finish(),
+        }
+    }
+}
 
 impl Local {
     pub fn new(stack: &mut stack::Stack) {
-        let (expression, _local) = stack_unpack!(stack, single, single);
+        let assignment = stack.pop_single();
 
-        stack.push_single(Box::new(Local(expression)))
+        stack.push_single(Box::new(Local::Variables(assignment)))
     }
 }
@@ -15,3 +15,21 @@ impl Expressions {
         stack.push_single(Box::new(Expressions(tail)));
     }
 }
+
+/// Expression in parentheses. Parentheses truncate multiple values to a single value
+#[derive(Debug)]
+pub struct Parentheses(pub Box<dyn expressions::Expression>);
+impl expressions::Expression for Parentheses {}
+
+impl Parentheses {
+    /// ‘(’ exp ‘)’. Only expressions with multiple values need a wrapper
+    pub fn new(stack: &mut stack::Stack) {
+        let (_rb, expression, _lb) = stack_unpack!(stack, single, single, single);
+
+        if expression.is_multi() {
+            stack.push_single(Box::new(Parentheses(expression)))
+        } else {
+            stack.push_single(expression)
+        }
+    }
+}
@@ -1,10 +1,9 @@
-use crate::ast::expressions::{self, primitives, statements, tables, variables};
-use crate::ast::stack;
-use crate::interpreter::cache;
+use crate::ast::expressions::{self, blocks, primitives, statements, tables, variables};
+use crate::ast::span::Span;
+use crate::ast::{parser, rules, stack};
 
 use std::collections::VecDeque;
 use std::rc::Rc;
-use std::cell::RefCell;
 
 #[derive(Debug)]
 pub struct Closure {
@@ -21,15 +20,28 @@ impl Closure {
     // ‘(’ [parlist] ‘)’ block end
     pub fn new(stack: &mut stack::Stack) {
         // We've remove braces before
-        let (_end, body, ellipsis, params, _function) =
+        let (end, body, ellipsis, params, function) =
             stack_unpack!(stack, single, single, optional, repetition, single);
 
         stack.push_single(Box::new(Closure {
             params,
             varargs: ellipsis.is_some(),
-            body: Rc::new(body),
+            body: Closure::body(function.as_ref(), body, end.as_ref()),
         }));
     }
+
+    /// Function body spans the whole definition from `function` to `end`. Anonymous functions are
+    /// named by the definition position in tracebacks
+    fn body(
+        function: &dyn expressions::Expression,
+        body: Box<dyn expressions::Expression>,
+        end: &dyn expressions::Expression,
+    ) -> Rc<Box<dyn expressions::Expression>> {
+        match Span::merge_options(function.span().cloned(), end.span().cloned()) {
+            Some(span) => Rc::new(Box::new(expressions::Spanned { span, expression: body })),
+            None => Rc::new(body),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -40,7 +52,7 @@ pub struct Function;
 impl Function {
     pub fn new(stack: &mut stack::Stack) {
         // This looks like a lot of terminals, but we need them to desugar method
-        let (_end, body, ellipsis, mut params, methodname, mut object, _function) =
+        let (end, body, ellipsis, mut params, methodname, mut object, function) =
             stack_unpack!(stack, single, single, optional, repetition, optional, single, single);
 
         // Check if have method name. If so, we make another indexing and add `self` argument
@@ -49,17 +61,16 @@ impl Function {
             object = Box::new(tables::Indexing {
                 object,
                 index: method,
-                cache: RefCell::new(cache::Cache::default()),
             });
 
             // Prepend `self` parameter to the parameters
-            params.push_front(Box::new(primitives::String("self".to_string())));
+            params.push_front(Box::new(primitives::String("self".into())));
         }
 
         let closure = Box::new(Closure {
             params,
             varargs: ellipsis.is_some(),
-            body: Rc::new(body),
+            body: Closure::body(function.as_ref(), body, end.as_ref()),
         }) as Box<dyn expressions::Expression>;
 
         stack.push_single(Box::new(variables::Assignment {
@@ -67,6 +78,23 @@ impl Function {
             explist: vec![closure].into(),
         }));
     }
+
+    /// `local function Name funcbody` declares a local variable instead of assigning the closure
+    pub fn new_local(stack: &mut stack::Stack) {
+        let (end, body, ellipsis, params, name, function) =
+            stack_unpack!(stack, single, single, optional, repetition, single, single);
+
+        let closure = Box::new(Closure {
+            params,
+            varargs: ellipsis.is_some(),
+            body: Closure::body(function.as_ref(), body, end.as_ref()),
+        });
+
+        stack.push_single(Box::new(blocks::Local::Function {
+            name: name.name().unwrap_or_default().to_string(),
+            closure,
+        }));
+    }
 }
 
 #[derive(Debug)]
@@ -84,6 +112,7 @@ impl FunctionParameters {
     /// This is ellipsis after varargs. Accoridng to grammar we can get here in two ways:
     /// - after namelist;
     /// - after another ellipsis.
+    ///
     /// Second one is invalid. So we check if we have repetitions on top. And later construct parameters itself.
     pub fn new_namelist_varargs(stack: &mut stack::Stack) {
         // Pop ellipsis and comma
@@ -129,34 +158,69 @@ impl FunctionParameters {
     }
 }
 
-#[derive(Debug)]
 pub struct Funcall {
     pub object: Box<dyn expressions::Expression>,
     pub args: VecDeque<Box<dyn expressions::Expression>>,
     pub method: Option<Box<dyn expressions::Expression>>,
+    /// Span of the call arguments. Used to report position of the call
+    pub span: Span,
+}
+impl expressions::Expression for Funcall {
+    fn is_funcall(&self) -> bool {
+        true
+    }
+
+    fn is_multi(&self) -> bool {
+        true
+    }
 }
-impl expressions::Expression for Funcall {}
 
 impl Funcall {
-    pub fn new(stack: &mut stack::Stack) {
+    /// Function call position is the position of arguments. Call expression spans the called object too
+    /// args
+    pub fn rule(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+        let mark = parser.mark();
+
+        if !rules::args(parser, stack) {
+            return false;
+        }
+
         let (args, object) = stack_unpack!(stack, repetition, single);
+        let span = parser.span(&mark).expect("Arguments consume tokens");
 
-        stack.push_single(Box::new(Funcall {
+        Funcall::push(stack, Funcall {
             object,
             args,
             method: None,
-        }))
+            span,
+        });
+        true
     }
 
-    pub fn new_self(stack: &mut stack::Stack) {
+    /// ‘:’ Name args
+    pub fn rule_self(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+        let mark = parser.mark();
+
+        if !rules::method_args(parser, stack) {
+            return false;
+        }
+
         let (args, method, _colon, object) =
             stack_unpack!(stack, repetition, single, single, single);
+        let span = parser.span(&mark).expect("Arguments consume tokens");
 
-        stack.push_single(Box::new(Funcall {
+        Funcall::push(stack, Funcall {
             object,
             args,
             method: Some(method),
-        }))
+            span,
+        });
+        true
+    }
+
+    fn push(stack: &mut stack::Stack, funcall: Funcall) {
+        let span = Span::merge_options(funcall.object.span().cloned(), Some(funcall.span.clone()));
+        stack.push_single(expressions::Spanned::wrap(Box::new(funcall), span));
     }
 
     pub fn new_args(stack: &mut stack::Stack) {
@@ -173,3 +237,14 @@ impl Funcall {
         }
     }
 }
+
+/// Debug doesn't print the span, because it doesn't affect program structure
+impl ::std::fmt::Debug for Funcall {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("Funcall")
+            .field("object", &self.object)
+            .field("args", &self.args)
+            .field("method", &self.method)
+            .finish()
+    }
+}
@@ -1,12 +1,17 @@
 use crate::ast::expressions;
 use crate::ast::stack;
 
-use crate::interpreter;
-
 #[derive(Debug)]
 pub struct Label(pub Box<dyn expressions::Expression>);
-impl interpreter::Eval for Label {}
-impl expressions::Expression for Label {}
+impl expressions::Expression for Label {
+    fn label(&self) -> Option<&str> {
+        self.0.name()
+    }
+
+    fn is_void(&self) -> bool {
+        true
+    }
+}
 
 impl Label {
     pub fn new(stack: &mut stack::Stack) {
@@ -17,7 +22,6 @@ impl Label {
 
 #[derive(Debug)]
 pub struct Goto(pub Box<dyn expressions::Expression>);
-impl interpreter::Eval for Goto {}
 impl expressions::Expression for Goto {}
 
 impl Goto {
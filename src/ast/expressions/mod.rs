@@ -1,4 +1,5 @@
 use crate::ast::lexer::tokens;
+use crate::ast::span::Span;
 
 #[macro_use]
 pub mod utils;
@@ -21,6 +22,56 @@ pub trait Expression: Debug + interpreter::Eval {
     fn clone(&self) -> Box<dyn Expression> {
         panic!("Trying to clone expression, which can't be cloned")
     }
+
+    /// Identifier name if expression is a name. Used to get names without evaluation
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Expression can be used as an assignment target: variable or table field
+    fn is_var(&self) -> bool {
+        false
+    }
+
+    /// Expression is a function call. Only calls can be used as expression statements
+    fn is_funcall(&self) -> bool {
+        false
+    }
+
+    /// Expression may produce multiple values: function calls and `...`
+    fn is_multi(&self) -> bool {
+        false
+    }
+
+    /// Label name if expression is a `::Name::` statement. Used by blocks to resolve `goto` jumps
+    fn label(&self) -> Option<&str> {
+        None
+    }
+
+    /// Statement introduces new local variables. Used to check if `goto` jumps into the scope of a local
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// Statement does nothing (labels and semicolons). Label followed by void statements is at the end of the block
+    fn is_void(&self) -> bool {
+        false
+    }
+
+    /// Value of the string literal. Used to describe `t.name` fields in error messages
+    fn string_literal(&self) -> Option<&str> {
+        None
+    }
+
+    /// Field name if expression is a table field with a constant name, i.e. `t.name` or `t["name"]`
+    fn field_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Position of the expression in the source code
+    fn span(&self) -> Option<&Span> {
+        None
+    }
 }
 
 impl PartialEq for Box<dyn Expression> {
@@ -34,4 +85,81 @@ impl Eq for Box<dyn Expression> {}
 // Struct for debugging. Wraps terminal. Basically Noop
 #[derive(Debug)]
 pub struct Terminal(pub tokens::Keyword);
-impl Expression for Terminal {}
+impl Expression for Terminal {
+    fn is_void(&self) -> bool {
+        self.0 == tokens::Keyword::SEMICOLONS
+    }
+}
+
+/// Expression with its position in the source code. Wrapper is transparent for the parser and the interpreter:
+/// it delegates everything to the wrapped expression and only adds position to runtime errors
+pub struct Spanned {
+    pub span: Span,
+    pub expression: Box<dyn Expression>,
+}
+
+impl Spanned {
+    /// Wrap expression, unless it already has a span or the span is unknown
+    pub fn wrap(expression: Box<dyn Expression>, span: Option<Span>) -> Box<dyn Expression> {
+        match span {
+            Some(span) if expression.span().is_none() => Box::new(Spanned { span, expression }),
+            _ => expression,
+        }
+    }
+}
+
+impl Expression for Spanned {
+    fn clone(&self) -> Box<dyn Expression> {
+        Box::new(Spanned {
+            span: self.span.clone(),
+            expression: self.expression.clone(),
+        })
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.expression.name()
+    }
+
+    fn is_var(&self) -> bool {
+        self.expression.is_var()
+    }
+
+    fn is_funcall(&self) -> bool {
+        self.expression.is_funcall()
+    }
+
+    fn is_multi(&self) -> bool {
+        self.expression.is_multi()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.expression.label()
+    }
+
+    fn is_local(&self) -> bool {
+        self.expression.is_local()
+    }
+
+    fn is_void(&self) -> bool {
+        self.expression.is_void()
+    }
+
+    fn string_literal(&self) -> Option<&str> {
+        self.expression.string_literal()
+    }
+
+    fn field_name(&self) -> Option<&str> {
+        self.expression.field_name()
+    }
+
+    fn span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+}
+
+/// Debug doesn't print the span, because it doesn't affect program structure
+impl Debug for Spanned {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        self.expression.fmt(f)
+    }
+}
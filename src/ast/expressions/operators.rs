@@ -4,6 +4,7 @@ use crate::ast::expressions;
 use crate::ast::lexer::tokens::{self, Keyword};
 use crate::ast::parser;
 use crate::ast::rules;
+use crate::ast::span::Span;
 use crate::ast::stack;
 
 const DEBUG: bool = false;
@@ -42,12 +43,11 @@ impl Binop {
             return false;
         }
 
-        Binop::prec_rule(parser, stack, 0);
-        true
+        Binop::prec_rule(parser, stack, 0)
     }
 
     // https://en.wikipedia.org/wiki/Operator-precedence_parser
-    pub fn prec_rule(parser: &mut parser::Parser, stack: &mut stack::Stack, precedence: usize) {
+    pub fn prec_rule(parser: &mut parser::Parser, stack: &mut stack::Stack, precedence: usize) -> bool {
         // or
         // and
         // <     >     <=    >=    ~=    ==
@@ -91,7 +91,7 @@ impl Binop {
         // while lookahead is a binary operator whose precedence is >= min_precedence
         while let Some(op) = parser.peek().and_then(|token| token.keyword()) {
             if !ops_prec_table.contains_key(&op) || ops_prec_table[&op] < precedence {
-                return;
+                return true;
             }
 
             // op := lookahead
@@ -100,10 +100,8 @@ impl Binop {
 
             // rhs := parse_primary ()
             if !rules::exp_prefix(parser, stack) {
-                panic!(format!(
-                    "Expecter expressiion after binary operator, got {:?}",
-                    parser.peek()
-                ))
+                parser.expected_error("expression");
+                return false;
             }
 
             // lookahead := peek next token
@@ -117,13 +115,19 @@ impl Binop {
                 }
                 // rhs := parse_expression_1 (rhs, lookahead's precedence)
                 // lookahead := peek next token
-                Binop::prec_rule(parser, stack, ops_prec_table[&op2]);
+                if !Binop::prec_rule(parser, stack, ops_prec_table[&op2]) {
+                    return false;
+                }
             }
             let (right_expression, left_expression) = stack_unpack!(stack, single, single);
 
             // lhs := the result of applying op with operands lhs and rhs
-            stack.push_single(Box::new(Binop(op, left_expression, right_expression)));
+            let span = Span::merge_options(left_expression.span().cloned(), right_expression.span().cloned());
+            let binop = Box::new(Binop(op, left_expression, right_expression));
+            stack.push_single(expressions::Spanned::wrap(binop, span));
         }
+
+        true
     }
 }
 
@@ -145,18 +149,20 @@ impl Unop {
                     return false;
                 }
 
+                let mark = parser.mark();
                 parser.shift();
 
                 if rules::exp_prefix(parser, stack) {
                     let expression = stack.pop_single();
 
-                    stack.push_single(Box::new(Unop(keyword, expression)));
+                    stack.push_single(expressions::Spanned::wrap(
+                        Box::new(Unop(keyword, expression)),
+                        parser.span(&mark),
+                    ));
                     return true;
                 } else {
-                    panic!(format!(
-                        "Expecter expressiion after unary operator, got {:?}",
-                        parser.peek()
-                    ))
+                    parser.expected_error("expression");
+                    return false;
                 }
             }
         }
@@ -2,8 +2,7 @@ use crate::ast::expressions;
 use crate::ast::lexer::tokens;
 use crate::ast::parser;
 use crate::ast::stack;
-
-use std::string::String as StdString;
+use crate::utils;
 
 #[derive(Debug, Clone)]
 pub struct Nil;
@@ -31,23 +30,30 @@ impl expressions::Expression for Number {}
 
 impl Number {
     pub fn rule(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
-        if let Some(tokens::Token {
-            token: tokens::TokenType::Number(number),
-            ..
-        }) = parser.peek().cloned()
-        {
-            parser.shift();
-            stack.push_single(Box::new(Number(number)));
-            true
-        } else {
-            false
-        }
+        let number: Box<dyn expressions::Expression> = match parser.peek().map(|token| &token.token) {
+            Some(tokens::TokenType::Number(number)) => Box::new(Number(*number)),
+            Some(tokens::TokenType::Integer(integer)) => Box::new(Integer(*integer)),
+            _ => return false,
+        };
+
+        parser.shift();
+        stack.push_single(number);
+        true
     }
 }
 
+/// Integer numeral. Parsed by `Number::rule`
 #[derive(Debug, Clone)]
-pub struct String(pub StdString);
-impl expressions::Expression for String {}
+pub struct Integer(pub i64);
+impl expressions::Expression for Integer {}
+
+#[derive(Debug, Clone)]
+pub struct String(pub utils::ByteString);
+impl expressions::Expression for String {
+    fn string_literal(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+}
 
 impl String {
     pub fn rule(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
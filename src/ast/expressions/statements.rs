@@ -9,7 +9,11 @@ pub enum Statement {
     Ellipsis,
     Return(Option<Box<dyn expressions::Expression>>),
 }
-impl expressions::Expression for Statement {}
+impl expressions::Expression for Statement {
+    fn is_multi(&self) -> bool {
+        matches!(self, Statement::Ellipsis)
+    }
+}
 
 impl Statement {
     make_keyword_rule![ellipsis, (tokens::Keyword::DOT3, Statement::Ellipsis)];
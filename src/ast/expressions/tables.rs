@@ -1,7 +1,4 @@
 use std::collections::VecDeque;
-use std::cell::RefCell;
-
-use crate::interpreter::cache;
 
 use crate::ast::expressions::{self, primitives};
 use crate::ast::lexer::tokens;
@@ -12,10 +9,16 @@ use crate::ast::stack;
 pub struct Indexing {
     pub object: Box<dyn expressions::Expression>,
     pub index: Box<dyn expressions::Expression>,
-    /// We need interior mutability to update cache
-    pub cache: RefCell<cache::Cache>
 }
-impl expressions::Expression for Indexing {}
+impl expressions::Expression for Indexing {
+    fn is_var(&self) -> bool {
+        true
+    }
+
+    fn field_name(&self) -> Option<&str> {
+        self.index.string_literal()
+    }
+}
 
 impl ::std::fmt::Debug for Indexing {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
@@ -42,14 +45,14 @@ impl Indexing {
     pub fn new(stack: &mut stack::Stack) {
         let (index, object) = stack_unpack!(stack, single, single);
 
-        stack.push_single(Box::new(Indexing { object, index, cache: RefCell::new(cache::Cache::default()) }));
+        stack.push_single(Box::new(Indexing { object, index }));
     }
 
     pub fn new_indexing_chain(stack: &mut stack::Stack) {
         let (chain, mut object) = stack_unpack!(stack, repetition, single);
 
         for index in chain.into_iter() {
-            object = Box::new(Indexing { object, index, cache: RefCell::new(cache::Cache::default()) })
+            object = Box::new(Indexing { object, index })
         }
 
         stack.push_single(object)
@@ -64,18 +67,42 @@ pub struct TableField {
 impl expressions::Expression for TableField {}
 
 impl TableField {
-    pub fn name_rule(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
-        if let Some(tokens::Token {
-            token: tokens::TokenType::Id(string),
-            ..
-        }) = parser.peek().cloned()
-        {
+    /// Both `Name = exp` and `exp` fields may start with a name, so we parse expression first
+    /// and then decide by the next token, what field we have.
+    /// field ::= Name ‘=’ exp | exp
+    pub fn rule_name_or_value(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+        if !rules::exp(parser, stack) {
+            return false;
+        }
+
+        if let Some(tokens::Keyword::ASSIGN) = parser.peek().and_then(|token| token.keyword()) {
+            let name = stack.pop_single();
+            let key = match name.name() {
+                Some(name) => primitives::String(name.into()),
+                None => {
+                    // Only names may be keys of `key = value` fields
+                    parser.expected_error("'}'");
+                    return false;
+                }
+            };
             parser.shift();
-            stack.push_single(Box::new(primitives::String(string)));
-            true
+
+            if !rules::exp(parser, stack) {
+                parser.expected_error("expression");
+                return false;
+            }
+
+            let value = stack.pop_single();
+            stack.push_single(Box::new(TableField {
+                key: Some(Box::new(key)),
+                value,
+            }))
         } else {
-            false
+            let value = stack.pop_single();
+            stack.push_single(Box::new(TableField { key: None, value }))
         }
+
+        true
     }
 
     // terminal!(Keyword::LSBRACKET), exp, terminal!(Keyword::RSBRACKET), terminal!(Keyword::EQUAL), exp
@@ -89,23 +116,6 @@ impl TableField {
         }))
     }
 
-    // variables::Id::rule, terminal!(Keyword::EQUAL), exp
-    pub fn new_object_index(stack: &mut stack::Stack) {
-        let (value, _assign, key) = stack_unpack!(stack, single, single, single);
-
-        stack.push_single(Box::new(TableField {
-            key: Some(key),
-            value,
-        }))
-    }
-
-    // exp
-    pub fn new_value(stack: &mut stack::Stack) {
-        let value = stack.pop_single();
-
-        stack.push_single(Box::new(TableField { key: None, value }))
-    }
-
     /// Sequence of fields. We either first field or consequential
     pub fn new_list_name(stack: &mut stack::Stack) {
         let field = stack.pop_single();
@@ -18,7 +18,15 @@ pub struct Id {
     /// We need interior mutability to update cache
     pub cache: RefCell<cache::Cache>
 }
-impl expressions::Expression for Id {}
+impl expressions::Expression for Id {
+    fn name(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn is_var(&self) -> bool {
+        true
+    }
+}
 
 impl Id {
     pub fn rule(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
@@ -31,6 +39,7 @@ impl Id {
             stack.push_single(Box::new(Id {id, cache: RefCell::new(cache::Cache::default())}));
             true
         } else {
+            parser.set_expected(parser::Expected::Rule("<name>"));
             false
         }
     }
@@ -42,9 +51,10 @@ impl Id {
         }) = parser.peek().cloned()
         {
             parser.shift();
-            stack.push_single(Box::new(expressions::primitives::String(string)));
+            stack.push_single(Box::new(expressions::primitives::String(string.into())));
             true
         } else {
+            parser.set_expected(parser::Expected::Rule("<name>"));
             false
         }
     }
@@ -72,10 +82,60 @@ pub struct Assignment {
 impl expressions::Expression for Assignment {}
 
 impl Assignment {
-    pub fn new(stack: &mut stack::Stack) {
-        let (explist, _assignment, varlist) = stack_unpack!(stack, repetition, single, repetition);
+    /// Both function call and assignment statements start with a prefix expression.
+    /// We parse prefix expression first and then decide by the next token, what statement we have.
+    /// exprstat ::= functioncall | varlist ‘=’ explist
+    pub fn rule_statement(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+        if !rules::prefixexp(parser, stack) {
+            return false;
+        }
 
-        stack.push_single(Box::new(Assignment { varlist, explist }))
+        match parser.peek().and_then(|token| token.keyword()) {
+            Some(Keyword::ASSIGN) | Some(Keyword::COMMA) => {
+                let mut varlist = VecDeque::new();
+                varlist.push_back(stack.pop_single());
+
+                while let Some(Keyword::COMMA) = parser.peek().and_then(|token| token.keyword()) {
+                    parser.shift();
+
+                    if !rules::prefixexp(parser, stack) {
+                        parser.expected_error("variable");
+                        return false;
+                    }
+                    varlist.push_back(stack.pop_single());
+                }
+
+                if let Some(var) = varlist.iter().find(|var| !var.is_var()) {
+                    parser.syntax_error(&format!("Cannot assign to {:?}", var));
+                    return false;
+                }
+
+                if let Some(Keyword::ASSIGN) = parser.peek().and_then(|token| token.keyword()) {
+                    parser.shift();
+                } else {
+                    parser.expected_error("'='");
+                    return false;
+                }
+
+                if !rules::explist(parser, stack) {
+                    parser.expected_error("expression");
+                    return false;
+                }
+
+                let explist = stack.pop_repetition();
+                stack.push_single(Box::new(Assignment { varlist, explist }))
+            }
+            _ => {
+                if let stack::Element::Single(expression) = stack.peek() {
+                    if !expression.is_funcall() {
+                        parser.syntax_error("syntax error");
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
     }
 
     pub fn rule_local(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
@@ -84,6 +144,7 @@ impl Assignment {
             parser.shift();
 
             if !rules::explist(parser, stack) {
+                parser.expected_error("expression");
                 return false;
             }
 
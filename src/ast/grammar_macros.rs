@@ -27,21 +27,36 @@ macro_rules! and {
     [($($parse_funcs: expr),+) => $nandler_func: expr] => {
         |parser: &mut parser::Parser, stack: &mut stack::Stack| -> bool {
             let mut entered = false; // If we already accepted first token
+            let start = parser.mark();
 
-            ($(if $parse_funcs(parser, stack) {
+            $(let mark = parser.mark(); parser.clear_expected(); if $parse_funcs(parser, stack) {
                 entered = true;
+                // Expressions, pushed by the nested rule, get span of consumed tokens
+                stack.set_span(parser.span(&mark));
                 debug_parser!("And statement rule {} accepted expression {:?}. Parser state {:?}", stringify!($parse_funcs), stack.peek(), parser);
             } else {
                 if entered {
-                    panic!("TODO: Custom error message. Unexpected token: {:?}. Rule: {:?}", parser.peek(), stringify!($parse_funcs));
+                    // Failed rule described what it expected
+                    parser.sequence_error();
                 }
 
                 debug_parser!("And statement rule {} didn't accept parser input {:?}", stringify!($parse_funcs), parser);
                 return false
-            }), +);
+            })+
 
             let _ = entered; // To suppress unused boolean
+
+            // Nested rules may fail after error, leaving stack in inconsistent state
+            if parser.error().is_some() {
+                return false
+            }
+
+            // Handler builds new expression from stack elements. Its span covers them and
+            // consumed tokens, because expression may include elements, pushed before this rule (i.e. `a.b`)
+            stack.take_popped_span();
             $nandler_func(stack);
+            let span = $crate::ast::span::Span::merge_options(parser.span(&start), stack.take_popped_span());
+            stack.set_span(span);
             debug_parser!("And handling function {:?} successfully handled expression and returned {:?}",
                 stringify!($nandler_func),
                 stack.peek());
@@ -102,13 +117,79 @@ macro_rules! repetition {
     }
 }
 
+/// Error recovery for the recovering parser. If the rule fails with syntax error, partially parsed
+/// expressions are removed from the stack, the error is recorded and parser skips to the next statement.
+/// Macro has two modifications:
+/// - recover!(rule) Fails after recovery, as if the rule didn't match;
+/// - recover!(rule, void) Pushes void statement after recovery, so repetition of statements continues
+#[macro_export]
+macro_rules! recover {
+    ($parse_func:expr, void) => {
+        |parser: &mut parser::Parser, stack: &mut stack::Stack| -> bool {
+            let (start, len) = (parser.mark(), stack.len());
+
+            if $parse_func(parser, stack) {
+                return true
+            }
+
+            if !parser.recover(&start) {
+                return false
+            }
+
+            debug_parser!("Recovered after rule {} failure. Parser state {:?}", stringify!($parse_func), parser);
+            stack.truncate(len);
+            stack.push_single(Box::new($crate::ast::expressions::Terminal($crate::ast::lexer::tokens::Keyword::SEMICOLONS)));
+            true
+        }
+    };
+    ($parse_func:expr) => {
+        |parser: &mut parser::Parser, stack: &mut stack::Stack| -> bool {
+            let (start, len) = (parser.mark(), stack.len());
+
+            if $parse_func(parser, stack) {
+                return true
+            }
+
+            if parser.recover(&start) {
+                debug_parser!("Recovered after rule {} failure. Parser state {:?}", stringify!($parse_func), parser);
+                stack.truncate(len);
+            }
+
+            false
+        }
+    }
+}
+
+/// Macro has two modifications:
+/// - rule!(name, rule) Failed rule is described by the failed nested rule;
+/// - rule!(name, "description", rule) Failed rule is described as a whole in syntax errors, i.e. "expression"
 #[macro_export]
 macro_rules! rule {
+    ($name: ident, $description: literal, $parse_func:expr) => {
+        pub fn $name(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+            debug_parser!("Executing rule {}", stringify!($name));
+            let mark = parser.mark();
+
+            if $parse_func(parser, stack) {
+                stack.set_span(parser.span(&mark));
+                true
+            } else {
+                parser.set_expected(parser::Expected::Rule($description));
+                false
+            }
+        }
+    };
     ($name: ident, $parse_func:expr) => {
         pub fn $name(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
             debug_parser!("Executing rule {}", stringify!($name));
+            let mark = parser.mark();
 
-            $parse_func(parser, stack)
+            if $parse_func(parser, stack) {
+                stack.set_span(parser.span(&mark));
+                true
+            } else {
+                false
+            }
         }
     };
 }
@@ -117,23 +198,20 @@ macro_rules! rule {
 macro_rules! terminal {
     ($keyword: expr) => {
         |parser: &mut parser::Parser, stack: &mut stack::Stack| -> bool {
-            if let Some(token) = parser.peek().cloned() {
-                match token.keyword() {
-                    Some(keyword) => {
-                        if keyword == $keyword {
-                            parser.shift();
-                            debug_parser!("Accepted keyword {:?}", keyword);
-                            stack.push_single(Box::new(crate::ast::expressions::Terminal(keyword)));
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    _ => false
+            match parser.peek().and_then(|token| token.keyword()) {
+                Some(keyword) if keyword == $keyword => {
+                    let mark = parser.mark();
+                    parser.shift();
+                    debug_parser!("Accepted keyword {:?}", keyword);
+                    stack.push_single(Box::new($crate::ast::expressions::Terminal(keyword)));
+                    stack.set_span(parser.span(&mark));
+                    true
+                }
+                _ => {
+                    parser.set_expected(parser::Expected::Keyword($keyword));
+                    false
                 }
-            } else {
-                false
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,9 @@
+pub mod numerals;
 pub mod tokens;
 
 use self::tokens::{get_operator_table, get_token_table, Keyword, Token, TokenType};
 
+use crate::error;
 use crate::utils::AsExclusiveTakeWhile;
 use std::collections::HashMap;
 use std::fmt;
@@ -18,19 +20,25 @@ pub struct Lexer {
     // Line and column
     row: usize,
     column: usize,
+    /// Line and column of the first character of the last token
+    token_start: (usize, usize),
 }
 
 impl Lexer {
     pub fn new(source_code: String) -> Self {
         let chars = Vec::<char>::from_iter(source_code.chars());
         let peekable = chars.into_iter().peekable();
-        Lexer {
+        let mut lexer = Lexer {
             char_iterator: peekable,
             token_table: get_token_table(),
             operator_table: get_operator_table(),
             row: 1,
             column: 0,
-        }
+            token_start: (1, 1),
+        };
+
+        lexer.skip_shebang();
+        lexer
     }
 
     fn advance_pos(&mut self, n: usize) {
@@ -42,35 +50,166 @@ impl Lexer {
         self.column = 0;
     }
 
-    fn parse_next_token(&mut self) -> Option<Token> {
-        if let Some(&chr) = self.char_iterator.peek() {
-            if chr.is_alphabetic() || chr == '_' {
-                Some(Token::new(self.parse_identifier(), self.row, self.column))
-            } else if chr.is_numeric() {
-                Some(Token::new(self.parse_number(), self.row, self.column))
-            } else if chr == '"' {
-                Some(Token::new(self.parse_string(), self.row, self.column))
-            } else if chr == '\n' {
-                self.char_iterator.next();
-                self.newline();
-                self.parse_next_token()
-            } else if chr == ' ' || chr == '\t' {
+    /// Current line and column
+    pub fn position(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// Line and column, where the last token starts. Token itself keeps position of its last character
+    pub fn token_start(&self) -> (usize, usize) {
+        self.token_start
+    }
+
+    fn error(&self, message: String) -> error::Error {
+        error::Error::lexical(message, self.row, self.column)
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let chr = self.char_iterator.next();
+        if chr.is_some() {
+            self.advance_pos(1);
+        }
+        chr
+    }
+
+    /// Look ahead `n` characters without consuming them
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.char_iterator.clone().nth(n)
+    }
+
+    /// Consume line break. `\r\n` and `\n\r` sequences are single line breaks
+    fn skip_newline(&mut self) {
+        let first = self.char_iterator.next();
+        if let Some(&second) = self.char_iterator.peek() {
+            if (second == '\n' || second == '\r') && Some(second) != first {
                 self.char_iterator.next();
-                self.advance_pos(1);
-                self.parse_next_token()
-            } else {
-                Some(Token::new(self.parse_operator(), self.row, self.column))
             }
+        }
+        self.newline();
+    }
+
+    /// Skip the first line, if it starts with `#!`
+    fn skip_shebang(&mut self) {
+        if self.peek_nth(0) == Some('#') && self.peek_nth(1) == Some('!') {
+            while !matches!(self.char_iterator.peek(), None | Some('\n') | Some('\r')) {
+                self.next_char();
+            }
+        }
+    }
+
+    fn parse_next_token(&mut self) -> Option<error::Result<Token>> {
+        loop {
+            let chr = *self.char_iterator.peek()?;
+            self.token_start = (self.row, self.column + 1);
+
+            let token = match chr {
+                '\n' | '\r' => {
+                    self.skip_newline();
+                    continue;
+                }
+                ' ' | '\t' | '\x0b' | '\x0c' => {
+                    self.next_char();
+                    continue;
+                }
+                '-' if self.peek_nth(1) == Some('-') => {
+                    if let Err(error) = self.skip_comment() {
+                        return Some(Err(error));
+                    }
+                    continue;
+                }
+                '"' | '\'' => self.parse_string(),
+                '[' if matches!(self.peek_nth(1), Some('[') | Some('=')) => self.parse_long_string(),
+                _ if chr.is_alphabetic() || chr == '_' => Ok(self.parse_identifier()),
+                _ if chr.is_ascii_digit() => self.parse_number(),
+                '.' if matches!(self.peek_nth(1), Some(chr) if chr.is_ascii_digit()) => self.parse_number(),
+                _ => self.parse_operator(),
+            };
+
+            return Some(token.map(|token| Token::new(token, self.row, self.column)));
+        }
+    }
+
+    /// Skip `-- line comment` or `--[==[ long comment ]==]`
+    fn skip_comment(&mut self) -> error::Result<()> {
+        let (row, column) = (self.row, self.column);
+        self.next_char();
+        self.next_char();
+
+        if let Some('[') = self.char_iterator.peek() {
+            if let Some(level) = self.long_bracket_level() {
+                return match self.read_long_string(level) {
+                    Some(_) => Ok(()),
+                    None => Err(error::Error::lexical(
+                        "Unmatched long comment brackets".to_string(),
+                        row,
+                        column,
+                    )),
+                };
+            }
+        }
+
+        while !matches!(self.char_iterator.peek(), None | Some('\n') | Some('\r')) {
+            self.next_char();
+        }
+
+        Ok(())
+    }
+
+    /// Consume opening long bracket `[==[` and return its level, which is the number of `=`.
+    /// If brackets are not opening long bracket, nothing is consumed
+    fn long_bracket_level(&mut self) -> Option<usize> {
+        let level = self.bracket_level('[')?;
+
+        for _ in 0..level + 2 {
+            self.next_char();
+        }
+        Some(level)
+    }
+
+    /// Level of the long bracket `[==[` or `]==]` at the current position, without consuming it
+    fn bracket_level(&self, bracket: char) -> Option<usize> {
+        let level = self.char_iterator.clone().skip(1).take_while(|chr| *chr == '=').count();
+
+        if self.peek_nth(level + 1) == Some(bracket) {
+            Some(level)
         } else {
             None
         }
     }
 
+    /// Read long string content until closing bracket of the `level`. Returns None at the end of input.
+    /// Line break right after the opening bracket is skipped, others are converted to `\n`
+    fn read_long_string(&mut self, level: usize) -> Option<String> {
+        let mut string = String::new();
+
+        if let Some('\n') | Some('\r') = self.char_iterator.peek() {
+            self.skip_newline();
+        }
+
+        loop {
+            let chr = *self.char_iterator.peek()?;
+
+            match chr {
+                '\n' | '\r' => {
+                    self.skip_newline();
+                    string.push('\n');
+                }
+                ']' if self.bracket_level(']') == Some(level) => {
+                    for _ in 0..level + 2 {
+                        self.next_char();
+                    }
+                    return Some(string);
+                }
+                _ => string.extend(self.next_char()),
+            }
+        }
+    }
+
     fn parse_identifier(&mut self) -> TokenType {
-        let id_chars = |chr: &char| chr.is_alphanumeric();
+        let id_chars = |chr: &char| chr.is_alphanumeric() || *chr == '_';
 
         let id: String = self.char_iterator.take_while_exclusive(id_chars).collect();
-        self.advance_pos(id.len());
+        self.advance_pos(id.chars().count());
 
         // If keyword map contains the keyword, return Token::Keyword
         // Else return a Token::Identifier
@@ -80,45 +219,257 @@ impl Lexer {
         }
     }
 
-    fn parse_string(&mut self) -> TokenType {
-        // Looking for the closing doublequote
-        let string_chars = |chr: &char| *chr != '"';
+    /// [[long string]] or [==[long string]==]
+    fn parse_long_string(&mut self) -> error::Result<TokenType> {
+        let (row, column) = (self.row, self.column);
 
-        // Skip starting doublequote
-        self.char_iterator.next();
+        let level = match self.long_bracket_level() {
+            Some(level) => level,
+            None => {
+                // Delimiter is skipped, so lexing may continue after the error
+                let error = self.error("Invalid long string delimiter".to_string());
+                self.next_char();
+                while self.char_iterator.peek() == Some(&'=') {
+                    self.next_char();
+                }
+                return Err(error);
+            }
+        };
 
-        let string: String = self
-            .char_iterator
-            .take_while_exclusive(string_chars)
-            .collect();
-        self.advance_pos(string.len() + 2); // With doublequotes
+        match self.read_long_string(level) {
+            Some(string) => Ok(TokenType::String(string.into())),
+            None => Err(error::Error::lexical(
+                "Unmatched long string brackets".to_string(),
+                row,
+                column,
+            )),
+        }
+    }
+
+    /// Single or double quoted string with escape sequences. Escapes produce arbitrary bytes,
+    /// which don't have to form valid UTF-8
+    fn parse_string(&mut self) -> error::Result<TokenType> {
+        // Unmatched quotes error points to the string start
+        let (row, column) = (self.row, self.column);
+
+        let quote = self.next_char().unwrap_or_default();
+        let mut bytes = Vec::new();
 
-        // Skip ending doublequote
-        if self.char_iterator.next().is_none() {
-            panic!("Unmatched double quotes at {}:{}", self.row, self.column)
+        loop {
+            let chr = match self.char_iterator.peek() {
+                Some(&chr) => chr,
+                None => {
+                    let quotes = if quote == '"' { "double" } else { "single" };
+                    let message = format!("Unmatched {} quotes", quotes);
+                    return Err(error::Error::lexical(message, row, column));
+                }
+            };
+
+            match chr {
+                '\n' | '\r' => return Err(self.error("Unfinished string".to_string())),
+                '\\' => {
+                    if let Err(error) = self.parse_escape(&mut bytes) {
+                        self.skip_string(quote);
+                        return Err(error);
+                    }
+                }
+                _ => {
+                    self.next_char();
+                    if chr == quote {
+                        break;
+                    }
+                    bytes.extend_from_slice(chr.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+            }
         }
 
-        TokenType::String(string)
+        Ok(TokenType::String(bytes.into()))
     }
 
-    fn parse_number(&mut self) -> TokenType {
-        let numeric_chars = |chr: &char| chr.is_numeric() || *chr == '.';
+    /// Skip the rest of the invalid string up to the closing quote, so lexing may continue after the error.
+    /// Unescaped line break ends the string too
+    fn skip_string(&mut self, quote: char) {
+        while let Some(&chr) = self.char_iterator.peek() {
+            match chr {
+                '\n' | '\r' => break,
+                '\\' => {
+                    self.next_char();
+                    if let Some('\n') | Some('\r') = self.char_iterator.peek() {
+                        self.skip_newline();
+                    } else {
+                        self.next_char();
+                    }
+                }
+                _ => {
+                    self.next_char();
+                    if chr == quote {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Escape sequence in a string, starting with the backslash
+    fn parse_escape(&mut self, bytes: &mut Vec<u8>) -> error::Result<()> {
+        self.next_char();
+
+        let chr = match self.char_iterator.peek() {
+            Some(&chr) => chr,
+            // Unmatched quotes are reported by the string parser
+            None => return Ok(()),
+        };
+
+        let byte = match chr {
+            'a' => b'\x07',
+            'b' => b'\x08',
+            'f' => b'\x0c',
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'v' => b'\x0b',
+            '\\' => b'\\',
+            '"' => b'"',
+            '\'' => b'\'',
+            '\n' | '\r' => {
+                self.skip_newline();
+                bytes.push(b'\n');
+                return Ok(());
+            }
+            // Skip following whitespaces, including line breaks
+            'z' => {
+                self.next_char();
+                while let Some(&chr) = self.char_iterator.peek() {
+                    match chr {
+                        '\n' | '\r' => self.skip_newline(),
+                        _ if chr.is_whitespace() => {
+                            self.next_char();
+                        }
+                        _ => break,
+                    }
+                }
+                return Ok(());
+            }
+            'x' => {
+                self.next_char();
+                let digits: String = self.char_iterator.clone().take(2).collect();
+
+                return match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.chars().all(|chr| chr.is_ascii_hexdigit()) => {
+                        self.next_char();
+                        self.next_char();
+                        bytes.push(byte);
+                        Ok(())
+                    }
+                    _ => Err(self.error("Hexadecimal digit expected in '\\x' escape".to_string())),
+                };
+            }
+            'u' => return self.parse_utf8_escape(bytes),
+            _ if chr.is_ascii_digit() => {
+                let digits: String = self
+                    .char_iterator
+                    .clone()
+                    .take(3)
+                    .take_while(|chr| chr.is_ascii_digit())
+                    .collect();
+                for _ in 0..digits.len() {
+                    self.next_char();
+                }
+
+                return match digits.parse::<u8>() {
+                    Ok(byte) => {
+                        bytes.push(byte);
+                        Ok(())
+                    }
+                    Err(_) => Err(self.error(format!("Decimal escape '\\{}' too large", digits))),
+                };
+            }
+            _ => return Err(self.error(format!("Invalid escape sequence '\\{}'", chr))),
+        };
 
-        // Looking for the end of the number
-        let number: String = self
+        self.next_char();
+        bytes.push(byte);
+        Ok(())
+    }
+
+    /// `\u{XXX}` escape. Value is encoded with UTF-8, extended up to 2^31 as in Lua 5.3
+    fn parse_utf8_escape(&mut self, bytes: &mut Vec<u8>) -> error::Result<()> {
+        self.next_char();
+        if self.next_char() != Some('{') {
+            return Err(self.error("Missing '{' in '\\u{xxxx}' escape".to_string()));
+        }
+
+        let digits: String = self
             .char_iterator
-            .take_while_exclusive(numeric_chars)
+            .take_while_exclusive(|chr| chr.is_ascii_hexdigit())
             .collect();
-        self.advance_pos(number.len());
+        self.advance_pos(digits.len());
+
+        let code = match u32::from_str_radix(&digits, 16) {
+            Ok(code) if code <= 0x7FFF_FFFF => code,
+            Ok(_) => return Err(self.error("UTF-8 value too large".to_string())),
+            Err(_) => return Err(self.error("Hexadecimal digit expected in '\\u' escape".to_string())),
+        };
+
+        if self.next_char() != Some('}') {
+            return Err(self.error("Missing '}' in '\\u{xxxx}' escape".to_string()));
+        }
+
+        if code < 0x80 {
+            bytes.push(code as u8);
+            return Ok(());
+        }
+
+        // Continuation bytes are filled from the lowest bits, while the first byte holds the rest
+        let mut continuation = Vec::new();
+        let mut code = code;
+        let mut first_byte_limit = 0x3f;
+        while code > first_byte_limit {
+            continuation.push(0x80 | (code & 0x3f) as u8);
+            code >>= 6;
+            first_byte_limit >>= 1;
+        }
 
-        TokenType::Number(
-            number
-                .parse::<f64>()
-                .unwrap_or_else(|_| panic!("Failed to parse number '{}'", number)),
-        )
+        let first_byte_mark = ((!first_byte_limit) << 1) as u8;
+        bytes.push(first_byte_mark | code as u8);
+        bytes.extend(continuation.into_iter().rev());
+
+        Ok(())
+    }
+
+    /// Numeral is read greedily, as the reference lexer does, and then validated.
+    /// So `3..2` or `0xfg` are malformed numbers, not sequences of tokens
+    fn parse_number(&mut self) -> error::Result<TokenType> {
+        let mut number = String::new();
+        let mut exponent_markers = ['e', 'E'];
+
+        if self.peek_nth(0) == Some('0') && matches!(self.peek_nth(1), Some('x') | Some('X')) {
+            number.extend(self.next_char());
+            number.extend(self.next_char());
+            exponent_markers = ['p', 'P'];
+        }
+
+        while let Some(&chr) = self.char_iterator.peek() {
+            if exponent_markers.contains(&chr) {
+                number.extend(self.next_char());
+                if let Some('+') | Some('-') = self.char_iterator.peek() {
+                    number.extend(self.next_char());
+                }
+            } else if chr.is_alphanumeric() || chr == '.' || chr == '_' {
+                number.extend(self.next_char());
+            } else {
+                break;
+            }
+        }
+
+        match numerals::parse(&number) {
+            Some(numerals::Numeral::Integer(integer)) => Ok(TokenType::Integer(integer)),
+            Some(numerals::Numeral::Float(float)) => Ok(TokenType::Number(float)),
+            None => Err(self.error(format!("Malformed number '{}'", number))),
+        }
     }
 
-    fn parse_operator(&mut self) -> TokenType {
+    fn parse_operator(&mut self) -> error::Result<TokenType> {
         // First we try longer operators then shorter, to avoid returning '>' instead of '>='
         // Lenghts are 3, 2, 1
         for n_operator in (1..4).rev() {
@@ -127,13 +478,18 @@ impl Lexer {
             if let Some(keyword) = self.operator_table.get(&n_character_operator).cloned() {
                 // Advance original iterator. Need to coolect, to power on lazy iterartor
                 let _ = self.char_iterator.by_ref().take(n_operator).count();
-                self.advance_pos(n_operator);
+                // Operator may be shorter than `n_operator` at the end of input
+                self.advance_pos(n_character_operator.chars().count());
 
-                return TokenType::Keyword(keyword.clone());
+                return Ok(TokenType::Keyword(keyword.clone()));
             }
         }
 
-        panic!("Can't parse token at {}:{}", self.row, self.column)
+        // Symbol is skipped, so lexing may continue after the error
+        let chr = self.char_iterator.peek().cloned().unwrap_or_default();
+        let error = self.error(format!("Unexpected symbol '{}'", chr));
+        self.next_char();
+        Err(error)
     }
 }
 
@@ -145,9 +501,9 @@ impl fmt::Debug for Lexer {
 
 // ----- Iterator trait implementation for the scanner -----
 impl Iterator for Lexer {
-    type Item = Token;
+    type Item = error::Result<Token>;
 
-    fn next(&mut self) -> Option<Token> {
+    fn next(&mut self) -> Option<error::Result<Token>> {
         self.parse_next_token()
     }
 }
@@ -0,0 +1,113 @@
+/// Value of a numeral. Integer numerals are kept apart from float ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Numeral {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Numeral {
+    pub fn as_float(self) -> f64 {
+        match self {
+            Numeral::Integer(integer) => integer as f64,
+            Numeral::Float(float) => float,
+        }
+    }
+}
+
+/// Parse Lua 5.3 numeral. The whole string must be a numeral without sign and spaces.
+/// Decimal integers, which don't fit into integer, become floats. Hexadecimal integers wrap around
+pub fn parse(string: &str) -> Option<Numeral> {
+    match string
+        .strip_prefix("0x")
+        .or_else(|| string.strip_prefix("0X"))
+    {
+        Some(hex) => parse_hex(hex),
+        None => parse_decimal(string),
+    }
+}
+
+/// Split `digits[.digits][exponent]` into mantissa and exponent parts. Mantissa must contain digits
+fn split_exponent<'a>(
+    string: &'a str,
+    markers: &[char],
+    is_digit: fn(char) -> bool,
+) -> Option<(&'a str, Option<&'a str>)> {
+    let (mantissa, exponent) = match string.find(markers) {
+        Some(position) => (&string[..position], Some(&string[position + 1..])),
+        None => (string, None),
+    };
+
+    let mut parts = mantissa.splitn(2, '.');
+    let integral = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let all_digits = |part: &str| part.chars().all(is_digit);
+
+    if integral.len() + fraction.len() == 0 || !all_digits(integral) || !all_digits(fraction) {
+        return None;
+    }
+
+    if let Some(exponent) = exponent {
+        let digits = exponent
+            .strip_prefix(|chr| chr == '+' || chr == '-')
+            .unwrap_or(exponent);
+        if digits.is_empty() || !digits.chars().all(|chr| chr.is_ascii_digit()) {
+            return None;
+        }
+    }
+
+    Some((mantissa, exponent))
+}
+
+fn parse_decimal(string: &str) -> Option<Numeral> {
+    let (mantissa, exponent) = split_exponent(string, &['e', 'E'], |chr| chr.is_ascii_digit())?;
+
+    if exponent.is_none() && !mantissa.contains('.') {
+        if let Ok(integer) = string.parse::<i64>() {
+            return Some(Numeral::Integer(integer));
+        }
+    }
+
+    string.parse::<f64>().ok().map(Numeral::Float)
+}
+
+fn parse_hex(string: &str) -> Option<Numeral> {
+    let (mantissa, exponent) = split_exponent(string, &['p', 'P'], |chr| chr.is_ascii_hexdigit())?;
+    let digit = |chr: char| chr.to_digit(16).unwrap_or_default();
+
+    if exponent.is_none() && !mantissa.contains('.') {
+        let integer = mantissa.chars().fold(0i64, |integer, chr| {
+            integer.wrapping_mul(16).wrapping_add(i64::from(digit(chr)))
+        });
+        return Some(Numeral::Integer(integer));
+    }
+
+    let mut value = 0f64;
+    // Binary exponent. Each fraction digit moves the point by 4 bits
+    let mut binary_exponent = 0i64;
+    let mut fraction = false;
+
+    for chr in mantissa.chars() {
+        if chr == '.' {
+            fraction = true;
+            continue;
+        }
+
+        value = value * 16f64 + f64::from(digit(chr));
+        if fraction {
+            binary_exponent -= 4;
+        }
+    }
+
+    if let Some(exponent) = exponent {
+        // Huge exponents overflow to infinity or zero anyway
+        let exponent = exponent.parse::<i64>().unwrap_or(if exponent.starts_with('-') {
+            i64::MIN / 2
+        } else {
+            i64::MAX / 2
+        });
+        binary_exponent = binary_exponent.saturating_add(exponent);
+    }
+
+    let binary_exponent = binary_exponent.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+    Some(Numeral::Float(value * 2f64.powi(binary_exponent)))
+}
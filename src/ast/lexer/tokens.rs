@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::utils;
+
 #[allow(dead_code)]
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 pub enum Keyword {
@@ -69,10 +71,10 @@ pub enum Keyword {
 impl Keyword {
     // unop ::= ‘-’ | not | ‘#’ | ‘~’
     pub fn is_unop(&self) -> bool {
-        match *self {
-            Keyword::MINUS | Keyword::NOT | Keyword::HASH | Keyword::TILDA => true,
-            _ => false,
-        }
+        matches!(
+            *self,
+            Keyword::MINUS | Keyword::NOT | Keyword::HASH | Keyword::TILDA
+        )
     }
 
     // binop ::=  ‘+’ | ‘-’ | ‘*’ | ‘/’ | ‘//’ | ‘^’ | ‘%’ |
@@ -80,7 +82,8 @@ impl Keyword {
     //            ‘<’ | ‘<=’ | ‘>’ | ‘>=’ | ‘==’ | ‘~=’ |
     //            and | or
     pub fn is_binop(&self) -> bool {
-        match *self {
+        matches!(
+            *self,
             Keyword::PLUS
             | Keyword::MINUS
             | Keyword::MUL
@@ -101,10 +104,56 @@ impl Keyword {
             | Keyword::EQ
             | Keyword::NEQ
             | Keyword::AND
-            | Keyword::OR => true,
-            _ => false,
+            | Keyword::OR
+        )
+    }
+
+    /// Change of the block nesting after the keyword. Blocks are opened by `do`, `then`, `function` and
+    /// `repeat`, and closed by `end` and `until`. `elseif` closes the block, opened by the previous `then`
+    pub fn nesting(&self) -> isize {
+        match *self {
+            Keyword::DO | Keyword::THEN | Keyword::FUNCTION | Keyword::REPEAT => 1,
+            Keyword::END | Keyword::UNTIL | Keyword::ELSEIF => -1,
+            _ => 0,
         }
     }
+
+    /// Keywords, where the parser may resume after a syntax error: statement starts and block ends
+    pub fn is_statement_boundary(&self) -> bool {
+        matches!(
+            *self,
+            Keyword::SEMICOLONS
+            | Keyword::PATH
+            | Keyword::BREAK
+            | Keyword::GOTO
+            | Keyword::DO
+            | Keyword::WHILE
+            | Keyword::REPEAT
+            | Keyword::IF
+            | Keyword::FOR
+            | Keyword::FUNCTION
+            | Keyword::LOCAL
+            | Keyword::RETURN
+            | Keyword::END
+            | Keyword::ELSE
+            | Keyword::ELSEIF
+            | Keyword::UNTIL
+        )
+    }
+}
+
+/// Keyword as it's written in the source code
+impl std::fmt::Display for Keyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let text = get_token_table()
+            .into_iter()
+            .chain(get_operator_table())
+            .find(|(_, keyword)| keyword == self)
+            .map(|(text, _)| text)
+            .expect("Every keyword has its text");
+
+        f.write_str(&text)
+    }
 }
 
 impl PartialEq<Token> for Keyword {
@@ -182,11 +231,28 @@ pub fn get_operator_table() -> HashMap<String, Keyword> {
 pub enum TokenType {
     Keyword(Keyword),
     Id(String),
-    String(String),
+    String(utils::ByteString),
+    /// Float numeral
     Number(f64),
+    /// Integer numeral
+    Integer(i64),
     None,
 }
 
+/// Token, as it's shown in syntax errors, i.e. `'end'` or `<eof>`
+impl std::fmt::Display for TokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TokenType::Keyword(keyword) => write!(f, "'{}'", keyword),
+            TokenType::Id(id) => write!(f, "'{}'", id),
+            TokenType::String(string) => write!(f, "'{:?}'", string),
+            TokenType::Number(number) => write!(f, "'{}'", number),
+            TokenType::Integer(integer) => write!(f, "'{}'", integer),
+            TokenType::None => write!(f, "<eof>"),
+        }
+    }
+}
+
 impl From<Keyword> for TokenType {
     fn from(keyword: Keyword) -> Self {
         TokenType::Keyword(keyword)
@@ -224,8 +290,8 @@ impl Token {
     }
 }
 
-impl Into<TokenType> for Token {
-    fn into(self) -> TokenType {
-        self.token.clone()
+impl From<Token> for TokenType {
+    fn from(token: Token) -> Self {
+        token.token
     }
 }
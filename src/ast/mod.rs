@@ -15,8 +15,12 @@ pub mod grammar_macros;
 pub mod expressions;
 pub mod parser;
 pub mod rules;
+pub mod span;
 
-use crate::interpreter::environment;
+use crate::error;
+use crate::interpreter::{environment, stdlib, types};
+use crate::utils;
+use std::collections::VecDeque;
 use std::fmt::{Debug, Error, Formatter};
 
 pub struct AST {
@@ -24,24 +28,88 @@ pub struct AST {
 }
 
 impl AST {
-    pub fn new(source_code: String) -> Self {
-        let mut parser = parser::Parser::new(source_code);
+    pub fn new(source_code: String) -> error::Result<Self> {
+        AST::with_name(source_code, span::DEFAULT_CHUNK_NAME)
+    }
+
+    /// Parse the source code of the chunk `name`. Runtime errors are reported as `name:line: message`
+    pub fn with_name(source_code: String, name: &str) -> error::Result<Self> {
+        let mut parser = parser::Parser::new(source_code).with_chunk_name(name);
         let mut stack = stack::Stack::default();
 
-        rules::chunk(&mut parser, &mut stack);
+        let parsed = rules::chunk(&mut parser, &mut stack);
 
-        AST {
-            top_expression: stack.pop_single(),
+        // Chunk must consume the whole input
+        if !parsed || parser.peek().is_some() {
+            parser.expected_error("statement");
         }
+
+        if let Some(error) = parser.take_error() {
+            return Err(error);
+        }
+
+        Ok(AST {
+            top_expression: stack.pop_single(),
+        })
     }
 
-    pub fn eval(&self) {
-        let mut env = crate::utils::Shared::new(environment::Environment::new(
+    /// Parse the source code, collecting all lexical and syntax errors instead of stopping at the first one.
+    /// Statements with errors are skipped, so the AST is partial if there are diagnostics
+    pub fn new_recovering(source_code: String) -> (Self, Vec<parser::Diagnostic>) {
+        let mut parser = parser::Parser::new_recovering(source_code);
+        let mut stack = stack::Stack::default();
+
+        rules::chunk_recovering(&mut parser, &mut stack);
+
+        let ast = AST {
+            top_expression: stack.pop_single(),
+        };
+        (ast, parser.take_diagnostics())
+    }
+
+    /// Evaluate the chunk in a new environment with the standard library loaded
+    pub fn eval(&self) -> error::Result<()> {
+        self.exec(&mut AST::environment(), VecDeque::new())?;
+        Ok(())
+    }
+
+    /// Root environment with the standard library loaded. Chunks executed in the same
+    /// environment share global variables
+    pub fn environment() -> utils::Shared<environment::Environment> {
+        let env = utils::Shared::new(environment::Environment::new(
             None,
             environment::BreakFlag::None,
         ));
+        stdlib::load(&mut env.borrow_mut());
+
+        env
+    }
+
+    /// Execute the chunk in the environment. Arguments are accessible with `...`.
+    /// Returns values of the chunk `return` statement
+    pub fn exec(
+        &self,
+        env: &mut utils::Shared<environment::Environment>,
+        args: VecDeque<types::Type>,
+    ) -> error::Result<VecDeque<types::Type>> {
+        // Chunk is a vararg function, so it may `return` values, and its locals are not visible
+        // to other chunks, executed in the same environment
+        let mut chunk_env = utils::Shared::new(environment::Environment::new(
+            Some(env.clone()),
+            environment::BreakFlag::Return(None),
+        ));
+        chunk_env.borrow_mut().set_varargs(args);
+        self.top_expression.eval(&mut chunk_env)?;
+
+        let unresolved_label = chunk_env.borrow_mut().take_goto();
+        if let Some(label) = unresolved_label {
+            return Err(self
+                .top_expression
+                .runtime_error(format!("No visible label '{}' for `goto`", label)));
+        }
 
-        self.top_expression.eval(&mut env);
+        let retval = chunk_env.borrow_mut().retval();
+        Ok(retval)
     }
 }
 
@@ -1,4 +1,8 @@
+use std::rc::Rc;
+
 use super::lexer::{tokens, Lexer};
+use super::span::{self, Position, Span};
+use crate::error;
 
 const DEBUG: bool = false;
 
@@ -8,31 +12,136 @@ pub enum Result {
     Error(String),
 }
 
+/// Syntax or lexical error with the parser state, where it happened
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Error with the full message and position
+    pub error: error::Error,
+    /// Description of the expected input, i.e. "')'" or "expression". None for lexical errors
+    /// and syntax errors, which are not about missing input
+    pub expected: Option<String>,
+    /// Unexpected token. None at the end of input and for lexical errors
+    pub found: Option<tokens::TokenType>,
+}
+
+impl Diagnostic {
+    /// Row and column of the error
+    pub fn position(&self) -> (usize, usize) {
+        self.error.position().unwrap_or_default()
+    }
+}
+
+/// Input, which a failed rule expected. Describes the failed part of a sequence in syntax errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    Keyword(tokens::Keyword),
+    /// Description of a named rule, i.e. "expression"
+    Rule(&'static str),
+}
+
+impl std::fmt::Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expected::Keyword(keyword) => write!(f, "'{}'", keyword),
+            Expected::Rule(description) => f.write_str(description),
+        }
+    }
+}
+
+/// Parser state before a rule. Used to get the span of tokens, consumed by the rule
+#[derive(Debug, Clone, Copy)]
+pub struct Mark {
+    consumed: usize,
+    start: Position,
+    /// Block nesting before the rule
+    depth: isize,
+}
+
 #[derive(Debug)]
 pub struct Parser {
     /// Lexer
     lexer: Lexer,
+    /// Name of the chunk for expression spans
+    chunk: Rc<str>,
     /// Active token
     lookahead_token: Option<tokens::Token>,
+    /// Position of the first character of the active token
+    lookahead_start: Position,
+    /// Position of the last character of the last consumed token
+    last_end: Position,
+    /// Number of consumed tokens. Used to check, that parser moves forward while recovering
+    consumed: usize,
+    /// Block nesting of the consumed tokens. Recovering parser skips nested blocks of the failed statement
+    depth: isize,
+    /// Current lexical or syntax error. While there is an error, parser stops producing tokens
+    error: Option<Diagnostic>,
+    /// Parser records errors and resumes at statement boundaries instead of stopping at the first error
+    recovering: bool,
+    /// Errors, recorded in recovery mode
+    diagnostics: Vec<Diagnostic>,
+    /// Lexer reached end of input inside an unfinished string or comment. Recovering parser treats
+    /// the rest of the input as missing
+    exhausted: bool,
+    /// Input, expected by the last failed rule
+    expected: Option<Expected>,
 }
 
 impl Parser {
     pub fn new(input: String) -> Parser {
         Parser {
             lexer: Lexer::new(input),
+            chunk: Rc::from(span::DEFAULT_CHUNK_NAME),
             lookahead_token: None,
+            lookahead_start: Position::default(),
+            last_end: Position::default(),
+            consumed: 0,
+            depth: 0,
+            error: None,
+            recovering: false,
+            diagnostics: Vec::new(),
+            exhausted: false,
+            expected: None,
+        }
+    }
+
+    /// Parser, which collects all errors. See `Parser::recover`
+    pub fn new_recovering(input: String) -> Parser {
+        Parser {
+            recovering: true,
+            ..Parser::new(input)
         }
     }
 
+    /// Set name of the chunk, which is reported in runtime errors
+    pub fn with_chunk_name(mut self, name: &str) -> Parser {
+        self.chunk = Rc::from(name);
+        self
+    }
+
     pub fn peek(&mut self) -> Option<&tokens::Token> {
-        let result = if let Some(ref token) = self.lookahead_token {
-            Some(token)
-        } else if let Some(token) = self.lexer.next() {
-            self.lookahead_token = Some(token);
-            self.lookahead_token.as_ref()
-        } else {
-            None
-        };
+        if self.error.is_some() || self.exhausted {
+            return None;
+        }
+
+        if self.lookahead_token.is_none() {
+            match self.lexer.next() {
+                Some(Ok(token)) => {
+                    let (row, column) = self.lexer.token_start();
+                    self.lookahead_start = Position::new(row, column);
+                    self.lookahead_token = Some(token)
+                }
+                Some(Err(error)) => {
+                    self.error = Some(Diagnostic {
+                        error,
+                        expected: None,
+                        found: None,
+                    })
+                }
+                None => (),
+            }
+        }
+
+        let result = self.lookahead_token.as_ref();
 
         debug_parser!("Parser peek {:?}", result);
         result
@@ -41,6 +150,175 @@ impl Parser {
     /// Function to shift parset. Must be called only by functions, which consume token
     pub fn shift(&mut self) {
         debug_parser!("Parser shift {:?}", self.lookahead_token);
-        self.lookahead_token = None
+        if let Some(token) = self.lookahead_token.take() {
+            self.last_end = Position::new(token.row, token.column);
+            self.consumed += 1;
+            self.depth += token.keyword().map_or(0, |keyword| keyword.nesting());
+        }
+    }
+
+    /// Remember parser state before parsing a rule
+    pub fn mark(&mut self) -> Mark {
+        let start = match self.peek() {
+            Some(_) => self.lookahead_start,
+            None => self.last_end,
+        };
+
+        Mark {
+            consumed: self.consumed,
+            start,
+            depth: self.depth,
+        }
+    }
+
+    /// Span of tokens, consumed since the mark. None if nothing was consumed
+    pub fn span(&self, mark: &Mark) -> Option<Span> {
+        if self.consumed == mark.consumed {
+            return None;
+        }
+
+        Some(Span::new(self.chunk.clone(), mark.start, self.last_end))
+    }
+
+    /// Number of consumed tokens
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Remember, what the failed rule expected
+    pub fn set_expected(&mut self, expected: Expected) {
+        self.expected = Some(expected);
+    }
+
+    /// Forget expectation of the previous rule before the next one is tried
+    pub fn clear_expected(&mut self) {
+        self.expected = None;
+    }
+
+    /// Report syntax error for the failed rule of a sequence, i.e. `')' expected near 'end'`
+    pub fn sequence_error(&mut self) {
+        match self.expected.take() {
+            Some(expected) => self.expected_error(&expected.to_string()),
+            None => self.syntax_error("syntax error"),
+        }
+    }
+
+    /// Report syntax error, when the current token is not the `expected` input
+    pub fn expected_error(&mut self, expected: &str) {
+        self.report_syntax_error(&format!("{} expected", expected), Some(expected));
+    }
+
+    /// Report syntax error at the current token
+    pub fn syntax_error(&mut self, message: &str) {
+        self.report_syntax_error(message, None);
+    }
+
+    /// Only the first error is saved, because following errors are caused by the first one
+    fn report_syntax_error(&mut self, message: &str, expected: Option<&str>) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let (error, found) = match self.peek().cloned() {
+            Some(token) => {
+                let message = format!("{} near {}", message, token.token);
+                (error::Error::syntax(message, &token), Some(token.token))
+            }
+            None if self.error.is_some() => return,
+            // End of input is reported at the end of the last token
+            None => {
+                let Position { row, column } = self.last_end;
+                let message = format!("{} {}", message, error::END_OF_INPUT);
+                let token = tokens::Token::new(tokens::TokenType::None, row, column);
+                (error::Error::syntax(message, &token), None)
+            }
+        };
+
+        self.error = Some(Diagnostic {
+            error,
+            expected: expected.map(str::to_string),
+            found,
+        });
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.recovering
+    }
+
+    /// Record the current error and skip tokens up to the next statement boundary, so parsing may continue.
+    /// Boundaries are statement keywords and names at the start of a line. Blocks, opened after the `start`
+    /// mark of the failed rule, are skipped up to their `end`. If the rule consumed nothing,
+    /// the unexpected token is skipped, so the parser always moves forward.
+    /// Returns false, if parser is not in recovery mode or there is no error
+    pub fn recover(&mut self, start: &Mark) -> bool {
+        if !self.recovering {
+            return false;
+        }
+
+        let diagnostic = match self.error.take() {
+            Some(diagnostic) => diagnostic,
+            None => return false,
+        };
+
+        // Errors after unfinished string or comment are caused by the missing rest of the input
+        if !self.exhausted {
+            self.diagnostics.push(diagnostic.clone());
+        }
+
+        if let error::Error::Lexical { .. } = diagnostic.error {
+            // Lexer skips invalid input itself, unless it reached end of input
+            if diagnostic.error.is_incomplete() {
+                self.exhausted = true;
+                return true;
+            }
+        } else if self.consumed == start.consumed && self.peek().is_some() {
+            self.shift();
+        }
+
+        loop {
+            let last_row = self.last_end.row;
+            let token = match self.peek() {
+                Some(token) => token,
+                None => break,
+            };
+            let boundary = match &token.token {
+                tokens::TokenType::Keyword(keyword) => keyword.is_statement_boundary(),
+                tokens::TokenType::Id(_) => token.row > last_row,
+                _ => false,
+            };
+
+            if boundary && self.depth <= start.depth {
+                break;
+            }
+            self.shift();
+        }
+
+        // Lexical error while skipping tokens
+        if self.error.is_some() {
+            let start = Mark {
+                consumed: self.consumed,
+                ..*start
+            };
+            return self.recover(&start);
+        }
+
+        true
+    }
+
+    /// Errors, recorded in recovery mode
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn error(&self) -> Option<&error::Error> {
+        self.error.as_ref().map(|diagnostic| &diagnostic.error)
+    }
+
+    pub fn take_error(&mut self) -> Option<error::Error> {
+        self.error.take().map(|diagnostic| diagnostic.error)
     }
 }
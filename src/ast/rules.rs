@@ -40,7 +40,40 @@ pub fn remove_enclosing_brackets(stack: &mut stack::Stack) {
 rule!(chunk, block);
 
 // block ::= {stat} [retstat]
-rule!(block, and![(repetition!(stat), optional!(retstat, nil)) => blocks::Block::new]);
+// In recovery mode failed statements are skipped up to the next statement boundary
+rule!(block, and![(repetition!(recover!(stat, void)), optional!(recover!(retstat), nil)) => blocks::Block::new]);
+
+/// Chunk for the recovering parser. Unlike `chunk` it consumes the whole input:
+/// tokens, which can't start a statement (i.e. unmatched `end`), are reported and skipped
+pub fn chunk_recovering(parser: &mut parser::Parser, stack: &mut stack::Stack) -> bool {
+    let mut statements = VecDeque::new();
+    let mut chunk_retstat = None;
+
+    loop {
+        repetition!(recover!(stat, void))(parser, stack);
+        optional!(recover!(retstat), nil)(parser, stack);
+        let (block_retstat, block_statements) = stack_unpack!(stack, optional, repetition);
+
+        // Statements after `return` are unreachable, but they are still checked for errors
+        if chunk_retstat.is_none() {
+            statements.extend(block_statements);
+            chunk_retstat = block_retstat;
+        }
+
+        if parser.peek().is_none() {
+            break;
+        }
+
+        let start = parser.mark();
+        parser.expected_error("statement");
+        parser.recover(&start);
+    }
+
+    stack.push_repetition(statements);
+    stack.push_optional(chunk_retstat);
+    blocks::Block::new(stack);
+    true
+}
 
 // stat ::=  ‘;’ |
 //      varlist ‘=’ explist |
@@ -57,10 +90,11 @@ rule!(block, and![(repetition!(stat), optional!(retstat, nil)) => blocks::Block:
 //      function funcname funcbody |
 //      local function Name funcbody |
 //      local namelist [‘=’ explist]
+//
+// Assignment and function call both start with prefixexp, so they are parsed by the single rule
 rule!(stat, or![
     and![(terminal!(Keyword::SEMICOLONS)) => ignore],
-    and![(varlist, terminal!(Keyword::ASSIGN), explist) => variables::Assignment::new],
-    functioncall,
+    variables::Assignment::rule_statement,
     label,
     statements::Statement::breakstat,
     and![(terminal!(Keyword::GOTO), variables::Id::rule) => labels::Goto::new],
@@ -103,12 +137,9 @@ rule!(stat, or![
         ]) => ignore],
     and![(terminal!(Keyword::FUNCTION), funcname, funcbody) => function::Function::new],
     and![(terminal!(Keyword::LOCAL), or![
-            // Because function expects indication of method name, we push empty optional value after Id
-            and![(terminal!(Keyword::FUNCTION),
-                and![(variables::Id::rule) => |stack: &mut stack::Stack| { stack.push_optional(None) } ],
-                funcbody) => function::Function::new],
-            and![(namelist, variables::Assignment::rule_local) => ignore]
-        ]) => blocks::Local::new]
+            and![(terminal!(Keyword::FUNCTION), variables::Id::rule, funcbody) => function::Function::new_local],
+            and![(namelist, variables::Assignment::rule_local) => blocks::Local::new]
+        ]) => second]
 ]);
 
 // retstat ::= return [explist] [‘;’]
@@ -139,7 +170,7 @@ rule!(varlist, and![(
     prepend_vector_prefix]);
 
 // var_suffix ::= ‘[’ exp ‘]’ [var_suffix] | ‘.’ Name [var_suffix]
-rule!(var_suffix, or![
+rule!(var_suffix, "'[' or '.'", or![
     and![(and![(terminal!(Keyword::LSBRACKET), exp, terminal!(Keyword::RSBRACKET)) =>
         tables::Indexing::new_table], optional!(var_suffix)) => ignore],
     and![(and![(terminal!(Keyword::DOT), variables::Id::rule_string_id) =>
@@ -156,7 +187,7 @@ rule!(var_repetition, or![
 rule!(var, or![
     and![(variables::Id::rule, optional!(var_repetition)) => ignore],
     and![(
-        and![(terminal!(Keyword::LBRACE), exp, terminal!(Keyword::RBRACE)) => remove_enclosing_brackets],
+        and![(terminal!(Keyword::LBRACE), exp, terminal!(Keyword::RBRACE)) => expression::Parentheses::new],
         var_repetition) => ignore]]);
 
 
@@ -168,7 +199,7 @@ rule!(namelist, and![(
     prepend_vector_prefix]);
 
 // explist ::= exp {‘,’ exp}
-rule!(explist, and![(
+rule!(explist, "expression", and![(
     exp,
     repetition!(and![(
         terminal!(Keyword::COMMA),
@@ -194,12 +225,12 @@ rule!(exp_prefix, or![
 ]);
 
 // exp ::= binop
-rule!(exp, binop);
+rule!(exp, "expression", binop);
 
 // prefixexp_prefix ::= Name | ‘(’ exp ‘)’
 rule!(prefixexp_prefix, or![
     variables::Id::rule,
-    and![(terminal!(Keyword::LBRACE), exp, terminal!(Keyword::RBRACE)) => remove_enclosing_brackets]]);
+    and![(terminal!(Keyword::LBRACE), exp, terminal!(Keyword::RBRACE)) => expression::Parentheses::new]]);
 
 // prefixexp_suffix ::= var_suffix [prefixexp_suffix] | functioncall_suffix [prefixexp_suffix]
 rule!(prefixexp_suffix, or![
@@ -212,11 +243,14 @@ rule!(prefixexp, and![(prefixexp_prefix, optional!(prefixexp_suffix)) => ignore]
 
 // To resolve 3-way recursion (prefixexp, var, functioncall), we need this set of rules
 // functioncall_suffix ::= args [functioncall_suffix] | ‘:’ Name args [functioncall_suffix]
-rule!(functioncall_suffix, or![
-    and![(and![(args) => function::Funcall::new], optional!(functioncall_suffix)) => ignore],
-    and![(and![(terminal!(Keyword::COLONS), variables::Id::rule_string_id, args) => function::Funcall::new_self], optional!(functioncall_suffix)) => ignore]
+rule!(functioncall_suffix, "function arguments", or![
+    and![(function::Funcall::rule, optional!(functioncall_suffix)) => ignore],
+    and![(function::Funcall::rule_self, optional!(functioncall_suffix)) => ignore]
 ]);
 
+// method_args ::= ‘:’ Name args
+rule!(method_args, and![(terminal!(Keyword::COLONS), variables::Id::rule_string_id, args) => ignore]);
+
 // functioncall_repetition ::= functioncall_suffix [functioncall_repetition] | var_suffix [var_suffix] functioncall_suffix [functioncall_repetition]
 rule!(functioncall_repetition, or![
     and![(functioncall_suffix, optional!(functioncall_repetition)) => ignore],
@@ -227,7 +261,7 @@ rule!(functioncall_repetition, or![
 rule!(functioncall, and![(prefixexp_prefix, functioncall_repetition) => ignore]);
 
 // args ::=  ‘(’ [explist] ‘)’ | tableconstructor | LiteralString
-rule!(args, or![
+rule!(args, "function arguments", or![
     and![(terminal!(Keyword::LBRACE), optional!(explist), terminal!(Keyword::RBRACE)) => function::Funcall::new_args],
     tableconstructor,
     primitives::String::rule
@@ -264,7 +298,7 @@ rule!(funcbody, and![(and![(terminal!(Keyword::LBRACE),
 // -- Names always will produce vector and ellipsis will produce single element, which is the indicator of the end
 // See FunctionParameters::new* function for further documentation
 // parlist_name ::= Name [parlist_suffix] | ‘...’
-rule!(parlist_name, or![
+rule!(parlist_name, "<name>", or![
     and![(and![(variables::Id::rule_string_id) => function::FunctionParameters::new_name], optional!(parlist_suffix)) => ignore],
     and![(and![(terminal!(Keyword::DOT3)) => function::FunctionParameters::new_namelist_varargs], optional!(parlist_suffix)) => ignore]
 ]);
@@ -304,8 +338,7 @@ rule!(fieldlist, and![(
 // field ::= ‘[’ exp ‘]’ ‘=’ exp | Name ‘=’ exp | exp
 rule!(field, or![
     and![(terminal!(Keyword::LSBRACKET), exp, terminal!(Keyword::RSBRACKET), terminal!(Keyword::ASSIGN), exp) => tables::TableField::new_table_index],
-    and![(tables::TableField::name_rule, terminal!(Keyword::ASSIGN), exp) => tables::TableField::new_object_index],
-    and![(exp) => tables::TableField::new_value]
+    tables::TableField::rule_name_or_value
 ]);
 
 // fieldsep ::= ‘,’ | ‘;’
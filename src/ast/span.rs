@@ -0,0 +1,58 @@
+use std::fmt;
+use std::rc::Rc;
+
+/// Chunk name, used when source code has no name
+pub const DEFAULT_CHUNK_NAME: &str = "chunk";
+
+/// Position in the source code. Rows and columns start from 1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(row: usize, column: usize) -> Self {
+        Position { row, column }
+    }
+}
+
+/// Source code range of an expression. All spans of a chunk share its name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub chunk: Rc<str>,
+    /// Position of the first character
+    pub start: Position,
+    /// Position of the last character
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(chunk: Rc<str>, start: Position, end: Position) -> Self {
+        Span { chunk, start, end }
+    }
+
+    /// The smallest span, which contains both spans
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            chunk: self.chunk.clone(),
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Merge optional spans
+    pub fn merge_options(left: Option<Span>, right: Option<Span>) -> Option<Span> {
+        match (left, right) {
+            (Some(left), Some(right)) => Some(left.merge(&right)),
+            (left, right) => left.or(right),
+        }
+    }
+}
+
+/// Location as reported in error messages: `chunk:row`
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.chunk, self.start.row)
+    }
+}
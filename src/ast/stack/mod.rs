@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 
 use crate::ast::expressions;
+use crate::ast::span::Span;
 
 const DEBUG: bool = false;
 
@@ -14,6 +15,9 @@ pub enum Element {
 #[derive(Debug, Default)]
 pub struct Stack {
     elements: Vec<Element>,
+    /// Span of all elements, popped since `take_popped_span`. Rule handlers build expressions
+    /// from popped elements, so this is the span of the new expression
+    popped_span: Option<Span>,
 }
 
 impl Stack {
@@ -25,6 +29,36 @@ impl Stack {
         self.elements.is_empty()
     }
 
+    /// Take span of elements, popped since the last call
+    pub fn take_popped_span(&mut self) -> Option<Span> {
+        self.popped_span.take()
+    }
+
+    /// Set span of the expression on top of the stack, unless it already has one
+    pub fn set_span(&mut self, span: Option<Span>) {
+        if span.is_none() {
+            return;
+        }
+
+        if let Some(Element::Single(_)) = self.elements.last() {
+            if let Some(Element::Single(expression)) = self.elements.pop() {
+                self.push_single(expressions::Spanned::wrap(expression, span));
+            }
+        }
+    }
+
+    fn add_popped_span(&mut self, expression: &dyn expressions::Expression) {
+        if let Some(span) = expression.span() {
+            self.popped_span = Span::merge_options(self.popped_span.take(), Some(span.clone()));
+        }
+    }
+
+    /// Drop elements above `len`. Used to discard partially parsed rules after syntax error
+    pub fn truncate(&mut self, len: usize) {
+        self.elements.truncate(len);
+        debug_parser!("Stack truncate to {}", len)
+    }
+
     pub fn peek(&self) -> &Element {
         self.elements.last().unwrap()
     }
@@ -37,12 +71,13 @@ impl Stack {
         match self.elements.pop().unwrap() {
             Element::Single(expression) => {
                 debug_parser!("Stack pop: {:?}", expression);
+                self.add_popped_span(expression.as_ref());
                 expression
             }
-            element => panic!(format!(
+            element => panic!(
                 "Expected single element on stack. Got {:?}\nStack: {:?}",
                 element, self
-            )),
+            ),
         }
     }
 
@@ -54,12 +89,15 @@ impl Stack {
         match self.elements.pop().unwrap() {
             Element::Repetition(expressions) => {
                 debug_parser!("Stack pop: {:?}", expressions);
+                for expression in expressions.iter() {
+                    self.add_popped_span(expression.as_ref());
+                }
                 expressions
             }
-            element => panic!(format!(
+            element => panic!(
                 "Expected repetition vector on stack. Got {:?}\nStack: {:?}",
                 element, self
-            )),
+            ),
         }
     }
 
@@ -71,12 +109,15 @@ impl Stack {
         match self.elements.pop().unwrap() {
             Element::Optional(expression) => {
                 debug_parser!("Stack pop: {:?}", expression);
+                if let Some(ref expression) = expression {
+                    self.add_popped_span(expression.as_ref());
+                }
                 expression
             }
-            element => panic!(format!(
+            element => panic!(
                 "Expected optional element on stack. Got {:?}\nStack: {:?}",
                 element, self
-            )),
+            ),
         }
     }
 
@@ -0,0 +1,340 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::time::Instant;
+
+use crate::ast;
+use crate::error;
+use crate::interpreter::{environment, types};
+use crate::repl;
+use crate::utils;
+
+pub const USAGE: &str = "usage: maul [options] [script [args]]
+Available options are:
+  -e stat  execute string 'stat'
+  -i       enter interactive mode after executing 'script'
+  -l name  require library 'name' into global 'name'
+  -p       only parse 'script' and report all syntax errors
+  -v       show version information
+  -t       print parsing and evaluation time
+  --       stop handling options
+  -        stop handling options and execute stdin";
+
+/// Chunk names, used in error positions for code without a file
+pub const COMMAND_LINE_CHUNK_NAME: &str = "(command line)";
+pub const STDIN_CHUNK_NAME: &str = "stdin";
+
+/// Options, which are executed in the order of appearance before the script
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    /// -e stat
+    Execute(String),
+    /// -l name
+    Require(String),
+}
+
+/// Parsed command line
+#[derive(Debug, PartialEq, Default)]
+pub struct Options {
+    pub actions: Vec<Action>,
+    pub version: bool,
+    pub interactive: bool,
+    pub timing: bool,
+    /// Check syntax of the script without running it
+    pub parse_only: bool,
+    /// Index of the script in the command line. `-` means stdin
+    pub script: Option<usize>,
+}
+
+impl Options {
+    /// Parse command line. The first argument is the program name
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut index = 1;
+
+        while index < args.len() {
+            let arg = &args[index];
+
+            match arg.as_str() {
+                "--" => {
+                    if index + 1 < args.len() {
+                        options.script = Some(index + 1);
+                    }
+                    break;
+                }
+                "-" => {
+                    options.script = Some(index);
+                    break;
+                }
+                "-v" => options.version = true,
+                "-i" => {
+                    options.interactive = true;
+                    options.version = true;
+                }
+                "-t" => options.timing = true,
+                "-p" => options.parse_only = true,
+                _ if arg.starts_with("-e") || arg.starts_with("-l") => {
+                    // Value may follow the option immediately or be the next argument
+                    let value = if arg.len() > 2 {
+                        arg[2..].to_string()
+                    } else {
+                        index += 1;
+                        match args.get(index) {
+                            Some(value) => value.clone(),
+                            None => return Err(format!("'{}' needs argument", arg)),
+                        }
+                    };
+
+                    options.actions.push(if arg.starts_with("-e") {
+                        Action::Execute(value)
+                    } else {
+                        Action::Require(value)
+                    })
+                }
+                _ if arg.starts_with('-') => {
+                    return Err(format!("unrecognized option '{}'", arg))
+                }
+                _ => {
+                    options.script = Some(index);
+                    break;
+                }
+            }
+
+            index += 1;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Create global `arg` table. The script name goes to index 0, script arguments to positive indices,
+/// interpreter name and options to negative ones
+pub fn set_arg_table(
+    env: &mut utils::Shared<environment::Environment>,
+    args: &[String],
+    script: Option<usize>,
+) {
+    let script = script.unwrap_or(0) as isize;
+    let mut table = types::Table::new(env.borrow_mut().next_global_id());
+
+    for (index, arg) in args.iter().enumerate() {
+        table
+            .set(
+                types::Type::Integer((index as isize - script) as i64),
+                types::Type::String(arg.clone().into()),
+            )
+            .expect("Should never happen");
+    }
+
+    env.borrow_mut().add_variable(
+        "arg".to_string(),
+        types::Type::Table(utils::Shared::new(table)),
+    );
+}
+
+/// Parse and execute source code of the chunk `name` in the environment
+pub fn execute(
+    env: &mut utils::Shared<environment::Environment>,
+    source: String,
+    name: &str,
+    args: VecDeque<types::Type>,
+    timing: bool,
+) -> error::Result<VecDeque<types::Type>> {
+    let start = Instant::now();
+    let ast = ast::AST::with_name(source, name)?;
+
+    if timing {
+        let elapsed = Instant::now() - start;
+        eprintln!(
+            "Parsed input in {}.{:09} seconds",
+            elapsed.as_secs(),
+            elapsed.subsec_nanos()
+        );
+    }
+
+    let start = Instant::now();
+    let result = ast.exec(env, args);
+
+    if timing {
+        let elapsed = Instant::now() - start;
+        eprintln!(
+            "Evaluated in {}.{:09} seconds",
+            elapsed.as_secs(),
+            elapsed.subsec_nanos()
+        );
+    }
+
+    result
+}
+
+/// Load module `name` from `name.lua` or `name/init.lua` and store its result into global `name`.
+/// Dots in the module name are directory separators
+pub fn require(
+    env: &mut utils::Shared<environment::Environment>,
+    name: &str,
+    timing: bool,
+) -> error::Result<()> {
+    let path = name.replace('.', "/");
+    let candidates = [format!("{}.lua", path), format!("{}/init.lua", path)];
+
+    let found = candidates
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok().map(|source| (path, source)));
+    let (path, source) = match found {
+        Some(found) => found,
+        None => {
+            return Err(error::Error::runtime(format!(
+                "module '{}' not found: no file '{}'",
+                name,
+                candidates.join("', no file '")
+            )))
+        }
+    };
+
+    let result = execute(env, source, path, VecDeque::new(), timing)?
+        .pop_front()
+        .filter(|value| !value.is_nil())
+        .unwrap_or(types::Type::Boolean(true));
+
+    env.borrow_mut().add_variable(name.to_string(), result);
+    Ok(())
+}
+
+/// Report all lexical and syntax errors of the source code. Returns process exit code
+pub fn check(source: &str, program: &str) -> i32 {
+    let (_, diagnostics) = ast::AST::new_recovering(source.to_string());
+
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}: {}", program, diagnostic.error);
+    }
+
+    if diagnostics.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Print error message with its stack traceback, as the reference interpreter does
+pub fn print_error(program: &str, error: &error::Error) {
+    eprintln!("{}: {}", program, error);
+
+    if let Some(traceback) = error.traceback() {
+        eprintln!("{}", traceback);
+    }
+}
+
+/// Read the whole script. `-` reads stdin
+fn read_script(path: &str) -> Result<String, String> {
+    let result = if path == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map(|_| source)
+    } else {
+        fs::read_to_string(path)
+    };
+
+    result.map_err(|error| format!("cannot open {}: {}", path, error))
+}
+
+/// Run interpreter with command line arguments. Returns process exit code
+pub fn run(args: &[String]) -> i32 {
+    let program = args.first().map(String::as_str).unwrap_or("maul");
+
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}: {}\n{}", program, message, USAGE);
+            return 1;
+        }
+    };
+
+    if options.version {
+        print_version();
+    }
+
+    let mut env = ast::AST::environment();
+    set_arg_table(&mut env, args, options.script);
+
+    let report = |error: &dyn std::fmt::Display| {
+        eprintln!("{}: {}", program, error);
+        1
+    };
+
+    for action in options.actions.iter() {
+        let result = match action {
+            Action::Execute(source) => {
+                execute(
+                    &mut env,
+                    source.clone(),
+                    COMMAND_LINE_CHUNK_NAME,
+                    VecDeque::new(),
+                    options.timing,
+                )
+                .map(|_| ())
+            }
+            Action::Require(name) => require(&mut env, name, options.timing),
+        };
+
+        if let Err(error) = result {
+            print_error(program, &error);
+            return 1;
+        }
+    }
+
+    // Without script and options we run REPL for terminal and execute stdin otherwise
+    let script = match options.script {
+        Some(index) => &args[index],
+        None if options.actions.is_empty() && !options.version => {
+            if io::stdin().is_terminal() {
+                print_version();
+                return interactive(env, program);
+            }
+            "-"
+        }
+        None if options.interactive => return interactive(env, program),
+        None => return 0,
+    };
+    let script_args = options
+        .script
+        .map(|index| {
+            args[index + 1..]
+                .iter()
+                .map(|arg| types::Type::String(arg.clone().into()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let source = match read_script(script) {
+        Ok(source) => source,
+        Err(message) => return report(&message),
+    };
+
+    if options.parse_only {
+        return check(&source, program);
+    }
+
+    let name = if script == "-" { STDIN_CHUNK_NAME } else { script };
+    if let Err(error) = execute(&mut env, source, name, script_args, options.timing) {
+        print_error(program, &error);
+        return 1;
+    }
+
+    if options.interactive {
+        interactive(env, program)
+    } else {
+        0
+    }
+}
+
+fn print_version() {
+    println!("maul {} -- Lua 5.3 interpreter", env!("CARGO_PKG_VERSION"));
+}
+
+/// Run REPL in the environment. Interactive session errors don't affect the exit code
+fn interactive(env: utils::Shared<environment::Environment>, program: &str) -> i32 {
+    let mut history = repl::History::load_default();
+    let stdin = io::stdin();
+
+    repl::Repl::new(env).run(&mut stdin.lock(), &mut history, program);
+    0
+}
@@ -1,30 +1,197 @@
+use std::fmt;
+
 use crate::ast::lexer::tokens;
+use crate::ast::span::Span;
+use crate::interpreter::types;
 
-#[derive(Debug, PartialEq)]
-pub struct Error {
-    pub error_token: tokens::Token,
-    pub message: String,
+/// Interpreter error. Parsing and evaluation report errors instead of terminating the process,
+/// so the host application may handle them
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// Invalid character sequence in the source code
+    Lexical {
+        message: String,
+        row: usize,
+        column: usize,
+    },
+    /// Source code doesn't match the grammar
+    Syntax {
+        message: String,
+        row: usize,
+        column: usize,
+    },
+    /// Error raised while evaluating the script. Scripts may raise any value with `error` function.
+    /// Interpreter error messages get position of the expression, which failed. Values raised by
+    /// scripts are already `located` and kept as is. Traceback is the call stack at the moment of the error
+    Runtime {
+        value: types::Type,
+        located: bool,
+        traceback: Option<String>,
+    },
 }
 
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Syntax error messages end with this suffix, when the parser reached end of input
+pub const END_OF_INPUT: &str = "near <eof>";
+
 impl Error {
-    pub fn new(token: tokens::Token, message: &str) -> Self {
-        Error {
-            error_token: token,
-            message: message.to_owned(),
+    pub fn lexical(message: String, row: usize, column: usize) -> Self {
+        Error::Lexical {
+            message,
+            row,
+            column,
+        }
+    }
+
+    /// Syntax error at the token position
+    pub fn syntax(message: String, token: &tokens::Token) -> Self {
+        Error::Syntax {
+            message,
+            row: token.row,
+            column: token.column,
+        }
+    }
+
+    pub fn runtime(message: String) -> Self {
+        Error::Runtime {
+            value: types::Type::String(message.into()),
+            located: false,
+            traceback: None,
+        }
+    }
+
+    /// Runtime error with arbitrary error object
+    pub fn runtime_value(value: types::Type) -> Self {
+        Error::Runtime {
+            value,
+            located: true,
+            traceback: None,
+        }
+    }
+
+    /// Prepend position to the runtime error message, unless the error already has position
+    pub fn locate(self, span: &Span) -> Self {
+        match self {
+            Error::Runtime {
+                value: types::Type::String(message),
+                located: false,
+                traceback,
+            } => Error::Runtime {
+                value: types::Type::String(format!("{}: {}", span, message).into()),
+                located: true,
+                traceback,
+            },
+            error => error,
+        }
+    }
+
+    /// Attach stack traceback to the runtime error, unless it already has one.
+    /// Traceback is built only once, where the error is raised
+    pub fn with_traceback(mut self, make_traceback: impl FnOnce() -> String) -> Self {
+        if let Error::Runtime {
+            traceback: ref mut traceback @ None,
+            ..
+        } = self
+        {
+            *traceback = Some(make_traceback());
+        }
+
+        self
+    }
+
+    /// Stack traceback of the runtime error
+    pub fn traceback(&self) -> Option<&str> {
+        match self {
+            Error::Runtime { traceback, .. } => traceback.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Source code ended in the middle of a statement or a string, so it may be continued.
+    /// Used by REPL to request more lines
+    pub fn is_incomplete(&self) -> bool {
+        match self {
+            Error::Lexical { message, .. } => message.starts_with("Unmatched"),
+            Error::Syntax { message, .. } => message.ends_with(END_OF_INPUT),
+            Error::Runtime { .. } => false,
         }
     }
 
-    pub fn add_message(self, message: &str) -> Error {
-        Error {
-            error_token: self.error_token,
-            message: self.message + message,
+    /// Row and column of lexical and syntax errors
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Error::Lexical { row, column, .. } | Error::Syntax { row, column, .. } => {
+                Some((*row, *column))
+            }
+            Error::Runtime { .. } => None,
         }
     }
 
-    pub fn complain(&self) {
-        panic!(
-            "{}: {}. {} {:?}",
-            self.error_token.row, self.error_token.column, self.message, self.error_token
-        );
+    /// Error object, which is passed to the script, when error is caught
+    pub fn value(&self) -> types::Type {
+        match self {
+            Error::Runtime { value, .. } => value.clone(),
+            error => types::Type::String(error.to_string().into()),
+        }
     }
 }
+
+/// Traceback describes where the error happened, but it is not a part of the error itself
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (
+                Error::Lexical { message, row, column },
+                Error::Lexical { message: other_message, row: other_row, column: other_column },
+            )
+            | (
+                Error::Syntax { message, row, column },
+                Error::Syntax { message: other_message, row: other_row, column: other_column },
+            ) => (message, row, column) == (other_message, other_row, other_column),
+            (
+                Error::Runtime { value, located, .. },
+                Error::Runtime { value: other_value, located: other_located, .. },
+            ) => (value, located) == (other_value, other_located),
+            _ => false,
+        }
+    }
+}
+
+/// Natives report errors with messages
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::runtime(message)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Lexical {
+                message,
+                row,
+                column,
+            } => write!(f, "Lexical error at {}:{}: {}", row, column, message),
+            Error::Syntax {
+                message,
+                row,
+                column,
+            } => write!(f, "Syntax error at {}:{}: {}", row, column, message),
+            Error::Runtime { value, .. } => match value.unref() {
+                types::Type::String(message) => write!(f, "Runtime error: {}", message),
+                types::Type::Number(number) => {
+                    write!(f, "Runtime error: {}", types::format_number(*number))
+                }
+                types::Type::Integer(integer) => write!(f, "Runtime error: {}", integer),
+                value => write!(
+                    f,
+                    "Runtime error: (error object is a {} value)",
+                    value.type_name()
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,8 +1,9 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::ops::Deref;
 use std::rc::Rc;
 
+use crate::ast::span::Span;
 use crate::interpreter::types;
 use crate::utils::Shared;
 
@@ -38,36 +39,120 @@ pub enum BreakFlag {
     Goto(String, Box<BreakFlag>),
 }
 
+/// Name of the called function, as it was written at the call site
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionName {
+    Global(String),
+    Local(String),
+    Field(String),
+    Method(String),
+    /// Native function with its registered name, i.e. `table.unpack`
+    Native(String),
+    /// Anonymous function with position of its definition, if known
+    Anonymous(Option<Span>),
+    /// Iterator function of the generic `for` loop
+    ForIterator,
+}
+
+impl FunctionName {
+    /// Natives are known by their own names, anonymous functions by their definitions
+    pub fn resolve(self, function: &types::Type) -> FunctionName {
+        match (function, self) {
+            (types::Type::NativeFunction { name, .. }, _) => FunctionName::Native(name.clone()),
+            (types::Type::Function { body, .. }, FunctionName::Anonymous(_)) => FunctionName::Anonymous(body.span().cloned()),
+            (_, name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for FunctionName {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FunctionName::Global(name) | FunctionName::Native(name) => write!(formatter, "function '{}'", name),
+            FunctionName::Local(name) => write!(formatter, "local '{}'", name),
+            FunctionName::Field(name) => write!(formatter, "field '{}'", name),
+            FunctionName::Method(name) => write!(formatter, "method '{}'", name),
+            FunctionName::Anonymous(Some(span)) => write!(formatter, "function <{}>", span),
+            FunctionName::Anonymous(None) => write!(formatter, "?"),
+            FunctionName::ForIterator => write!(formatter, "for iterator 'for iterator'"),
+        }
+    }
+}
+
+/// Maximum number of nested calls. Lua calls recurse on the Rust stack, so deeper recursion fails with
+/// a runtime error instead of overflowing it. Interpreter thread stack must fit that many calls
+pub const MAX_CALL_DEPTH: usize = 2000;
+
+/// Number of the innermost and the outermost calls shown in long tracebacks
+const TRACEBACK_FIRST: usize = 10;
+const TRACEBACK_LAST: usize = 11;
+
+/// Active function call
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    /// Position of the call expression in the caller
+    pub span: Span,
+    pub function: FunctionName,
+}
+
+impl CallFrame {
+    fn is_native(&self) -> bool {
+        matches!(self.function, FunctionName::Native(_))
+    }
+}
+
+/// Active function calls, shared by all environments
+#[derive(Debug, Default)]
+struct CallStack {
+    /// Innermost call is the last
+    frames: Vec<CallFrame>,
+    /// Number of nested calls, including calls without frames, like metamethods
+    depth: usize,
+    /// Traceback of the error, which is handled by `xpcall` message handler. Handler is called after
+    /// the stack is unwound, so it gets traceback of the error instead of the current one
+    handled_traceback: Option<String>,
+}
+
 /// Environment structure. Each block starts new environment, settings current as a parent
 #[derive(Debug)]
 pub struct Environment {
     /// Global counter across all environments to set object ID's
     global_id_counter: Rc<RefCell<u64>>,
+    /// Active function calls. Shared by all environments
+    call_stack: Rc<RefCell<CallStack>>,
+    /// Metatable of all strings. Shared by all environments
+    string_metatable: Rc<RefCell<Option<Shared<types::Table>>>>,
     /// Variables registry from current environment
     data: HashMap<String, Rc<RefCell<types::Type>>>,
     /// Parent environment. Used to reference to a calling code
     parent: Option<Shared<Environment>>,
     /// Block execution break flag. See BreakFlag documentation
     break_flag: BreakFlag,
+    /// Extra arguments of vararg function, accessible with `...` expression
+    varargs: Option<VecDeque<types::Type>>,
     /// Environment self ID
     id: u64
 }
 
 impl Environment {
     pub fn new(parent: Option<Shared<Environment>>, break_flag: BreakFlag) -> Self {
-        let global_id_counter = if let Some(ref parent) = parent {
-                parent.borrow_mut().global_id_counter.clone()
+        let (global_id_counter, call_stack, string_metatable) = if let Some(ref parent) = parent {
+                let parent = parent.borrow();
+                (parent.global_id_counter.clone(), parent.call_stack.clone(), parent.string_metatable.clone())
             } else {
-                Rc::new(RefCell::new(0))
+                (Rc::new(RefCell::new(0)), Rc::new(RefCell::new(CallStack::default())), Rc::new(RefCell::new(None)))
             };
         let id = *global_id_counter.borrow();
         *global_id_counter.borrow_mut() += 1;
 
         Environment {
             global_id_counter,
+            call_stack,
+            string_metatable,
             data: HashMap::new(),
             parent,
             break_flag,
+            varargs: None,
             id,
         }
     }
@@ -83,6 +168,123 @@ impl Environment {
         value
     }
 
+    /// Set metatable, shared by all strings
+    pub fn set_string_metatable(&self, metatable: Shared<types::Table>) {
+        self.string_metatable.replace(Some(metatable));
+    }
+
+    /// Value metatable. Tables have their own metatables and strings share the string metatable
+    pub fn metatable(&self, value: &types::Type) -> Option<Shared<types::Table>> {
+        match value.unref() {
+            types::Type::String(_) => self.string_metatable.borrow().clone(),
+            value => value.metatable(),
+        }
+    }
+
+    /// Get metamethod from the value metatable
+    pub fn metamethod(&self, value: &types::Type, name: &str) -> Option<types::Type> {
+        match value.unref() {
+            types::Type::String(_) => {
+                let metatable = self.string_metatable.borrow().clone()?;
+                let metamethod = metatable.borrow().get(&types::Type::String(name.into()));
+
+                if metamethod.is_nil() {
+                    None
+                } else {
+                    Some(metamethod)
+                }
+            }
+            value => value.metamethod(name),
+        }
+    }
+
+    /// Register function call. Each call must be finished with `leave_call`
+    pub fn enter_call(&self, frame: CallFrame) {
+        self.call_stack.borrow_mut().frames.push(frame);
+    }
+
+    pub fn leave_call(&self) {
+        self.call_stack.borrow_mut().frames.pop();
+    }
+
+    /// Count nested function execution. Fails if there are too many nested calls.
+    /// Each successful call must be finished with `leave_function`
+    pub fn enter_function(&self) -> Result<(), String> {
+        let mut call_stack = self.call_stack.borrow_mut();
+        if call_stack.depth >= MAX_CALL_DEPTH {
+            return Err("stack overflow".to_string());
+        }
+
+        call_stack.depth += 1;
+        Ok(())
+    }
+
+    pub fn leave_function(&self) {
+        self.call_stack.borrow_mut().depth -= 1;
+    }
+
+    /// Position of the active call at the `level`. Level 1 is the innermost call
+    pub fn call_site(&self, level: usize) -> Option<Span> {
+        let frames = &self.call_stack.borrow().frames;
+        let index = frames.len().checked_sub(level)?;
+
+        frames.get(index).map(|frame| frame.span.clone())
+    }
+
+    /// Set traceback of the error, handled by the message handler. Returns traceback of the previous handled error
+    pub fn handle_error(&self, traceback: Option<String>) -> Option<String> {
+        std::mem::replace(&mut self.call_stack.borrow_mut().handled_traceback, traceback)
+    }
+
+    /// Stack traceback in the format of the reference interpreter, innermost call first.
+    /// `current` is the position, where the innermost Lua function is executed. Calls deeper than `level`
+    /// are skipped, so level 1 starts from the innermost call
+    pub fn traceback(&self, current: Option<&Span>, level: usize) -> String {
+        let call_stack = self.call_stack.borrow();
+        if let Some(ref traceback) = call_stack.handled_traceback {
+            return traceback.clone();
+        }
+
+        let frames = &call_stack.frames;
+        let depth = frames.len().saturating_sub(level.saturating_sub(1));
+        let mut lines = Vec::new();
+
+        // Function is executed at the position, where it calls the next function
+        let position = |index: usize| match frames.get(index) {
+            Some(frame) => frame.span.to_string(),
+            None => current.map(Span::to_string).unwrap_or_else(|| "?".to_string()),
+        };
+
+        for index in (0..depth).rev() {
+            let frame = &frames[index];
+
+            if frame.is_native() {
+                lines.push(format!("[C]: in {}", frame.function));
+            } else {
+                lines.push(format!("{}: in {}", position(index + 1), frame.function));
+            }
+        }
+
+        lines.push(format!("{}: in main chunk", position(0)));
+        lines.push("[C]: in ?".to_string());
+
+        // Long tracebacks keep only the innermost and outermost calls
+        if lines.len() > TRACEBACK_FIRST + TRACEBACK_LAST {
+            let skipped = lines.len() - TRACEBACK_FIRST - TRACEBACK_LAST;
+            lines.splice(
+                TRACEBACK_FIRST..TRACEBACK_FIRST + skipped,
+                vec![format!("...\t(skipping {} levels)", skipped)],
+            );
+        }
+
+        let mut result = "stack traceback:".to_string();
+        for line in lines {
+            result += "\n\t";
+            result += &line;
+        }
+        result
+    }
+
     /// Get variable value(reference). If current env doesn't contain the varable, checks in parent environments
     pub fn get(&mut self, varname: &str) -> Option<Rc<RefCell<types::Type>>> {
         if let Some(value) = self.data.get(varname) {
@@ -102,6 +304,42 @@ impl Environment {
         }
     }
 
+    /// Check if variable is visible as a local variable. Variables of the topmost environment are globals
+    pub fn is_local(&self, varname: &str) -> bool {
+        match (self.data.contains_key(varname), &self.parent) {
+            (true, parent) => parent.is_some(),
+            (false, Some(parent)) => parent.borrow().is_local(varname),
+            (false, None) => false,
+        }
+    }
+
+    /// Set extra arguments of vararg function or main chunk
+    pub fn set_varargs(&mut self, varargs: VecDeque<types::Type>) {
+        self.varargs = Some(varargs);
+    }
+
+    /// Get extra arguments of the innermost function. Returns `None` if the function is not a vararg function
+    pub fn varargs(&self) -> Option<VecDeque<types::Type>> {
+        if let Some(ref varargs) = self.varargs {
+            return Some(varargs.clone());
+        }
+
+        match (&self.break_flag, &self.parent) {
+            // Function boundary. Enclosing function varargs are not visible
+            (BreakFlag::Return(_), _) => None,
+            (_, Some(parent)) => parent.borrow().varargs(),
+            _ => None,
+        }
+    }
+
+    /// Add variable with `id` with `value` to the topmost environment
+    pub fn add_global(&mut self, id: String, value: types::Type) -> Rc<RefCell<types::Type>> {
+        match self.parent {
+            Some(ref parent) => parent.borrow_mut().add_global(id, value),
+            None => self.add_variable(id, value),
+        }
+    }
+
     /// Add variable with `id` with `value` to the current environment
     pub fn add_variable(&mut self, id: String, value: types::Type) -> Rc<RefCell<types::Type>> {
         let reference = match value {
@@ -165,9 +403,10 @@ impl Environment {
                     _ => false,
                 }
             }
-            // This doesn't look nice. We could fill old value from the inside. Will be todo
-            BreakFlag::Goto(_, _) => {
-                self.break_flag = flag;
+            // Goto interrupts current block. We save current flag to restore it, when the label is found
+            BreakFlag::Goto(label, _) => {
+                let old_flag = std::mem::replace(&mut self.break_flag, BreakFlag::None);
+                self.break_flag = BreakFlag::Goto(label, Box::new(old_flag));
                 true
             }
         }
@@ -177,15 +416,31 @@ impl Environment {
         &self.break_flag
     }
 
-    // Destroy environment and in case it contains return value, return it
-    pub fn retval(&mut self) -> types::Type {
+    /// Finish `goto` interruption. Restores flag the env had before the jump and returns label name
+    pub fn take_goto(&mut self) -> Option<String> {
+        match std::mem::replace(&mut self.break_flag, BreakFlag::None) {
+            BreakFlag::Goto(label, old_flag) => {
+                self.break_flag = *old_flag;
+                Some(label)
+            }
+            flag => {
+                self.break_flag = flag;
+                None
+            }
+        }
+    }
+
+    // Destroy environment and in case it contains return values, return them
+    pub fn retval(&mut self) -> VecDeque<types::Type> {
         if let BreakFlag::Return(ref mut some_ret) = self.break_flag {
-            if let Some(retval) = some_ret.take() {
-                return retval;
+            match some_ret.take() {
+                Some(types::Type::Vector(values)) => return values,
+                Some(value) => return VecDeque::from(vec![value]),
+                None => (),
             }
         }
 
-        types::Type::Nil
+        VecDeque::new()
     }
 }
 
@@ -1,53 +1,165 @@
+use std::collections::VecDeque;
+
 use crate::ast::expressions::{self, blocks};
+use crate::error;
+use crate::interpreter::expressions::{expression, functions};
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
+/// Moves unresolved `goto` from a nested block env to the enclosing env.
+/// Returns true if nested block was interrupted by `goto`, so enclosing statement must stop.
+fn propagate_goto(
+    local_env: &utils::Shared<environment::Environment>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> bool {
+    if let Some(label) = local_env.borrow_mut().take_goto() {
+        env.borrow_mut().break_execution(environment::BreakFlag::Goto(
+            label,
+            Box::new(environment::BreakFlag::None),
+        ));
+        true
+    } else {
+        false
+    }
+}
+
+/// Evaluate block in its own scope. The scope doesn't stop `break` and `return`, they reach
+/// the enclosing loop or function
+fn eval_scoped(
+    block: &dyn expressions::Expression,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<()> {
+    let mut local_env = utils::Shared::new(environment::Environment::new(
+        Some(env.clone()),
+        environment::BreakFlag::None,
+    ));
+
+    block.eval(&mut local_env)?;
+    propagate_goto(&local_env, env);
+
+    Ok(())
+}
+
+/// New scope for a loop iteration. `break` stops at its boundary
+fn loop_scope(env: &utils::Shared<environment::Environment>) -> utils::Shared<environment::Environment> {
+    utils::Shared::new(environment::Environment::new(
+        Some(env.clone()),
+        environment::BreakFlag::Break(false),
+    ))
+}
+
+/// Check if loop iteration is interrupted with `break` or `goto`
+fn interrupted(
+    local_env: &utils::Shared<environment::Environment>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> bool {
+    if let environment::BreakFlag::Break(true) = local_env.borrow().break_flag() {
+        return true;
+    }
+
+    propagate_goto(local_env, env)
+}
+
+impl blocks::Block {
+    /// Find position of the label in the current block. Nested blocks labels are not visible
+    fn find_label(&self, label: &str) -> Option<usize> {
+        self.statements
+            .iter()
+            .position(|statement| statement.label() == Some(label))
+    }
+
+    /// Check if jump from `from` statement to `to` label enters the scope of a local variable.
+    /// Label at the end of the block (followed only by void statements) is outside of local scopes.
+    fn check_goto_scope(&self, label: &str, from: usize, to: usize) -> error::Result<()> {
+        let at_the_end = self.retstat.is_none()
+            && self.statements.iter().skip(to).all(|statement| statement.is_void());
+
+        if to <= from || at_the_end {
+            return Ok(());
+        }
+
+        let statements = self.statements.iter().take(to).skip(from + 1);
+        if statements.clone().any(|statement| statement.is_local()) {
+            return Err(interpreter::Eval::runtime_error(
+                self,
+                format!("`goto {}` jumps into the scope of a local", label),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 /* pub struct Block {
     statements: VecDeque<Box<dyn expressions::Expression>>,
     retstat: Option<Box<dyn expressions::Expression>>,
 }*/
 impl interpreter::Eval for blocks::Block {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        for statement in &self.statements {
-            statement.eval(env);
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let mut index = 0;
 
-            // Check if broken
-            if let environment::BreakFlag::Break(true) = env.borrow().break_flag() {
-                return types::Type::Nil;
-            }
+        while index < self.statements.len() {
+            self.statements[index].eval(env)?;
+
+            // Check if broken or returned from a nested block
+            let label = match env.borrow().break_flag() {
+                environment::BreakFlag::Break(true) | environment::BreakFlag::Return(Some(_)) => {
+                    return Ok(types::Type::Nil)
+                }
+                environment::BreakFlag::Goto(label, _) => label.clone(),
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+
+            // Label is not visible from the current block. Leave flag for enclosing blocks
+            let target = match self.find_label(&label) {
+                Some(target) => target,
+                _ => return Ok(types::Type::Nil),
+            };
+
+            self.check_goto_scope(&label, index, target)?;
+            env.borrow_mut().take_goto();
+            index = target + 1;
         }
 
         if let Some(ref retstat) = self.retstat {
-            let return_value = retstat.eval(env);
+            let return_value = types::Type::Vector(retstat.eval_multi(env)?);
 
             if !env
                 .borrow_mut()
                 .break_execution(environment::BreakFlag::Return(Some(return_value)))
             {
-                self.runtime_error("Unexpected return statement. Not a function".to_string())
+                return Err(self.runtime_error("Unexpected return statement. Not a function".to_string()))
             }
         }
 
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
 
 // pub struct DoBlock(pub Box<dyn expressions::Expression>);
 impl interpreter::Eval for blocks::DoBlock {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let local_env =
-            environment::Environment::new(Some(env.clone()), environment::BreakFlag::Break(false));
-
-        self.0.eval(&mut utils::Shared::new(local_env));
-        types::Type::Nil
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        eval_scoped(&*self.0, env)?;
+        Ok(types::Type::Nil)
     }
 }
 
-// TODO: Do we need another wrapper for local?
-// pub struct Local(Box<dyn expressions::Expression>);
 impl interpreter::Eval for blocks::Local {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        self.0.eval(env)
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        match self {
+            blocks::Local::Variables(assignment) => assignment.eval(env),
+            blocks::Local::Function { name, closure } => {
+                // Declare the name first, so the closure sees its own variable
+                let reference = env.borrow_mut().add_variable(name.clone(), types::Type::Nil);
+                let function = closure.eval(env)?;
+                reference.replace(function);
+
+                Ok(types::Type::Nil)
+            }
+        }
     }
 }
 
@@ -56,21 +168,13 @@ impl interpreter::Eval for blocks::Local {
 //     pub block: Box<dyn expressions::Expression>,
 // }
 impl interpreter::Eval for blocks::WhileBlock {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let mut local_env = utils::Shared::new(environment::Environment::new(
-            Some(env.clone()),
-            environment::BreakFlag::Break(false),
-        ));
-
-        while self.condition.eval(env).as_bool() {
-            self.block.eval(&mut local_env);
-            // Check if broken
-            let env_borrow = local_env.borrow();
-            if let environment::BreakFlag::Break(true) = *env_borrow.break_flag() {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        while self.condition.eval(env)?.as_bool() {
+            if !iterate(&*self.block, Vec::new(), env)? {
                 break;
             }
         }
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
 
@@ -79,26 +183,22 @@ impl interpreter::Eval for blocks::WhileBlock {
 //     pub condition: Box<dyn expressions::Expression>,
 // }
 impl interpreter::Eval for blocks::RepeatBlock {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let mut local_env = utils::Shared::new(environment::Environment::new(
-            Some(env.clone()),
-            environment::BreakFlag::Break(false),
-        ));
-
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         loop {
-            self.block.eval(&mut local_env);
+            let mut local_env = loop_scope(env);
+            self.block.eval(&mut local_env)?;
 
-            // Check if broken
-            if let environment::BreakFlag::Break(true) = local_env.borrow().break_flag() {
+            if interrupted(&local_env, env) {
                 break;
             }
 
-            if self.condition.eval(env).as_bool() {
+            // Condition sees local variables of the body
+            if self.condition.eval(&mut local_env)?.as_bool() {
                 break;
             }
         }
 
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
 
@@ -107,13 +207,13 @@ impl interpreter::Eval for blocks::RepeatBlock {
 //     pub block: Box<dyn expressions::Expression>,
 // }
 impl interpreter::Eval for blocks::IfCondition {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        if self.condition.eval(env).as_bool() {
-            self.block.eval(env);
-            return types::Type::Boolean(true);
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        if self.condition.eval(env)?.as_bool() {
+            eval_scoped(&*self.block, env)?;
+            return Ok(types::Type::Boolean(true));
         }
 
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
 
@@ -122,76 +222,212 @@ impl interpreter::Eval for blocks::IfCondition {
 //     pub else_block: Option<Box<dyn expressions::Expression>>,
 // }
 impl interpreter::Eval for blocks::IfBlock {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         for condition in &self.conditions {
-            if condition.eval(env).as_bool() {
-                return types::Type::Nil;
+            if condition.eval(env)?.as_bool() {
+                return Ok(types::Type::Nil);
             }
         }
 
-        match &self.else_block {
-            Some(block) => block.eval(env),
-            _ => types::Type::Nil,
+        if let Some(block) = &self.else_block {
+            eval_scoped(&**block, env)?;
+        }
+
+        Ok(types::Type::Nil)
+    }
+}
+
+/// Run loop iteration with fresh loop variables, so closures capture values of their own iteration.
+/// Returns `false` if the loop is interrupted with `break` or `goto`
+fn iterate(
+    block: &dyn expressions::Expression,
+    variables: Vec<(String, types::Type)>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<bool> {
+    let mut local_env = loop_scope(env);
+
+    for (name, value) in variables {
+        local_env.borrow_mut().add_variable(name, value);
+    }
+
+    block.eval(&mut local_env)?;
+
+    Ok(!interrupted(&local_env, env))
+}
+
+impl blocks::NumericalForBlock {
+    /// Evaluate loop parameter. Strings are converted to floats
+    fn parameter(
+        &self,
+        exp: &dyn expressions::Expression,
+        name: &str,
+        env: &mut utils::Shared<environment::Environment>,
+    ) -> error::Result<types::Type> {
+        let value = exp.eval(env)?;
+
+        match value.unref() {
+            types::Type::Number(_) | types::Type::Integer(_) => Ok(value.value()),
+            value => value
+                .to_float()
+                .map(types::Type::Number)
+                .ok_or_else(|| interpreter::Eval::runtime_error(self, format!("'for' {} must be a number", name))),
         }
     }
 }
 
+/// Integer limit of the integer loop. Float limits are rounded toward the initial value and clipped
+/// to the integer range. Returns `None` if the loop must not run, because the limit is out of range
+fn integer_limit(limit: &types::Type, step: i64) -> Option<i64> {
+    let limit = match limit {
+        types::Type::Number(limit) if step < 0 => limit.ceil(),
+        types::Type::Number(limit) => limit.floor(),
+        limit => return limit.to_integer(),
+    };
+
+    match types::float_to_integer(limit) {
+        Some(limit) => Some(limit),
+        None if limit > 0f64 => (step > 0).then_some(i64::MAX),
+        None => (step < 0).then_some(i64::MIN),
+    }
+}
+
 // pub struct NumericalForBlock {
-//     pub init: Box<dyn expressions::Expression>,
+//     pub var_name: Box<dyn expressions::Expression>,
+//     pub init_value: Box<dyn expressions::Expression>,
 //     pub limit: Box<dyn expressions::Expression>,
 //     pub step: Option<Box<dyn expressions::Expression>>,
 //     pub block: Box<dyn expressions::Expression>,
 // }
 impl interpreter::Eval for blocks::NumericalForBlock {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         // Statement initialization
-        let eval_name = self.var_name.eval(env);
+        let eval_name = self.var_name.eval(env)?;
         let var_name = match_type!(&eval_name,
-            types::Type::String(value) => value.clone(),
-            _ => self.runtime_error(format!("{:?} cannot be used as `for` statement variable name", self.var_name))
+            types::Type::String(value) => value.to_string(),
+            _ => return Err(self.runtime_error(format!("{:?} cannot be used as `for` statement variable name", self.var_name)))
         );
 
-        let mut get_num = |exp: &dyn expressions::Expression, value_type| -> f64 {
-            let evaluated = exp.eval(env);
-            match_type!(&evaluated,
-                types::Type::Number(value) => *value,
-                _ => self.runtime_error(format!("{:?} cannot be used as `for` statement {} value", exp, value_type))
-            )
+        let init = self.parameter(self.init_value.as_ref(), "initial value", env)?;
+        let limit = self.parameter(self.limit.as_ref(), "limit", env)?;
+        let step = match &self.step {
+            Some(step) => self.parameter(step.as_ref(), "step", env)?,
+            None => types::Type::Integer(1),
         };
 
-        let init_num = get_num(self.init_value.as_ref(), "initial");
-        let limit_num = get_num(self.limit.as_ref(), "limit");
+        if step.to_float() == Some(0f64) {
+            return Err(self.runtime_error("'for' step is zero".to_string()));
+        }
 
-        let step_num = if let Some(step) = &self.step {
-            get_num(step.as_ref(), "step")
-        } else {
-            1f64
-        };
+        match (init, step) {
+            // Loop counter is an integer, if initial value and step are integers
+            (types::Type::Integer(init), types::Type::Integer(step)) => {
+                let limit = match integer_limit(&limit, step) {
+                    Some(limit) if (step > 0 && init <= limit) || (step < 0 && init >= limit) => limit,
+                    _ => return Ok(types::Type::Nil),
+                };
 
-        // Creating local env and assigning initial value
-        let mut local_env = utils::Shared::new(environment::Environment::new(
-            Some(env.clone()),
-            environment::BreakFlag::Break(false),
-        ));
+                // Number of iterations is computed beforehand, so the counter never overflows
+                let distance = if step > 0 {
+                    (limit as u64).wrapping_sub(init as u64)
+                } else {
+                    (init as u64).wrapping_sub(limit as u64)
+                };
+                let count = distance / step.unsigned_abs();
 
-        let mut i = init_num;
-        let counter_ref = local_env
-                .borrow_mut()
-                .add_variable(var_name.clone(), types::Type::Number(i));
+                let mut counter = init;
+                for _ in 0..=count {
+                    let variables = vec![(var_name.clone(), types::Type::Integer(counter))];
+                    if !iterate(self.block.as_ref(), variables, env)? {
+                        break;
+                    }
 
-        while (i - limit_num).abs() > std::f64::EPSILON {
-            counter_ref.replace(types::Type::Number(i));
+                    counter = counter.wrapping_add(step);
+                }
+            }
+            (init, step) => {
+                let (init, limit, step) = (
+                    init.to_float().unwrap(),
+                    limit.to_float().unwrap(),
+                    step.to_float().unwrap(),
+                );
 
-            self.block.eval(&mut local_env);
+                let mut counter = init;
+                while (step > 0f64 && counter <= limit) || (step < 0f64 && counter >= limit) {
+                    let variables = vec![(var_name.clone(), types::Type::Number(counter))];
+                    if !iterate(self.block.as_ref(), variables, env)? {
+                        break;
+                    }
 
-            // Check if broken
-            if let environment::BreakFlag::Break(true) = local_env.borrow().break_flag() {
+                    counter += step;
+                }
+            }
+        }
+
+        Ok(types::Type::Nil)
+    }
+}
+
+// pub struct GenericForBlock {
+//     pub namelist: VecDeque<Box<dyn expressions::Expression>>,
+//     pub explist: VecDeque<Box<dyn expressions::Expression>>,
+//     pub block: Box<dyn expressions::Expression>,
+// }
+impl interpreter::Eval for blocks::GenericForBlock {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let mut names = Vec::new();
+        for exp in &self.namelist {
+            match exp.eval(env)? {
+                types::Type::String(name) => names.push(name.to_string()),
+                _ => return Err(self.runtime_error(format!("{:?} cannot be used as `for` statement variable name", exp))),
+            }
+        }
+
+        // Expressions are adjusted to iterator function, invariant state and initial control variable
+        let mut values = expression::eval_list(&self.explist, env)?;
+        let iterator = values.pop_front().unwrap_or(types::Type::Nil);
+        let state = values.pop_front().unwrap_or(types::Type::Nil);
+        let mut control = values.pop_front().unwrap_or(types::Type::Nil);
+
+        if !functions::is_callable(&iterator) {
+            return Err(self.runtime_error(format!(
+                "attempt to call a {} value ({})",
+                iterator.type_name(),
+                environment::FunctionName::ForIterator
+            )));
+        }
+
+        // Iterator is called at the position of the expressions list
+        let span = self.explist.front().and_then(|exp| exp.span()).cloned();
+
+        loop {
+            let args = VecDeque::from(vec![state.clone(), control.clone()]);
+            let mut results = match &span {
+                Some(span) => functions::call_with_frame(
+                    self,
+                    iterator.clone(),
+                    environment::FunctionName::ForIterator,
+                    span.clone(),
+                    args,
+                    env,
+                )?,
+                None => functions::call(self, iterator.clone(), args, env)?,
+            };
+
+            control = results.front().map_or(types::Type::Nil, types::Type::value);
+            if control.is_nil() {
                 break;
             }
 
-            i += step_num;
+            let variables = names
+                .iter()
+                .map(|name| (name.clone(), results.pop_front().map_or(types::Type::Nil, |value| value.value())))
+                .collect();
+
+            if !iterate(self.block.as_ref(), variables, env)? {
+                break;
+            }
         }
 
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
@@ -1,17 +1,79 @@
-use crate::ast::expressions::expression;
+use std::collections::VecDeque;
+
+use crate::ast::expressions::{self, expression};
+use crate::error;
 
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
+/// Evaluate expressions list. Only the last expression expands to all its values,
+/// other expressions are truncated to the first value. Results are copies of values, not references
+pub fn eval_list(
+    expressions: &VecDeque<Box<dyn expressions::Expression>>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<VecDeque<types::Type>> {
+    let mut result = VecDeque::new();
+
+    for (index, exp) in expressions.iter().enumerate() {
+        if index + 1 == expressions.len() {
+            result.extend(exp.eval_multi(env)?.iter().map(types::Type::value));
+        } else {
+            result.push_back(exp.eval(env)?.value());
+        }
+    }
+
+    Ok(result)
+}
+
+/// Add description of the expression to the error message, like `attempt to call a nil value (global 'f')`.
+/// Only variables and fields with constant names can be described
+pub fn describe(
+    message: String,
+    expression: &dyn expressions::Expression,
+    env: &utils::Shared<environment::Environment>,
+) -> String {
+    if let Some(name) = expression.name() {
+        let kind = if env.borrow().is_local(name) { "local" } else { "global" };
+        format!("{} ({} '{}')", message, kind, name)
+    } else if let Some(name) = expression.field_name() {
+        format!("{} (field '{}')", message, name)
+    } else {
+        message
+    }
+}
+
+/// Name of the called function for stack tracebacks, as the function expression is written at the call site
+pub fn function_name(
+    expression: &dyn expressions::Expression,
+    env: &utils::Shared<environment::Environment>,
+) -> environment::FunctionName {
+    if let Some(name) = expression.name() {
+        if env.borrow().is_local(name) {
+            environment::FunctionName::Local(name.to_string())
+        } else {
+            environment::FunctionName::Global(name.to_string())
+        }
+    } else if let Some(name) = expression.field_name() {
+        environment::FunctionName::Field(name.to_string())
+    } else {
+        environment::FunctionName::Anonymous(None)
+    }
+}
+
 impl interpreter::Eval for expression::Expressions {
     // struct Expressions(VecDeque<Box<dyn expressions::Expression>>);
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let mut result = types::Type::Nil;
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(self.eval_multi(env)?.pop_front().unwrap_or(types::Type::Nil))
+    }
 
-        for exp in &self.0 {
-            result = exp.eval(env)
-        }
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        eval_list(&self.0, env)
+    }
+}
 
-        result
+impl interpreter::Eval for expression::Parentheses {
+    // struct Parentheses(Box<dyn expressions::Expression>);
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        self.0.eval(env)
     }
 }
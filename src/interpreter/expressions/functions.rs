@@ -1,6 +1,9 @@
 use std::collections::VecDeque;
 
 use crate::ast::expressions::{self, function};
+use crate::ast::span::Span;
+use crate::error;
+use crate::interpreter::expressions::{expression, tables};
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
@@ -10,119 +13,186 @@ impl interpreter::Eval for function::Closure {
     //     pub varargs: bool,
     //     pub body: Rc<Box<dyn expressions::Expression>>,
     // }
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let parameters: Vec<String> = self
-            .params
-            .iter()
-            .map(|exp| {
-                if let types::Type::String(string) = exp.eval(env) {
-                    string
-                } else {
-                    self.runtime_error(format!(
-                        "Function arguments contains not a string, but {:?}",
-                        exp
-                    ));
-                }
-            })
-            .collect();
-
-        types::Type::Function {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let mut parameters = Vec::new();
+
+        for exp in &self.params {
+            if let types::Type::String(string) = exp.eval(env)? {
+                parameters.push(string.to_string())
+            } else {
+                return Err(self.runtime_error(format!(
+                    "Function arguments contains not a string, but {:?}",
+                    exp
+                )));
+            }
+        }
+
+        Ok(types::Type::Function {
             id: env.borrow_mut().next_global_id(),
             parameters,
             varargs: self.varargs,
             body: self.body.clone(),
             env: env.clone(),
-        }
+        })
     }
 }
 
-fn eval_args(
-    args: &VecDeque<Box<dyn expressions::Expression>>,
+/// Call function with evaluated arguments. Tables with `__call` metamethod may be called as well.
+/// Too deep recursion fails with "stack overflow" error, which may be caught by `pcall`
+pub fn call(
+    this: &dyn interpreter::Eval,
+    function: types::Type,
+    args: VecDeque<types::Type>,
     call_env: &mut utils::Shared<environment::Environment>,
-) -> VecDeque<types::Type> {
-    let mut result = VecDeque::new();
-
-    for arg in args {
-        match arg.eval(call_env) {
-            types::Type::Vector(vec) => result.extend(vec.into_iter()),
-            value => result.push_back(value),
-        }
-    }
+) -> error::Result<VecDeque<types::Type>> {
+    call_env.borrow().enter_function().map_err(|error| this.runtime_error(error))?;
+    let result = call_unchecked(this, function, args, call_env);
+    call_env.borrow().leave_function();
 
     result
 }
 
-fn call_function(
-    this: &dyn expressions::Expression,
-    call_object: Option<types::Type>,
+fn call_unchecked(
+    this: &dyn interpreter::Eval,
     function: types::Type,
-    args: &VecDeque<Box<dyn expressions::Expression>>,
+    mut args: VecDeque<types::Type>,
     call_env: &mut utils::Shared<environment::Environment>,
-) -> types::Type {
+) -> error::Result<VecDeque<types::Type>> {
     match_type!(&function,
         types::Type::Function { parameters, varargs, body, env, .. } => {
             let mut local_env = environment::Environment::new(Some(env.clone()), environment::BreakFlag::Return(None));
-            let mut args = eval_args(args, call_env);
-
-            // self
-            if let Some(obj) = call_object {
-                args.push_front(obj)
-            }
 
-            // Bing args to parameters
+            // Bing args to parameters. Missing arguments are nils
             for parameter in parameters {
-                if let Some(arg) = args.pop_front() {
-                    local_env.add_variable(parameter.clone(), arg);
-                }
+                local_env.add_variable(parameter.clone(), args.pop_front().unwrap_or(types::Type::Nil));
             }
 
-            // Varargs
+            // Extra arguments are accessible with `...`
             if *varargs {
-                local_env.add_variable("arg".to_string(), types::Type::Vector(args));
+                local_env.set_varargs(args);
             }
 
             let mut shared_env = utils::Shared::new(local_env);
-            body.eval(&mut shared_env);
+            body.eval(&mut shared_env)?;
 
             let mut borrow = shared_env.borrow_mut();
-            borrow.retval()
+            // Labels are not visible outside of the function
+            if let Some(label) = borrow.take_goto() {
+                return Err(this.runtime_error(format!("No visible label '{}' for `goto`", label)))
+            }
+
+            Ok(borrow.retval())
         },
-        _ => this.runtime_error(format!("Cannot call {:?}, not a function", function))
+        types::Type::NativeFunction { function, .. } => {
+            // Function may reassign variable, which holds it. Keep our own reference
+            let function = function.clone();
+
+            function(call_env, args)
+        },
+        _ => {
+            // Callable object receives itself as the first argument
+            if let Some(handler) = function.metamethod("__call") {
+                args.push_front(function.value());
+                call(this, handler, args, call_env)
+            } else {
+                Err(this.runtime_error(format!("attempt to call a {} value", function.type_name())))
+            }
+        }
     )
 }
 
+/// Functions and objects with `__call` metamethod can be called
+pub fn is_callable(value: &types::Type) -> bool {
+    match value.unref() {
+        types::Type::Function { .. } | types::Type::NativeFunction { .. } => true,
+        value => value.metamethod("__call").is_some(),
+    }
+}
+
+fn call_function(
+    this: &function::Funcall,
+    call_object: Option<types::Type>,
+    function: types::Type,
+    name: environment::FunctionName,
+    args: &VecDeque<Box<dyn expressions::Expression>>,
+    call_env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<VecDeque<types::Type>> {
+    let mut args = expression::eval_list(args, call_env)?;
+
+    // self
+    if let Some(obj) = call_object {
+        args.push_front(obj)
+    }
+
+    call_with_frame(this, function, name, this.span.clone(), args, call_env)
+}
+
+/// Call function, registering the call at the `span` in the call stack.
+/// Call frame is removed even if function fails, so catched errors don't break the call stack.
+/// Errors, raised by natives, get traceback while the native is still on the stack
+pub fn call_with_frame(
+    this: &dyn interpreter::Eval,
+    function: types::Type,
+    name: environment::FunctionName,
+    span: Span,
+    args: VecDeque<types::Type>,
+    call_env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<VecDeque<types::Type>> {
+    call_env.borrow().enter_call(environment::CallFrame {
+        span,
+        function: name.resolve(&function),
+    });
+    let result = call(this, function, args, call_env)
+        .map_err(|error| error.with_traceback(|| call_env.borrow().traceback(None, 1)));
+    call_env.borrow().leave_call();
+
+    result
+}
+
 impl interpreter::Eval for function::Funcall {
     // pub struct Funcall {
     //     pub object: Box<dyn expressions::Expression>,
     //     pub args: VecDeque<Box<dyn expressions::Expression>>,
     //     pub method: Option<Box<dyn expressions::Expression>>,
+    //     pub span: Span,
     // }
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let call_object = self.object.eval(env);
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(self.eval_multi(env)?.pop_front().unwrap_or(types::Type::Nil))
+    }
 
-        // First let check if we have method name, in this case we expect table on method inside
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        let call_object = self.object.eval(env)?.value();
+
+        // First let check if we have method name, in this case we expect object with method inside
         if let Some(ref method_exp) = self.method {
-            let method_name = method_exp.eval(env);
+            let method_name = method_exp.eval(env)?;
 
             match_type!(&method_name,
                 types::Type::String(_) => {
-                    // This must be a table, because we call its method
-                    match_type!(&call_object,
-                        types::Type::Table{ ref map, .. } => {
-                            if let Some(method) = map.get(&method_name).cloned() {
-                                call_function(self, Some(call_object), types::Type::Reference(method), &self.args, env)
-                            } else {
-                                self.runtime_error(format!("Object doesn't contain method {:?}", method_name))
-                            }
-                        },
-                        _ => self.runtime_error(format!("Method call object is not a table, but {:?}", call_object))
-                    )
+                    let method = tables::index(self, call_object.clone(), Some(self.object.as_ref()), method_name.value(), env)?;
+
+                    if !is_callable(&method) {
+                        return Err(self.runtime_error(format!(
+                            "attempt to call a {} value (method '{}')",
+                            method.type_name(),
+                            method_exp.string_literal().unwrap_or_default()
+                        )))
+                    }
+
+                    let name = environment::FunctionName::Method(method_exp.string_literal().unwrap_or_default().to_string());
+                    call_function(self, Some(call_object), method, name, &self.args, env)
                 },
-                _ => self.runtime_error(format!("Method call method name is not a string, but {:?}", method_name))
+                _ => Err(self.runtime_error(format!("Method call method name is not a string, but {:?}", method_name)))
             )
         // Function
         } else {
-            call_function(self, None, call_object, &self.args, env)
+            if !is_callable(&call_object) {
+                let message = format!("attempt to call a {} value", call_object.type_name());
+                return Err(self.runtime_error(expression::describe(message, self.object.as_ref(), env)))
+            }
+
+            let name = expression::function_name(self.object.as_ref(), env);
+            call_function(self, None, call_object, name, &self.args, env)
         }
     }
 }
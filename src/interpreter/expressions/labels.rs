@@ -0,0 +1,30 @@
+use crate::ast::expressions::labels;
+use crate::error;
+use crate::interpreter::{self, environment, types};
+use crate::utils;
+
+// pub struct Label(pub Box<dyn expressions::Expression>);
+impl interpreter::Eval for labels::Label {
+    // Labels are resolved by blocks. See `blocks::Block` evaluation
+    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(types::Type::Nil)
+    }
+}
+
+// pub struct Goto(pub Box<dyn expressions::Expression>);
+impl interpreter::Eval for labels::Goto {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let label = match self.0.name() {
+            Some(label) => label.to_string(),
+            _ => return Err(self.runtime_error(format!("{:?} cannot be used as a label name", self.0))),
+        };
+
+        // Interrupt execution. Blocks will look for the label from innermost to outermost
+        env.borrow_mut().break_execution(environment::BreakFlag::Goto(
+            label,
+            Box::new(environment::BreakFlag::None),
+        ));
+
+        Ok(types::Type::Nil)
+    }
+}
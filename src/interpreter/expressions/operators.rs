@@ -1,48 +1,144 @@
-use std::clone::Clone;
+use std::cmp::Ordering;
 
-use crate::ast::expressions::operators;
+use crate::ast::expressions::{self, operators};
 use crate::ast::lexer::tokens::Keyword;
+use crate::error;
 
+use crate::interpreter::expressions::expression;
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
+/// Message about the operand of a wrong type. The operand is named the way index errors do,
+/// like `attempt to perform arithmetic on a nil value (local 'x')`
+fn operand_error(
+    action: &str,
+    value: &types::Type,
+    operand: &dyn expressions::Expression,
+    env: &utils::Shared<environment::Environment>,
+) -> String {
+    let message = format!("attempt to {} a {} value", action, value.type_name());
+    expression::describe(message, operand, env)
+}
+
+/// Left and right operand expressions of a binary operator
+type Operands<'a> = (&'a dyn expressions::Expression, &'a dyn expressions::Expression);
+
+/// Message about values, which can't be compared, like `attempt to compare number with nil`
+fn compare_error(left: &types::Type, right: &types::Type) -> String {
+    let (left, right) = (left.type_name(), right.type_name());
+
+    if left == right {
+        format!("attempt to compare two {} values", left)
+    } else {
+        format!("attempt to compare {} with {}", left, right)
+    }
+}
+
+/// Call unary metamethod. Lua passes operand twice to unary metamethods
+fn call_unary_metamethod(
+    exp: &dyn interpreter::Eval,
+    event: &str,
+    value: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<Option<types::Type>> {
+    let metamethod = match value.metamethod(event) {
+        Some(metamethod) => metamethod,
+        None => return Ok(None),
+    };
+
+    Ok(Some(metamethod.call(exp, vec![value, value], env)?.pop_front().unwrap_or(types::Type::Nil)))
+}
+
+/// Call binary metamethod. Metamethod is looked up in the left operand first and then in the right one
+fn call_binary_metamethod(
+    exp: &dyn interpreter::Eval,
+    event: &str,
+    left: &types::Type,
+    right: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<Option<types::Type>> {
+    let metamethod = match left.metamethod(event).or_else(|| right.metamethod(event)) {
+        Some(metamethod) => metamethod,
+        None => return Ok(None),
+    };
+
+    Ok(Some(metamethod.call(exp, vec![left, right], env)?.pop_front().unwrap_or(types::Type::Nil)))
+}
+
+/// Floor division of integers. Quotient is rounded toward minus infinity
+fn floor_div(left: i64, right: i64) -> i64 {
+    let quotient = left.wrapping_div(right);
+    if left.wrapping_rem(right) != 0 && (left ^ right) < 0 {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// Modulo of integers. Result has the sign of the divisor
+fn floor_mod(left: i64, right: i64) -> i64 {
+    let remainder = left.wrapping_rem(right);
+    if remainder != 0 && (remainder ^ right) < 0 {
+        remainder + right
+    } else {
+        remainder
+    }
+}
+
+/// Modulo of floats. Result has the sign of the divisor
+fn float_mod(left: f64, right: f64) -> f64 {
+    let remainder = left % right;
+    if (remainder > 0f64 && right < 0f64) || (remainder < 0f64 && right > 0f64) {
+        remainder + right
+    } else {
+        remainder
+    }
+}
+
 impl interpreter::Eval for operators::Unop {
     // unop ::= ‘-’ | not | ‘#’ | ‘~’
     // Keyword::MINUS, Keyword::NOT, Keyword::HASH, Keyword::TILDA
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let value = self.1.eval(env);
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let value = self.1.eval(env)?;
+        let operand = self.1.as_ref();
 
         // Keyword
         match self.0 {
-            Keyword::MINUS => match_type!(&value,
-                types::Type::Number(number) => types::Type::Number(-number),
-                types::Type::Table { ref metatable, .. } => {
-                    if let Some(metamethod) = metatable.get("__unm") {
-                        metamethod.call(vec![&value])
-                    } else {
-                        self.runtime_error(format!("{:?} metatable doesn't contain `__unm` function", value))
-                    }
-                },
-                _ => self.runtime_error(format!("Can't negate {:?} value", value))
-            ),
-            Keyword::NOT => types::Type::Boolean(!value.as_bool()),
-            Keyword::HASH => match_type!(&value,
-                types::Type::String(string) => types::Type::Number(string.as_bytes().len() as f64),
-                types::Type::Table { border, ref metatable, .. } => {
-                    if let Some(metamethod) = metatable.get("__len") {
-                        metamethod.call(vec![&value])
-                    } else {
-                        types::Type::Number(*border as f64)
-                    }
-                },
-                _ => {
-                    self.runtime_error(format!("Can't get length of {:?} value", value));
+            Keyword::MINUS => {
+                if let Some(number) = value.to_number() {
+                    Ok(match number {
+                        types::Type::Integer(integer) => types::Type::Integer(integer.wrapping_neg()),
+                        number => types::Type::Number(-number.to_float().unwrap_or_default()),
+                    })
+                } else if let Some(result) = call_unary_metamethod(self, "__unm", &value, env)? {
+                    Ok(result)
+                } else {
+                    Err(self.runtime_error(operand_error("perform arithmetic on", &value, operand, env)))
+                }
+            }
+            Keyword::NOT => Ok(types::Type::Boolean(!value.as_bool())),
+            Keyword::HASH => {
+                if let Some(result) = call_unary_metamethod(self, "__len", &value, env)? {
+                    return Ok(result);
+                }
+
+                match_type!(&value,
+                    types::Type::String(string) => Ok(types::Type::Integer(string.len() as i64)),
+                    types::Type::Table(table) => Ok(types::Type::Integer(table.borrow().border() as i64)),
+                    _ => Err(self.runtime_error(operand_error("get length of", &value, operand, env)))
+                )
+            }
+            Keyword::TILDA => {
+                if let Some(integer) = value.to_integer() {
+                    Ok(types::Type::Integer(!integer))
+                } else if let Some(result) = call_unary_metamethod(self, "__bnot", &value, env)? {
+                    Ok(result)
+                } else if value.to_number().is_some() {
+                    Err(self.runtime_error("number has no integer representation".to_string()))
+                } else {
+                    Err(self.runtime_error(operand_error("perform bitwise operation on", &value, operand, env)))
                 }
-            ),
-            Keyword::TILDA => match_type!(&value,
-                types::Type::Number(number) => types::Type::Number(!(*number as i64) as f64),
-                _ => self.runtime_error(format!("Can't apply bitwise not to {:?} value", value))
-            ),
+            }
             _ => panic!("Should never happen"),
         }
     }
@@ -51,192 +147,216 @@ impl interpreter::Eval for operators::Unop {
 fn eval_ariphmetic(
     exp: &dyn interpreter::Eval,
     op: &Keyword,
+    operands: Operands,
     left: types::Type,
     right: types::Type,
-) -> types::Type {
-    // Function to convert value for arithmetic operation
-    let normalize = |value, op| -> f64 {
-        match_type!(&value,
-            types::Type::Number(number) => *number,
-            types::Type::String(string) => {
-                if let Ok(number) = string.parse::<f64>() {
-                    number
-                } else {
-                    exp.runtime_error(format!("Can't convert string {:?} to apply {} operator", string, op))
-                }
-            },
-            _ => exp.runtime_error(format!("Can't apply {} operator to {:?} value", op, value))
-        )
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<types::Type> {
+    let (event, symbol) = match op {
+        Keyword::PLUS => ("__add", "+"),
+        Keyword::MINUS => ("__sub", "-"),
+        Keyword::MUL => ("__mul", "*"),
+        Keyword::DIV => ("__div", "/"),
+        Keyword::FLOORDIV => ("__idiv", "//"),
+        Keyword::MOD => ("__mod", "%"),
+        Keyword::POW => ("__pow", "^"),
+        _ => panic!("Should never happen"),
     };
 
-    macro_rules! metatable_binop {
-        ($mt_key: tt, $op: tt, $function: expr) => {{
-            // TODO: unref table
-            if let types::Type::Table { ref metatable, .. } = left {
-                if let Some(metamethod) = metatable.get($mt_key) {
-                    return metamethod.call(vec![&left, &right]);
+    match (left.to_number(), right.to_number()) {
+        // Division and exponentiation always produce floats
+        (Some(types::Type::Integer(leftnum)), Some(types::Type::Integer(rightnum)))
+            if *op != Keyword::DIV && *op != Keyword::POW =>
+        {
+            return Ok(types::Type::Integer(match op {
+                Keyword::PLUS => leftnum.wrapping_add(rightnum),
+                Keyword::MINUS => leftnum.wrapping_sub(rightnum),
+                Keyword::MUL => leftnum.wrapping_mul(rightnum),
+                Keyword::FLOORDIV | Keyword::MOD if rightnum == 0 => {
+                    return Err(exp.runtime_error(format!("attempt to perform 'n{}0'", symbol)))
                 }
-            }
+                Keyword::FLOORDIV => floor_div(leftnum, rightnum),
+                _ => floor_mod(leftnum, rightnum),
+            }));
+        }
+        (Some(leftnum), Some(rightnum)) => {
+            let (leftnum, rightnum) = (leftnum.to_float().unwrap(), rightnum.to_float().unwrap());
 
-            return types::Type::Number($function(normalize(left, $op), normalize(right, $op)));
-        }};
+            return Ok(types::Type::Number(match op {
+                Keyword::PLUS => leftnum + rightnum,
+                Keyword::MINUS => leftnum - rightnum,
+                Keyword::MUL => leftnum * rightnum,
+                Keyword::DIV => leftnum / rightnum,
+                Keyword::FLOORDIV => (leftnum / rightnum).floor(),
+                Keyword::MOD => float_mod(leftnum, rightnum),
+                _ => leftnum.powf(rightnum),
+            }));
+        }
+        _ => (),
     }
 
-    match op {
-        Keyword::PLUS => metatable_binop!("__add", "+", |left, right| left + right),
-        Keyword::MINUS => metatable_binop!("__sub", "-", |left, right| left - right),
-        Keyword::MUL => metatable_binop!("__mul", "*", |left, right| left * right),
-        Keyword::DIV => metatable_binop!("__div", "/", |left, right| left / right),
-        Keyword::FLOORDIV => metatable_binop!("__idiv", "//", |left: f64, right: f64| (left
-            / right)
-            .floor()),
-        Keyword::MOD => metatable_binop!("__mod", "%", |left: f64, right: f64| left % right),
-        Keyword::POW => metatable_binop!("__pow", "^", |left: f64, right: f64| left.powf(right)),
-        _ => panic!("Should never happen"),
+    if let Some(result) = call_binary_metamethod(exp, event, &left, &right, env)? {
+        return Ok(result);
     }
+
+    // Report the operand, which can't be converted
+    let (value, operand) = if left.to_number().is_none() { (&left, operands.0) } else { (&right, operands.1) };
+    Err(exp.runtime_error(operand_error("perform arithmetic on", value, operand, env)))
 }
 
-fn eval_equivalence(
+/// Check values equality. Numbers are compared exactly, so NaN is not equal to anything.
+/// Tables are compared with `__eq` metamethod, if they are not the same table
+fn values_equal(
     exp: &dyn interpreter::Eval,
-    op: &Keyword,
-    left: types::Type,
-    right: types::Type,
-) -> types::Type {
-    fn not(value: types::Type) -> types::Type {
-        types::Type::Boolean(!value.as_bool())
-    }
-
-    match_type!((&left, &right),
-        (types::Type::Number(leftnum), types::Type::Number(rightnum)) => {
-            match op {
-                Keyword::LESS => types::Type::Boolean(leftnum < rightnum),
-                Keyword::LEQ => types::Type::Boolean(leftnum <= rightnum),
-                Keyword::GREATER => types::Type::Boolean(leftnum > rightnum),
-                Keyword::GEQ => types::Type::Boolean(leftnum >= rightnum),
-                Keyword::EQ => types::Type::Boolean((leftnum - rightnum).abs() <= std::f64::EPSILON),
-                Keyword::NEQ => types::Type::Boolean((leftnum - rightnum).abs() > std::f64::EPSILON),
-                _ => panic!("Should never happen")
-            }
-        },
-        (types::Type::String(leftnum), types::Type::String(rightnum)) => {
-            match op {
-                Keyword::LESS => types::Type::Boolean(leftnum < rightnum),
-                Keyword::LEQ => types::Type::Boolean(leftnum <= rightnum),
-                Keyword::GREATER => types::Type::Boolean(leftnum > rightnum),
-                Keyword::GEQ => types::Type::Boolean(leftnum >= rightnum),
-                Keyword::EQ => types::Type::Boolean(leftnum == rightnum),
-                Keyword::NEQ => types::Type::Boolean(leftnum != rightnum),
-                _ => panic!("Should never happen")
+    left: &types::Type,
+    right: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<bool> {
+    match_type!((left, right),
+        (types::Type::Table(_), types::Type::Table(_)) => {
+            if left == right {
+                return Ok(true);
             }
+
+            Ok(call_binary_metamethod(exp, "__eq", left, right, env)?.is_some_and(|result| result.as_bool()))
         },
-        (types::Type::Table { ref metatable, .. }, _) => {
-            macro_rules! metatable_binop {
-                ($mt_key: tt, $op: tt) => {
-                    if let Some(metamethod) = metatable.get($mt_key) {
-                        metamethod.call(vec![&left, &right])
-                    } else {
-                        exp.runtime_error(format!("Can't compare values {:?} and {:?} with {} operator", &left, &right, $op))
-                    }
-                }
-            }
+        _ => Ok(left == right)
+    )
+}
 
+/// Check `left < right`. Numbers and strings are compared by value, other types require `__lt` metamethod
+fn less_than(
+    exp: &dyn interpreter::Eval,
+    left: &types::Type,
+    right: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<bool> {
+    match_type!((left, right),
+        (types::Type::Number(_) | types::Type::Integer(_), types::Type::Number(_) | types::Type::Integer(_)) => {
+            Ok(types::compare_numbers(left, right) == Some(Ordering::Less))
+        },
+        (types::Type::String(leftstr), types::Type::String(rightstr)) => Ok(leftstr < rightstr),
+        _ => match call_binary_metamethod(exp, "__lt", left, right, env)? {
+            Some(result) => Ok(result.as_bool()),
+            None => Err(exp.runtime_error(compare_error(left, right)))
+        }
+    )
+}
 
-            match op {
-                Keyword::LESS => metatable_binop!("__lt", "<"),
-                Keyword::LEQ => metatable_binop!("__le", "<="),
-                Keyword::GREATER => not(metatable_binop!("__le", ">")),
-                Keyword::GEQ => not(metatable_binop!("__lt", ">=")),
-                Keyword::EQ => metatable_binop!("__eq", "=="),
-                Keyword::NEQ => not(metatable_binop!("__eq", "~=")),
-                _ => panic!("Should never happen")
-            }
+/// Check `left <= right`. If there is no `__le` metamethod, we use `not (right < left)`
+fn less_equal(
+    exp: &dyn interpreter::Eval,
+    left: &types::Type,
+    right: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<bool> {
+    match_type!((left, right),
+        (types::Type::Number(_) | types::Type::Integer(_), types::Type::Number(_) | types::Type::Integer(_)) => {
+            Ok(matches!(types::compare_numbers(left, right), Some(Ordering::Less) | Some(Ordering::Equal)))
         },
-        // TODO: Function comparison not implemented
-        _ => types::Type::Boolean(false)
+        (types::Type::String(leftstr), types::Type::String(rightstr)) => Ok(leftstr <= rightstr),
+        _ => {
+            if let Some(result) = call_binary_metamethod(exp, "__le", left, right, env)? {
+                Ok(result.as_bool())
+            } else if let Some(result) = call_binary_metamethod(exp, "__lt", right, left, env)? {
+                Ok(!result.as_bool())
+            } else {
+                Err(exp.runtime_error(compare_error(left, right)))
+            }
+        }
     )
 }
 
-fn eval_bitwise(
+fn eval_equivalence(
     exp: &dyn interpreter::Eval,
     op: &Keyword,
     left: types::Type,
     right: types::Type,
-) -> types::Type {
-    macro_rules! metatable_binop {
-        ($mt_key: tt, $op: tt, $function: expr) => ({
-            match_type!(&left,
-                types::Type::Table { ref metatable, .. } => {
-                    if let Some(metamethod) = metatable.get($mt_key) {
-                        return metamethod.call(vec![&left, &right])
-                    }
-                },
-                _ => ()
-            );
-
-            match_type!((&left, &right),
-                (types::Type::Number(leftnum), types::Type::Number(rightnum)) => {
-                    return types::Type::Number($function(*leftnum as i64, *rightnum as i64) as f64)
-                },
-                _ => exp.runtime_error(format!("Bitwise operator can be applied only to numbers. Got {:?} and {:?}", left, right))
-            )
-        })
-    }
-
-    match op {
-        Keyword::SOR => metatable_binop!("__bor", "+", |left, right| left | right),
-        Keyword::SAND => metatable_binop!("__band", "-", |left, right| left & right),
-        Keyword::TILDA => metatable_binop!("__bxor", "-", |left, right| left ^ right),
-        Keyword::SHRIGHT => metatable_binop!("__bshr", "*", |left, right| left >> right),
-        Keyword::SHLEFT => metatable_binop!("__bshl", "/", |left, right| left << right),
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<types::Type> {
+    Ok(types::Type::Boolean(match op {
+        Keyword::LESS => less_than(exp, &left, &right, env)?,
+        Keyword::LEQ => less_equal(exp, &left, &right, env)?,
+        // `a > b` is translated to `b < a` and `a >= b` is translated to `b <= a`
+        Keyword::GREATER => less_than(exp, &right, &left, env)?,
+        Keyword::GEQ => less_equal(exp, &right, &left, env)?,
+        Keyword::EQ => values_equal(exp, &left, &right, env)?,
+        Keyword::NEQ => !values_equal(exp, &left, &right, env)?,
         _ => panic!("Should never happen"),
+    }))
+}
+
+/// Logical shift left. Negative shift moves bits to the right, all bits are shifted out if shift is too big
+fn shift_left(value: i64, shift: i64) -> i64 {
+    if shift <= -64 || shift >= 64 {
+        0
+    } else if shift >= 0 {
+        ((value as u64) << shift) as i64
+    } else {
+        ((value as u64) >> -shift) as i64
     }
 }
 
-// TODO. `or` Lazy evaluation
-fn eval_boolean(
-    _exp: &operators::Binop,
+fn eval_bitwise(
+    exp: &dyn interpreter::Eval,
     op: &Keyword,
+    operands: Operands,
     left: types::Type,
     right: types::Type,
-) -> types::Type {
-    types::Type::Boolean(match op {
-        Keyword::OR => left.as_bool() || right.as_bool(),
-        Keyword::AND => left.as_bool() && right.as_bool(),
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<types::Type> {
+    let (event, function): (&str, fn(i64, i64) -> i64) = match op {
+        Keyword::SOR => ("__bor", |left, right| left | right),
+        Keyword::SAND => ("__band", |left, right| left & right),
+        Keyword::TILDA => ("__bxor", |left, right| left ^ right),
+        Keyword::SHRIGHT => ("__shr", |left, right| shift_left(left, right.wrapping_neg())),
+        Keyword::SHLEFT => ("__shl", shift_left),
         _ => panic!("Should never happen"),
-    })
+    };
+
+    if let (Some(leftnum), Some(rightnum)) = (left.to_integer(), right.to_integer()) {
+        return Ok(types::Type::Integer(function(leftnum, rightnum)));
+    }
+
+    match call_binary_metamethod(exp, event, &left, &right, env)? {
+        Some(result) => Ok(result),
+        // Floats must have exact integer representation
+        None if left.to_number().is_some() && right.to_number().is_some() => {
+            Err(exp.runtime_error("number has no integer representation".to_string()))
+        }
+        None => {
+            let (value, operand) = if left.to_number().is_none() { (&left, operands.0) } else { (&right, operands.1) };
+            Err(exp.runtime_error(operand_error("perform bitwise operation on", value, operand, env)))
+        }
+    }
 }
 
 fn eval_concat(
     exp: &dyn interpreter::Eval,
     _op: &Keyword,
+    operands: Operands,
     left: types::Type,
     right: types::Type,
-) -> types::Type {
-    fn to_string(value: &types::Type) -> Option<String> {
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<types::Type> {
+    fn to_bytes(value: &types::Type) -> Option<Vec<u8>> {
         match_type!(value,
-            types::Type::Number(num) => Some(num.to_string()),
-            types::Type::String(str) => Some(str.clone()),
+            types::Type::Number(num) => Some(types::format_number(*num).into_bytes()),
+            types::Type::Integer(num) => Some(num.to_string().into_bytes()),
+            types::Type::String(str) => Some(str.to_vec()),
             _ => None
         )
     }
 
-    if let (Some(mut leftstr), Some(rightstr)) = (to_string(&left), to_string(&right)) {
-        leftstr.push_str(rightstr.as_str());
-        types::Type::String(leftstr)
-    } else if let types::Type::Table { ref metatable, .. } = left {
-        if let Some(metamethod) = metatable.get("__concat") {
-            metamethod.call(vec![&left, &right])
-        } else {
-            exp.runtime_error(format!(
-                "{:?} metatable doesn't contain `__concat` function",
-                left
-            ))
-        }
+    let (leftstr, rightstr) = (to_bytes(&left), to_bytes(&right));
+    if let (Some(mut leftstr), Some(rightstr)) = (leftstr.clone(), rightstr) {
+        leftstr.extend_from_slice(&rightstr);
+        Ok(types::Type::String(leftstr.into()))
+    } else if let Some(result) = call_binary_metamethod(exp, "__concat", &left, &right, env)? {
+        Ok(result)
     } else {
-        exp.runtime_error(format!(
-            "Concat operator can be applied only to strings, numbers or table. Got {:?} and {:?}",
-            left, right
-        ))
+        let (value, operand) = if leftstr.is_none() { (&left, operands.0) } else { (&right, operands.1) };
+        Err(exp.runtime_error(operand_error("concatenate", value, operand, env)))
     }
 }
 
@@ -263,12 +383,21 @@ impl interpreter::Eval for operators::Binop {
     // [Keyword::PLUS, Keyword::MINUS],
     // [Keyword::MUL, Keyword::DIV, Keyword::FLOOR_DIV, Keyword::MOD],
     // [Keyword::POW]
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         let operators::Binop(op, left, right) = self;
 
-        let left_value = left.eval(env);
-        // TODO: Lazy evaluation!!!
-        let right_value = right.eval(env);
+        let left_value = left.eval(env)?;
+
+        // `and` and `or` return the deciding operand. Right operand is evaluated only if it decides the result
+        match op {
+            Keyword::AND if !left_value.as_bool() => return Ok(left_value.value()),
+            Keyword::OR if left_value.as_bool() => return Ok(left_value.value()),
+            Keyword::AND | Keyword::OR => return Ok(right.eval(env)?.value()),
+            _ => (),
+        }
+
+        let right_value = right.eval(env)?;
+        let operands = (left.as_ref(), right.as_ref());
 
         match op {
             Keyword::PLUS
@@ -277,25 +406,24 @@ impl interpreter::Eval for operators::Binop {
             | Keyword::DIV
             | Keyword::FLOORDIV
             | Keyword::MOD
-            | Keyword::POW => eval_ariphmetic(self, op, left_value, right_value),
-            Keyword::OR | Keyword::AND => eval_boolean(self, op, left_value, right_value),
+            | Keyword::POW => eval_ariphmetic(self, op, operands, left_value, right_value, env),
             Keyword::LESS
             | Keyword::LEQ
             | Keyword::GREATER
             | Keyword::GEQ
             | Keyword::EQ
-            | Keyword::NEQ => eval_equivalence(self, op, left_value, right_value),
+            | Keyword::NEQ => eval_equivalence(self, op, left_value, right_value, env),
             Keyword::SOR | Keyword::TILDA | Keyword::SAND | Keyword::SHRIGHT | Keyword::SHLEFT => {
-                eval_bitwise(self, op, left_value, right_value)
+                eval_bitwise(self, op, operands, left_value, right_value, env)
             }
-            Keyword::DOT2 => eval_concat(self, op, left_value, right_value),
+            Keyword::DOT2 => eval_concat(self, op, operands, left_value, right_value, env),
             _ => panic!("Should never happen"),
         }
     }
 }
 
 impl interpreter::Eval for operators::Noop {
-    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        types::Type::Nil
+    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(types::Type::Nil)
     }
 }
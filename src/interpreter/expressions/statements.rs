@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
+
 use crate::ast::expressions::statements;
+use crate::error;
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
@@ -8,22 +11,36 @@ impl interpreter::Eval for statements::Statement {
     //     Ellipsis,
     //     Return(Option<Box<dyn expressions::Expression>>),
     // }
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         match &self {
             statements::Statement::Break => {
                 env.borrow_mut()
                     .break_execution(environment::BreakFlag::Break(true));
-                types::Type::Nil
+                Ok(types::Type::Nil)
+            }
+            _ => Ok(self.eval_multi(env)?.pop_front().unwrap_or(types::Type::Nil)),
+        }
+    }
+
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        match &self {
+            statements::Statement::Ellipsis => {
+                let varargs = env.borrow().varargs();
+
+                match varargs {
+                    Some(varargs) => Ok(varargs),
+                    None => Err(self.runtime_error("Cannot use '...' outside a vararg function".to_string())),
+                }
             }
-            statements::Statement::Ellipsis => unimplemented!("Hey, do you wanna some varargs?"),
             statements::Statement::Return(retval) => {
-                // Block already handles return mechanism, so we just return value
+                // Block already handles return mechanism, so we just return values
                 if let Some(expression) = retval {
-                    expression.eval(env)
+                    expression.eval_multi(env)
                 } else {
-                    types::Type::Nil
+                    Ok(VecDeque::new())
                 }
             }
+            _ => Ok(VecDeque::from(vec![self.eval(env)?])),
         }
     }
 }
@@ -1,126 +1,228 @@
-use std::cell::RefCell;
-use std::clone::Clone;
-use std::collections::{HashMap, VecDeque};
-use std::rc::Rc;
+use std::collections::VecDeque;
 
-use crate::ast::expressions::tables;
+use crate::ast::expressions::{self, tables};
+use crate::error;
+use crate::interpreter::expressions::{expression, functions};
 use crate::interpreter::{self, environment, types};
 use crate::utils;
 
-type TableHashMap = HashMap<types::Type, Rc<RefCell<types::Type>>>;
-
-fn update_table_border(table: &TableHashMap, border: &mut usize) {
-    while table.contains_key(&types::Type::Number((*border + 1) as f64)) {
-        *border += 1;
-    }
-}
+/// Maximum length of `__index` and `__newindex` chains. Protects us from metatables loops
+const MAX_METATABLE_CHAIN: usize = 2000;
 
 impl interpreter::Eval for tables::Table {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let mut map: TableHashMap = HashMap::new();
-        let mut border: usize = 0;
-
-        for field_expression in &self.0 {
-            if let types::Type::Vector(mut key_value) = field_expression.eval(env) {
-                // Key AND value
-                if key_value.len() == 2 {
-                    let value = key_value.pop_back().unwrap();
-                    let key = key_value.pop_back().unwrap();
-
-                    map.insert(key, Rc::new(RefCell::new(value)));
-                    update_table_border(&map, &mut border);
-                // Only key
-                } else if key_value.len() == 1 {
-                    let mut key: types::Type;
-                    let value = key_value.pop_back().unwrap();
-
-                    loop {
-                        border += 1;
-                        key = types::Type::Number(border as f64);
-
-                        if !map.contains_key(&key) {
-                            break;
-                        }
-                    }
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let mut table = types::Table::new(env.borrow_mut().next_global_id());
+        // Positional fields are numbered in order, regardless of other fields
+        let mut position = 0;
+
+        for (index, field_expression) in self.0.iter().enumerate() {
+            // Only the last field expands to multiple values
+            let fields = if index + 1 == self.0.len() {
+                field_expression.eval_multi(env)?
+            } else {
+                VecDeque::from(vec![field_expression.eval(env)?])
+            };
 
-                    map.insert(key, Rc::new(RefCell::new(value)));
-                    update_table_border(&map, &mut border);
+            for field in fields {
+                if let types::Type::Vector(mut key_value) = field {
+                    // Key AND value
+                    let (key, value) = if key_value.len() == 2 {
+                        let value = key_value.pop_back().unwrap();
+                        (key_value.pop_back().unwrap(), value)
+                    // Only value
+                    } else if key_value.len() == 1 {
+                        position += 1;
+                        (types::Type::Integer(position), key_value.pop_back().unwrap())
+                    } else {
+                        panic!("Internal interpreter error. Table constructor returns invalid number of elements: {}", key_value.len());
+                    };
+
+                    table.set(key, value).map_err(|error| self.runtime_error(error))?;
                 } else {
-                    panic!("Internal interpreter error. Table constructor returns invalid number of elements: {}", key_value.len());
+                    panic!("Internal interpreter error. Table constructor returns not a vector");
                 }
-            } else {
-                panic!("Internal interpreter error. Table constructor returns not a vector");
             }
         }
 
-        types::Type::Table {
-            id: env.borrow_mut().next_global_id(),
-            map,
-            metatable: HashMap::new(),
-            border,
-        }
+        Ok(types::Type::Table(utils::Shared::new(table)))
     }
 }
 
 impl interpreter::Eval for tables::TableField {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         let mut result_vector: VecDeque<types::Type> = VecDeque::new();
 
         if let Some(ref expression) = self.key {
-            let key = expression.eval(env);
+            let key = expression.eval(env)?;
 
             if key.is_nil() {
-                self.runtime_error("Cannot use `nil` as a table key".to_string())
+                return Err(self.runtime_error("Cannot use `nil` as a table key".to_string()))
             }
 
-            result_vector.push_back(key);
+            result_vector.push_back(key.value());
         }
 
-        result_vector.push_back(self.value.eval(env));
+        result_vector.push_back(self.value.eval(env)?.value());
+
+        Ok(types::Type::Vector(result_vector))
+    }
+
+    /// Positional field expands to a field for each value
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        if self.key.is_some() {
+            return Ok(VecDeque::from(vec![self.eval(env)?]));
+        }
 
-        types::Type::Vector(result_vector)
+        Ok(self
+            .value
+            .eval_multi(env)?
+            .iter()
+            .map(|value| types::Type::Vector(VecDeque::from(vec![value.value()])))
+            .collect())
     }
 }
 
-impl interpreter::Eval for tables::Indexing {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        let table = self.object.eval(env).into_reference();
-        // This is bullshit. WTF Ref
-        let mut table_borrow = table.borrow_mut();
-
-        // We can index only tables
-        if let types::Type::Table {
-            ref mut map,
-            ref mut border,
-            id,
-            ..
-        } = *table_borrow
-        {
-            if let Some(cached_value) = self.cache.borrow().get(id) {
-                return cached_value
-            }
+fn index_error(
+    value: &types::Type,
+    object_expression: Option<&dyn expressions::Expression>,
+    env: &utils::Shared<environment::Environment>,
+) -> String {
+    let message = format!("attempt to index a {} value", value.type_name());
 
-            let key = self.index.eval(env);
+    match object_expression {
+        Some(object_expression) => expression::describe(message, object_expression, env),
+        None => message,
+    }
+}
 
-            // If we have this entry, return reference to it
-            return if let Some(result) = map.get(&key).cloned() {
-                self.cache.borrow_mut().set(id, &result);
+/// Get object field value. If table doesn't contain the field, we follow `__index` metamethods chain.
+/// `object_expression` is the source of the object, used to describe it in error messages
+pub fn index(
+    this: &dyn interpreter::Eval,
+    object: types::Type,
+    object_expression: Option<&dyn expressions::Expression>,
+    key: types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<types::Type> {
+    let mut object = object;
+    let mut object_expression = object_expression;
+
+    for _ in 0..MAX_METATABLE_CHAIN {
+        // Only the first object of the chain comes from the expression
+        let described = object_expression.take();
+        let handler = match object.unref() {
+            types::Type::Table(table) => {
+                let table = table.borrow();
+                let value = table.get(&key);
+
+                if !value.is_nil() {
+                    return Ok(value);
+                }
 
-                types::Type::Reference(result)
-            // If we have no such entry in the table, we add new entry with Nil value
-            // In case of chaind indexing, we must get an error about indexing Nil value
-            } else {
-                let new_entry = Rc::new(RefCell::new(types::Type::Nil));
-                self.cache.borrow_mut().set(id, &new_entry);
+                match table.metamethod("__index") {
+                    Some(handler) => handler,
+                    None => return Ok(types::Type::Nil),
+                }
+            }
+            value => match env.borrow().metamethod(value, "__index") {
+                Some(handler) => handler,
+                None => return Err(this.runtime_error(index_error(value, described, env))),
+            },
+        };
+
+        match handler {
+            types::Type::Function { .. } | types::Type::NativeFunction { .. } => {
+                let args = VecDeque::from(vec![object, key]);
+                return Ok(functions::call(this, handler, args, env)?.pop_front().unwrap_or(types::Type::Nil));
+            }
+            _ => object = handler,
+        }
+    }
 
-                map.insert(key, new_entry.clone());
-                update_table_border(&map, border);
+    Err(this.runtime_error("'__index' chain too long; possibly a loop".to_string()))
+}
 
-                types::Type::Reference(new_entry)
-            };
+/// Set object field value. If table doesn't contain the field, we follow `__newindex` metamethods chain
+pub fn new_index(
+    this: &dyn interpreter::Eval,
+    object: types::Type,
+    object_expression: Option<&dyn expressions::Expression>,
+    key: types::Type,
+    value: types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> error::Result<()> {
+    let mut object = object;
+    let mut object_expression = object_expression;
+
+    for _ in 0..MAX_METATABLE_CHAIN {
+        let described = object_expression.take();
+        let handler = match object.unref() {
+            types::Type::Table(table) => {
+                // Table may be a metatable of itself, so we can't borrow it mutably, while looking for metamethod
+                let handler = {
+                    let table = table.borrow();
+                    if table.get(&key).is_nil() {
+                        table.metamethod("__newindex")
+                    } else {
+                        None
+                    }
+                };
+
+                match handler {
+                    Some(handler) => handler,
+                    None => return table.borrow_mut().set(key, value).map_err(|error| this.runtime_error(error)),
+                }
+            }
+            value => match env.borrow().metamethod(value, "__newindex") {
+                Some(handler) => handler,
+                None => return Err(this.runtime_error(index_error(value, described, env))),
+            },
+        };
+
+        match handler {
+            types::Type::Function { .. } | types::Type::NativeFunction { .. } => {
+                let args = VecDeque::from(vec![object, key, value]);
+                functions::call(this, handler, args, env)?;
+                return Ok(());
+            }
+            _ => object = handler,
         }
+    }
+
+    Err(this.runtime_error("'__newindex' chain too long; possibly a loop".to_string()))
+}
 
-        // Because of NLL
-        self.runtime_error(format!("Attempt to index `{}` value, not a table", table_borrow))
+impl interpreter::Eval for tables::Indexing {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        let object = self.object.eval(env)?.value();
+        let key = self.index.eval(env)?.value();
+
+        index(self, object, Some(self.object.as_ref()), key, env)
+    }
+
+    fn assign(&self, env: &mut utils::Shared<environment::Environment>, value: types::Type) -> error::Result<()> {
+        let target = self.resolve_target(env)?;
+        self.assign_target(env, target, value)
+    }
+
+    fn resolve_target(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<interpreter::Target> {
+        let object = self.object.eval(env)?.value();
+        let key = self.index.eval(env)?.value();
+
+        Ok(interpreter::Target::Field { object, key })
+    }
+
+    fn assign_target(
+        &self,
+        env: &mut utils::Shared<environment::Environment>,
+        target: interpreter::Target,
+        value: types::Type,
+    ) -> error::Result<()> {
+        match target {
+            interpreter::Target::Field { object, key } => {
+                new_index(self, object, Some(self.object.as_ref()), key, value, env)
+            }
+            interpreter::Target::Expression => self.assign(env, value),
+        }
     }
 }
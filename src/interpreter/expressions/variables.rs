@@ -2,58 +2,55 @@ use std::cell::RefCell;
 use std::clone::Clone;
 use std::rc::Rc;
 
-use crate::ast::expressions::{self, variables};
+use crate::ast::expressions::variables;
+use crate::error;
+use crate::interpreter::expressions::expression;
 use crate::interpreter::{self, environment, types};
 use crate::utils;
-use std::collections::VecDeque;
 
 const DEBUG: bool = false;
 
-// pub struct Id(pub String);
-impl interpreter::Eval for variables::Id {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
+impl variables::Id {
+    /// Find variable in the environment. Reading doesn't create variables, so undefined variables have no reference
+    fn reference(&self, env: &utils::Shared<environment::Environment>) -> Option<Rc<RefCell<types::Type>>> {
         // Let's check if we can retrieve value from cache
-        if let Some(cached_value) = self.get_cached(&*env.borrow()) {
+        if let Some(types::Type::Reference(cached_value)) = self.get_cached(&env.borrow()) {
             if DEBUG {
                 println!("Returning cached value {:?}: {:?}", self.id, cached_value);
             }
-            return cached_value
+            return Some(cached_value)
         };
 
-        let find_result = env.borrow_mut().get(&self.id).clone();
-        let var_reference = if let Some(refcell) = find_result {
-            refcell
-        } else {
-            let new_entry = Rc::new(RefCell::new(types::Type::Nil));
-            env.borrow_mut()
-                .add_variable(self.id.clone(), types::Type::Reference(new_entry.clone()));
-            new_entry
-        };
+        let var_reference = env.borrow_mut().get(&self.id)?;
 
         // Save value into cache
-        self.set_cached(&*env.borrow(), &var_reference);
+        self.set_cached(&env.borrow(), &var_reference);
         if DEBUG {
             println!("Saving cached value {:?}: {:?}", self.id, var_reference);
         }
-        types::Type::Reference(var_reference)
+        Some(var_reference)
     }
 }
 
-/// Function to evaluate vars and expressions and properly append result into a target
-fn eval_expression(
-    expressions: &VecDeque<Box<dyn expressions::Expression>>,
-    env: &mut utils::Shared<environment::Environment>,
-) -> VecDeque<types::Type> {
-    let mut result = VecDeque::new();
+// pub struct Id(pub String);
+impl interpreter::Eval for variables::Id {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(self.reference(env).map_or(types::Type::Nil, types::Type::Reference))
+    }
 
-    for exp in expressions {
-        match exp.eval(env) {
-            types::Type::Vector(vec) => result.extend(vec.into_iter()),
-            value => result.push_back(value),
+    /// Assignment to undefined variable creates a global variable
+    fn assign(&self, env: &mut utils::Shared<environment::Environment>, value: types::Type) -> error::Result<()> {
+        match self.reference(env) {
+            Some(reference) => {
+                reference.replace(value);
+            }
+            None => {
+                env.borrow_mut().add_global(self.id.clone(), value);
+            }
         }
-    }
 
-    result
+        Ok(())
+    }
 }
 
 // pub struct Assignment {
@@ -61,36 +58,25 @@ fn eval_expression(
 //     pub explist: VecDeque<Box<dyn expressions::Expression>>,
 // }
 impl interpreter::Eval for variables::Assignment {
-    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        // TODO: Rework to use deque and pop values
-        let mut var_typevec = eval_expression(&self.varlist, env);
-        let mut exp_typevec = eval_expression(&self.explist, env);
-
-        while !var_typevec.is_empty() {
-            let key = var_typevec.pop_front().unwrap();
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        // Tables and keys of the targets are evaluated first, then all values. We copy values, so `a, b = b, a` works
+        let targets = self
+            .varlist
+            .iter()
+            .map(|var| var.resolve_target(env))
+            .collect::<error::Result<Vec<_>>>()?;
+        let mut values = expression::eval_list(&self.explist, env)?;
 
-            let value = if exp_typevec.is_empty() {
-                types::Type::Nil
-            } else {
-                exp_typevec.pop_front().unwrap()
-            };
+        for (var, target) in self.varlist.iter().zip(targets) {
+            let value = values.pop_front().unwrap_or(types::Type::Nil);
 
             if DEBUG {
-                println!("Executing assignment {:?} = {:?}", key, value)
+                println!("Executing assignment {:?} = {:?}", var, value)
             }
 
-            // We can only add variable by name, this should be an Id or change reference. Everything else is an error
-            match key {
-                types::Type::String(var_id) => {
-                    env.borrow_mut().add_variable(var_id, value);
-                },
-                types::Type::Reference(reference) => {
-                    reference.replace(value);
-                }
-                _ => self.runtime_error(format!("Can't use '{}' as a lvalue", key)),
-            }
+            var.assign_target(env, target, value)?;
         }
 
-        types::Type::Nil
+        Ok(types::Type::Nil)
     }
 }
@@ -1,33 +1,121 @@
 #[macro_use]
 pub mod types;
+pub mod table;
 pub mod environment;
 pub mod expressions;
 pub mod cache;
 pub mod native;
+pub mod stdlib;
+
+use std::collections::VecDeque;
 
 use crate::ast;
+use crate::error;
 use crate::utils;
 
 pub trait Eval: std::fmt::Debug {
-    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> types::Type {
+    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
         println!("{:?} `eval` unimplemented", self);
         unimplemented!();
     }
 
-    #[cfg(test)]
-    fn runtime_error(&self, error: String) -> ! {
-        panic!("Runtime error: {}", error);
+    /// Evaluate expression, which may produce multiple values: function calls and `...`.
+    /// Other expressions always produce exactly one value
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        Ok(VecDeque::from(vec![self.eval(env)?]))
+    }
+
+    /// Assign value to the expression. Variables evaluate to references, local names evaluate to strings.
+    /// Table fields override it to respect `__newindex` metamethod
+    fn assign(&self, env: &mut utils::Shared<environment::Environment>, value: types::Type) -> error::Result<()> {
+        match self.eval(env)? {
+            types::Type::String(var_id) => {
+                env.borrow_mut().add_variable(var_id.to_string(), value);
+            }
+            types::Type::Reference(reference) => {
+                reference.replace(value);
+            }
+            key => return Err(self.runtime_error(format!("Can't use '{}' as a lvalue", key))),
+        }
+
+        Ok(())
     }
 
-    #[cfg(not(test))]
-    fn runtime_error(&self, error: String) -> ! {
-        println!("Runtime error: {}", error);
-        ::std::process::exit(1)
+    /// Evaluate parts of the assignment target, which must be evaluated before the assigned values
+    fn resolve_target(&self, _env: &mut utils::Shared<environment::Environment>) -> error::Result<Target> {
+        Ok(Target::Expression)
     }
+
+    /// Assign value to the target, resolved with `resolve_target`
+    fn assign_target(
+        &self,
+        env: &mut utils::Shared<environment::Environment>,
+        _target: Target,
+        value: types::Type,
+    ) -> error::Result<()> {
+        self.assign(env, value)
+    }
+
+    /// Make runtime error, raised by the expression
+    fn runtime_error(&self, message: String) -> error::Error {
+        error::Error::runtime(message)
+    }
+}
+
+/// Assignment target. Table and key of a field are evaluated before the assigned values,
+/// so `i, a[i] = i + 1, 20` sets `a[i]` with the old value of `i`
+pub enum Target {
+    /// Variable, which is looked up on assignment
+    Expression,
+    /// Table field with evaluated table and key
+    Field { object: types::Type, key: types::Type },
 }
 
 impl Eval for ast::expressions::Terminal {
-    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> types::Type {
-        types::Type::Nil
+    fn eval(&self, _env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        Ok(types::Type::Nil)
+    }
+}
+
+impl ast::expressions::Spanned {
+    /// Error of the innermost failed expression gets its position and the stack traceback
+    fn locate_error(&self, error: error::Error, env: &utils::Shared<environment::Environment>) -> error::Error {
+        error
+            .locate(&self.span)
+            .with_traceback(|| env.borrow().traceback(Some(&self.span), 1))
+    }
+}
+
+/// Errors of the wrapped expression get its position
+impl Eval for ast::expressions::Spanned {
+    fn eval(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<types::Type> {
+        self.expression.eval(env).map_err(|error| self.locate_error(error, env))
+    }
+
+    fn eval_multi(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<VecDeque<types::Type>> {
+        self.expression.eval_multi(env).map_err(|error| self.locate_error(error, env))
+    }
+
+    fn assign(&self, env: &mut utils::Shared<environment::Environment>, value: types::Type) -> error::Result<()> {
+        self.expression.assign(env, value).map_err(|error| self.locate_error(error, env))
+    }
+
+    fn resolve_target(&self, env: &mut utils::Shared<environment::Environment>) -> error::Result<Target> {
+        self.expression.resolve_target(env).map_err(|error| self.locate_error(error, env))
+    }
+
+    fn assign_target(
+        &self,
+        env: &mut utils::Shared<environment::Environment>,
+        target: Target,
+        value: types::Type,
+    ) -> error::Result<()> {
+        self.expression
+            .assign_target(env, target, value)
+            .map_err(|error| self.locate_error(error, env))
+    }
+
+    fn runtime_error(&self, message: String) -> error::Error {
+        error::Error::runtime(message).locate(&self.span)
     }
 }
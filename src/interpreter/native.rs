@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::error;
+use crate::interpreter::expressions::functions;
+use crate::interpreter::{self, environment, types};
+use crate::utils;
+
+/// Native function result. Functions return multiple values or error.
+/// Error messages are converted to runtime errors with `?` operator
+pub type NativeResult = Result<VecDeque<types::Type>, error::Error>;
+
+/// Function implemented in Rust. Receives calling environment and list of arguments
+pub type NativeFunction =
+    dyn Fn(&mut utils::Shared<environment::Environment>, VecDeque<types::Type>) -> NativeResult;
+
+/// Library function pointer. Libraries are lists of named functions
+pub type LibraryFunction =
+    fn(&mut utils::Shared<environment::Environment>, VecDeque<types::Type>) -> NativeResult;
+
+/// Create native function value, which can be called from the interpreter as a Lua function
+pub fn function<F>(env: &mut environment::Environment, name: &str, function: F) -> types::Type
+where
+    F: Fn(&mut utils::Shared<environment::Environment>, VecDeque<types::Type>) -> NativeResult
+        + 'static,
+{
+    types::Type::NativeFunction {
+        id: env.next_global_id(),
+        name: name.to_string(),
+        function: Rc::new(function),
+    }
+}
+
+/// Register named native function in the environment
+pub fn register<F>(env: &mut environment::Environment, name: &str, function: F)
+where
+    F: Fn(&mut utils::Shared<environment::Environment>, VecDeque<types::Type>) -> NativeResult
+        + 'static,
+{
+    let value = self::function(env, name, function);
+    env.add_variable(name.to_string(), value);
+}
+
+/// Register library table with native functions in the environment. I.e. `table.pack`
+pub fn register_library(
+    env: &mut environment::Environment,
+    name: &str,
+    functions: &[(&str, LibraryFunction)],
+) {
+    let mut library = types::Table::new(env.next_global_id());
+
+    for (function_name, function) in functions {
+        let value = self::function(env, &format!("{}.{}", name, function_name), *function);
+        library
+            .set(types::Type::String((*function_name).into()), value)
+            .unwrap();
+    }
+
+    env.add_variable(name.to_string(), types::Type::Table(utils::Shared::new(library)));
+}
+
+/// Caller expression for `functions::call`, when value is called from a native function
+#[derive(Debug)]
+struct NativeCall;
+impl interpreter::Eval for NativeCall {}
+
+/// Call value from the native function. Errors are returned to the native function
+pub fn call(
+    function: types::Type,
+    args: VecDeque<types::Type>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> NativeResult {
+    // Function is called at the position of the native call, so it is visible in tracebacks
+    let call_site = env.borrow().call_site(1);
+    let span = match call_site {
+        Some(span) => span,
+        None => return functions::call(&NativeCall, function, args, env),
+    };
+
+    env.borrow().enter_call(environment::CallFrame {
+        span,
+        function: environment::FunctionName::Anonymous(None).resolve(&function),
+    });
+    let result = functions::call(&NativeCall, function, args, env);
+    env.borrow().leave_call();
+
+    result
+}
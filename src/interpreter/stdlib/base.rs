@@ -0,0 +1,418 @@
+use std::collections::VecDeque;
+use std::io::Write;
+
+use crate::error;
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::{bad_argument, check_any, check_integer, check_table, opt_integer};
+
+pub fn load(env: &mut environment::Environment) {
+    native::register(env, "assert", assert);
+    native::register(env, "error", error);
+    native::register(env, "getmetatable", getmetatable);
+    native::register(env, "ipairs", ipairs);
+    native::register(env, "next", next);
+    native::register(env, "pairs", pairs);
+    native::register(env, "pcall", pcall);
+    native::register(env, "print", print);
+    native::register(env, "rawequal", rawequal);
+    native::register(env, "rawget", rawget);
+    native::register(env, "rawlen", rawlen);
+    native::register(env, "rawset", rawset);
+    native::register(env, "select", select);
+    native::register(env, "setmetatable", setmetatable);
+    native::register(env, "tonumber", tonumber);
+    native::register(env, "tostring", tostring);
+    native::register(env, "type", type_);
+    native::register(env, "xpcall", xpcall);
+
+    env.add_variable(
+        "_VERSION".to_string(),
+        types::Type::String("Lua 5.3".into()),
+    );
+}
+
+/// Convert value to a string the way `tostring` does
+pub fn to_string(value: &types::Type) -> utils::ByteString {
+    match value.unref() {
+        types::Type::Nil => "nil".into(),
+        types::Type::Boolean(value) => value.to_string().into(),
+        types::Type::Number(number) => types::format_number(*number).into(),
+        types::Type::Integer(integer) => integer.to_string().into(),
+        types::Type::String(string) => string.clone(),
+        types::Type::Table(table) => format!("table: 0x{:08x}", table.borrow().id).into(),
+        types::Type::Function { id, .. } | types::Type::NativeFunction { id, .. } => {
+            format!("function: 0x{:08x}", id).into()
+        }
+        value => format!("{:?}", value).into(),
+    }
+}
+
+/// Convert value to a string, calling its `__tostring` metamethod, if there is one
+pub fn to_string_meta(
+    value: &types::Type,
+    env: &mut utils::Shared<environment::Environment>,
+) -> Result<utils::ByteString, error::Error> {
+    let metamethod = match value.metamethod("__tostring") {
+        Some(metamethod) => metamethod,
+        None => return Ok(to_string(value)),
+    };
+
+    let result = native::call(metamethod, VecDeque::from(vec![value.value()]), env)?;
+    match result.front().map(types::Type::unref) {
+        Some(string @ types::Type::String(_))
+        | Some(string @ types::Type::Number(_))
+        | Some(string @ types::Type::Integer(_)) => Ok(to_string(string)),
+        _ => Err("'__tostring' must return a string".to_string().into()),
+    }
+}
+
+fn assert(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    if check_any("assert", &args, 1)?.as_bool() {
+        Ok(args)
+    } else if let Some(message) = args.get(1) {
+        Err(error::Error::runtime_value(message.value()))
+    } else {
+        Err("assertion failed!".to_string().into())
+    }
+}
+
+/// Raise error with any value. String messages get position of the call at the `level`:
+/// 1 is the function, which called `error`, 2 is the function, which called it, and so on
+fn error(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let value = args.front().map_or(types::Type::Nil, types::Type::value);
+    let level = opt_integer("error", &args, 2, 1)?;
+
+    if let types::Type::String(message) = &value {
+        let call_site = if level > 0 {
+            env.borrow().call_site(level as usize)
+        } else {
+            None
+        };
+
+        if let Some(span) = call_site {
+            let message = format!("{}: {}", span, message);
+            return Err(error::Error::runtime_value(types::Type::String(message.into())));
+        }
+    }
+
+    Err(error::Error::runtime_value(value))
+}
+
+fn getmetatable(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let metatable = env.borrow().metatable(check_any("getmetatable", &args, 1)?);
+    let metatable = match metatable {
+        Some(metatable) => {
+            // Protected metatable is hidden behind `__metatable` field value
+            let protection = metatable
+                .borrow()
+                .get(&types::Type::String("__metatable".into()));
+
+            if protection.is_nil() {
+                types::Type::Table(metatable)
+            } else {
+                protection
+            }
+        }
+        None => types::Type::Nil,
+    };
+
+    Ok(VecDeque::from(vec![metatable]))
+}
+
+fn ipairs_iterator(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let index = check_integer("ipairs", &args, 2)? + 1;
+    let value = match check_any("ipairs", &args, 1)? {
+        types::Type::Table(table) => table.borrow().get(&types::Type::Integer(index)),
+        _ => types::Type::Nil,
+    };
+
+    Ok(if value.is_nil() {
+        VecDeque::from(vec![types::Type::Nil])
+    } else {
+        VecDeque::from(vec![types::Type::Integer(index), value])
+    })
+}
+
+fn ipairs(
+    env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    check_any("ipairs", &args, 1)?;
+
+    let iterator = native::function(&mut env.borrow_mut(), "ipairs_iterator", ipairs_iterator);
+    Ok(VecDeque::from(vec![
+        iterator,
+        args.pop_front().unwrap(),
+        types::Type::Integer(0),
+    ]))
+}
+
+fn next(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let key = args.get(1).map(types::Type::unref).unwrap_or(&types::Type::Nil);
+
+    let table = check_table("next", &args, 1)?;
+    let table = table.borrow();
+    if let Some((key, value)) = table.next(key)? {
+        return Ok(VecDeque::from(vec![key, value]));
+    }
+
+    Ok(VecDeque::from(vec![types::Type::Nil]))
+}
+
+/// Iterator over table entries. Objects may provide their own iterator with `__pairs` metamethod
+fn pairs(
+    env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    if let Some(metamethod) = args.front().and_then(|object| object.metamethod("__pairs")) {
+        let object = args.pop_front().unwrap().value();
+        let mut results = native::call(metamethod, VecDeque::from(vec![object]), env)?;
+        results.resize(3, types::Type::Nil);
+
+        return Ok(results);
+    }
+
+    check_table("pairs", &args, 1)?;
+
+    let iterator = native::function(&mut env.borrow_mut(), "next", next);
+    Ok(VecDeque::from(vec![
+        iterator,
+        args.pop_front().unwrap(),
+        types::Type::Nil,
+    ]))
+}
+
+/// Call function in protected mode. Returns status and function results or error object
+fn pcall(
+    env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let function = check_any("pcall", &args, 1)?.clone();
+    args.pop_front();
+
+    Ok(match native::call(function, args, env) {
+        Ok(mut values) => {
+            values.push_front(types::Type::Boolean(true));
+            values
+        }
+        Err(error) => VecDeque::from(vec![types::Type::Boolean(false), error.value()]),
+    })
+}
+
+fn print(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let strings = args
+        .iter()
+        .map(|value| to_string_meta(value, env).map(utils::ByteString::into_bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Strings may contain any bytes, so they are written as is
+    let mut line = strings.join(&b'\t');
+    line.push(b'\n');
+    std::io::stdout()
+        .write_all(&line)
+        .map_err(|error| error.to_string())?;
+
+    Ok(VecDeque::new())
+}
+
+fn rawequal(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let equal = check_any("rawequal", &args, 1)? == check_any("rawequal", &args, 2)?;
+
+    Ok(VecDeque::from(vec![types::Type::Boolean(equal)]))
+}
+
+fn rawget(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let value = check_table("rawget", &args, 1)?
+        .borrow()
+        .get(check_any("rawget", &args, 2)?);
+
+    Ok(VecDeque::from(vec![value]))
+}
+
+fn rawlen(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let length = match args.front().map(types::Type::unref) {
+        Some(types::Type::Table(table)) => table.borrow().border(),
+        Some(types::Type::String(string)) => string.len(),
+        _ => return Err(bad_argument("rawlen", 1, "table or string expected").into()),
+    };
+
+    Ok(VecDeque::from(vec![types::Type::Integer(length as i64)]))
+}
+
+fn rawset(
+    _env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let table = check_table("rawset", &args, 1)?;
+    let key = check_any("rawset", &args, 2)?.clone();
+    let value = check_any("rawset", &args, 3)?.clone();
+
+    table.borrow_mut().set(key, value)?;
+
+    Ok(VecDeque::from(vec![args.pop_front().unwrap()]))
+}
+
+fn select(
+    _env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let count = args.len().saturating_sub(1) as i64;
+
+    if let types::Type::String(string) = check_any("select", &args, 1)? {
+        if string.as_bytes() == b"#" {
+            return Ok(VecDeque::from(vec![types::Type::Integer(count)]));
+        }
+    }
+
+    let index = check_integer("select", &args, 1)?;
+    let start = if index < 0 { count + index } else { index - 1 };
+
+    if index == 0 || start < 0 {
+        return Err(bad_argument("select", 1, "index out of range").into());
+    }
+
+    Ok(args.split_off((start as usize + 1).min(args.len())))
+}
+
+fn setmetatable(
+    _env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let table = check_table("setmetatable", &args, 1)?;
+    let metatable = match args.get(1).map(types::Type::unref) {
+        Some(types::Type::Nil) => None,
+        Some(types::Type::Table(metatable)) => Some(metatable.clone()),
+        _ => return Err(bad_argument("setmetatable", 2, "nil or table expected").into()),
+    };
+
+    if table.borrow().metamethod("__metatable").is_some() {
+        return Err("cannot change a protected metatable".to_string().into());
+    }
+
+    table.borrow_mut().metatable = metatable;
+
+    Ok(VecDeque::from(vec![args.pop_front().unwrap()]))
+}
+
+fn tonumber(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let value = check_any("tonumber", &args, 1)?;
+
+    let result = if args.get(1).is_none_or(types::Type::is_nil) {
+        value.to_number()
+    } else {
+        let base = check_integer("tonumber", &args, 2)?;
+        let string = match value {
+            types::Type::String(string) => String::from_utf8_lossy(string).trim().to_lowercase(),
+            _ => {
+                let message = format!("string expected, got {}", value.type_name());
+                return Err(bad_argument("tonumber", 1, &message).into());
+            }
+        };
+
+        if !(2..=36).contains(&base) {
+            return Err(bad_argument("tonumber", 2, "base out of range").into());
+        }
+
+        let (negative, digits) = match string.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, string.as_str()),
+        };
+
+        // Integers wrap around, as numerals do
+        let number = digits.chars().try_fold(0i64, |number, chr| {
+            chr.to_digit(base as u32)
+                .map(|digit| number.wrapping_mul(base).wrapping_add(i64::from(digit)))
+        });
+
+        match number {
+            Some(number) if !digits.is_empty() => Some(types::Type::Integer(if negative {
+                number.wrapping_neg()
+            } else {
+                number
+            })),
+            _ => None,
+        }
+    };
+
+    Ok(VecDeque::from(vec![result.unwrap_or(types::Type::Nil)]))
+}
+
+fn tostring(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = to_string_meta(check_any("tostring", &args, 1)?, env)?;
+
+    Ok(VecDeque::from(vec![types::Type::String(string)]))
+}
+
+fn type_(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let name = check_any("type", &args, 1)?.type_name();
+
+    Ok(VecDeque::from(vec![types::Type::String(name.into())]))
+}
+
+/// Call function in protected mode with message handler. Handler receives error object
+/// and its first result is returned instead of the error object
+fn xpcall(
+    env: &mut utils::Shared<environment::Environment>,
+    mut args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let function = check_any("xpcall", &args, 1)?.clone();
+    let handler = check_any("xpcall", &args, 2)?.clone();
+    let args = args.split_off(2);
+
+    match native::call(function, args, env) {
+        Ok(mut values) => {
+            values.push_front(types::Type::Boolean(true));
+            Ok(values)
+        }
+        Err(error) => {
+            let previous = env.borrow().handle_error(error.traceback().map(str::to_string));
+            let handled = native::call(handler, VecDeque::from(vec![error.value()]), env);
+            env.borrow().handle_error(previous);
+
+            let value = match handled {
+                Ok(mut values) => values.pop_front().unwrap_or(types::Type::Nil),
+                Err(error) => error.value(),
+            };
+
+            Ok(VecDeque::from(vec![types::Type::Boolean(false), value]))
+        }
+    }
+}
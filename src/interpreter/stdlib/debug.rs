@@ -0,0 +1,40 @@
+use std::collections::VecDeque;
+
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::{check_string, opt_integer};
+
+pub fn load(env: &mut environment::Environment) {
+    native::register_library(env, "debug", &[("traceback", traceback)]);
+}
+
+/// Traceback of the call stack, appended to the message. Level 1 is the function, which called `traceback`.
+/// Messages, which are not strings or numbers, are returned as is
+fn traceback(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let message = match args.front().map(types::Type::unref) {
+        None | Some(types::Type::Nil) => None,
+        Some(types::Type::String(_)) | Some(types::Type::Number(_)) | Some(types::Type::Integer(_)) => {
+            Some(check_string("traceback", &args, 1)?)
+        }
+        Some(value) => return Ok(VecDeque::from(vec![value.clone()])),
+    };
+    let level = opt_integer("traceback", &args, 2, 1)?;
+
+    // `traceback` itself is the innermost call
+    let traceback = env.borrow().traceback(None, level.max(0) as usize + 1);
+    let result = match message {
+        Some(message) => {
+            let mut result = message.into_bytes();
+            result.push(b'\n');
+            result.extend_from_slice(traceback.as_bytes());
+            result.into()
+        }
+        None => traceback.into(),
+    };
+
+    Ok(VecDeque::from(vec![types::Type::String(result)]))
+}
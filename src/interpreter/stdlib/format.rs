@@ -0,0 +1,419 @@
+//! printf-style conversions of `string.format`. Conversions follow C `printf` of the reference
+//! implementation: flags `-+ #0`, width and precision of at most two digits
+
+use crate::interpreter::types;
+
+/// Maximum number of digits in width and precision
+const MAX_DIGITS: usize = 2;
+/// Hexadecimal digits in the fraction of a float
+const FRACTION_DIGITS: usize = 13;
+
+/// Conversion specification, i.e. `%-10.3f`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Spec {
+    /// `-`: justify to the left
+    left: bool,
+    /// `+`: always print the sign of signed numbers
+    plus: bool,
+    /// ` `: print space instead of the plus sign
+    space: bool,
+    /// `#`: alternate form. Prefix of hexadecimal and octal numbers, decimal point of floats
+    alternate: bool,
+    /// `0`: pad numbers with zeros
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    pub conversion: u8,
+}
+
+impl Spec {
+    /// Parse specification, following `%`. Returns specification and its length
+    pub fn parse(template: &[u8]) -> Result<(Spec, usize), String> {
+        let mut spec = Spec::default();
+        let mut length = 0;
+
+        while let Some(flag) = template.get(length) {
+            match flag {
+                b'-' => spec.left = true,
+                b'+' => spec.plus = true,
+                b' ' => spec.space = true,
+                b'#' => spec.alternate = true,
+                b'0' => spec.zero = true,
+                _ => break,
+            }
+            length += 1;
+        }
+
+        let digits = |length: &mut usize| {
+            let start = *length;
+            while *length - start < MAX_DIGITS
+                && template.get(*length).is_some_and(u8::is_ascii_digit)
+            {
+                *length += 1;
+            }
+            String::from_utf8_lossy(&template[start..*length])
+                .parse::<usize>()
+                .ok()
+        };
+
+        spec.width = digits(&mut length).unwrap_or(0);
+        if template.get(length) == Some(&b'.') {
+            length += 1;
+            spec.precision = Some(digits(&mut length).unwrap_or(0));
+        }
+
+        // `%%` can't have modifiers, so the modifiers before `%` are the invalid option
+        let conversion = template.get(length).copied();
+        let option = match conversion {
+            Some(b'%') | None => length,
+            Some(_) => length + 1,
+        };
+        length += conversion.map_or(0, |_| 1);
+        let invalid = || {
+            format!(
+                "invalid option '%{}' to 'format'",
+                String::from_utf8_lossy(&template[..option])
+            )
+        };
+
+        // Different conversions accept different flags
+        let (flags, precision): (&[u8], bool) = match conversion.ok_or_else(invalid)? {
+            b'c' => (b"-", false),
+            b'd' | b'i' | b'u' => (b"-+0 ", true),
+            b'o' | b'x' | b'X' => (b"-#0", true),
+            b'a' | b'A' | b'e' | b'E' | b'f' | b'F' | b'g' | b'G' => (b"-+ #0", true),
+            b's' => (b"-", true),
+            b'q' if length > 1 => return Err("specifier '%q' cannot have modifiers".to_string()),
+            b'q' => (b"", false),
+            _ => return Err(invalid()),
+        };
+
+        let used = [
+            (spec.left, b'-'),
+            (spec.plus, b'+'),
+            (spec.space, b' '),
+            (spec.alternate, b'#'),
+            (spec.zero, b'0'),
+        ];
+        if used
+            .iter()
+            .any(|(used, flag)| *used && !flags.contains(flag))
+            || (!precision && spec.precision.is_some())
+        {
+            return Err(invalid());
+        }
+
+        spec.conversion = conversion.unwrap();
+        Ok((spec, length))
+    }
+
+    /// Specification has flags, width or precision
+    pub fn has_modifiers(&self) -> bool {
+        *self
+            != Spec {
+                conversion: self.conversion,
+                ..Spec::default()
+            }
+    }
+
+    /// Pad the value up to the width. Zeros are inserted between the prefix and the body
+    fn pad(&self, prefix: &str, body: &[u8], zero: bool, result: &mut Vec<u8>) {
+        let padding = self.width.saturating_sub(prefix.len() + body.len());
+
+        if self.left {
+            result.extend_from_slice(prefix.as_bytes());
+            result.extend_from_slice(body);
+            result.resize(result.len() + padding, b' ');
+        } else if self.zero && zero {
+            result.extend_from_slice(prefix.as_bytes());
+            result.resize(result.len() + padding, b'0');
+            result.extend_from_slice(body);
+        } else {
+            result.resize(result.len() + padding, b' ');
+            result.extend_from_slice(prefix.as_bytes());
+            result.extend_from_slice(body);
+        }
+    }
+
+    /// Sign of a signed number
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// `%c`
+    pub fn format_char(&self, byte: u8, result: &mut Vec<u8>) {
+        self.pad("", &[byte], false, result)
+    }
+
+    /// `%s`. Precision limits the number of bytes
+    pub fn format_string(&self, string: &[u8], result: &mut Vec<u8>) {
+        let length = self
+            .precision
+            .map_or(string.len(), |precision| precision.min(string.len()));
+        self.pad("", &string[..length], false, result)
+    }
+
+    /// `%d`, `%i`, `%u`, `%o`, `%x` and `%X`. Unsigned conversions print negative numbers in two's complement
+    pub fn format_integer(&self, integer: i64, result: &mut Vec<u8>) {
+        let unsigned = integer as u64;
+        let (sign, mut digits) = match self.conversion {
+            b'd' | b'i' => (self.sign(integer < 0), integer.unsigned_abs().to_string()),
+            b'o' => ("", format!("{:o}", unsigned)),
+            b'x' => ("", format!("{:x}", unsigned)),
+            b'X' => ("", format!("{:X}", unsigned)),
+            _ => ("", unsigned.to_string()),
+        };
+
+        // Precision is the minimum number of digits. Zero with zero precision has no digits at all
+        if let Some(precision) = self.precision {
+            if integer == 0 && precision == 0 {
+                digits.clear();
+            }
+            if digits.len() < precision {
+                digits.insert_str(0, &"0".repeat(precision - digits.len()));
+            }
+        }
+
+        let prefix = match self.conversion {
+            b'o' if self.alternate && !digits.starts_with('0') => {
+                digits.insert(0, '0');
+                sign
+            }
+            b'x' if self.alternate && integer != 0 => "0x",
+            b'X' if self.alternate && integer != 0 => "0X",
+            _ => sign,
+        };
+
+        self.pad(prefix, digits.as_bytes(), self.precision.is_none(), result)
+    }
+
+    /// `%a`, `%e`, `%f`, `%g` and their uppercase variants
+    pub fn format_float(&self, number: f64, result: &mut Vec<u8>) {
+        let upper = self.conversion.is_ascii_uppercase();
+        let sign = self.sign(number.is_sign_negative());
+
+        if !number.is_finite() {
+            let body = if number.is_nan() { "nan" } else { "inf" };
+            let body = if upper {
+                body.to_uppercase()
+            } else {
+                body.to_string()
+            };
+            return self.pad(sign, body.as_bytes(), false, result);
+        }
+
+        let number = number.abs();
+        let precision = self.precision.unwrap_or(6);
+        let (prefix, body) = match self.conversion.to_ascii_lowercase() {
+            b'a' => (
+                format!("{}0x", sign),
+                hexadecimal(number, self.precision, self.alternate),
+            ),
+            b'e' => (
+                sign.to_string(),
+                exponential(number, precision, self.alternate),
+            ),
+            b'f' => (sign.to_string(), fixed(number, precision, self.alternate)),
+            _ => (sign.to_string(), general(number, precision, self.alternate)),
+        };
+
+        if upper {
+            self.pad(
+                &prefix.to_uppercase(),
+                body.to_uppercase().as_bytes(),
+                true,
+                result,
+            )
+        } else {
+            self.pad(&prefix, body.as_bytes(), true, result)
+        }
+    }
+}
+
+/// `%f` of a non-negative number
+fn fixed(number: f64, precision: usize, alternate: bool) -> String {
+    let mut result = format!("{:.*}", precision, number);
+    if alternate && precision == 0 {
+        result.push('.');
+    }
+
+    result
+}
+
+/// `%e` of a non-negative number. Exponent has at least two digits
+fn exponential(number: f64, precision: usize, alternate: bool) -> String {
+    let (mut mantissa, exponent) = split_exponential(number, precision);
+    if alternate && precision == 0 {
+        mantissa.push('.');
+    }
+
+    format!(
+        "{}e{}{:02}",
+        mantissa,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+/// Mantissa and decimal exponent of a non-negative number after rounding to the precision
+fn split_exponential(number: f64, precision: usize) -> (String, i32) {
+    let scientific = format!("{:.*e}", precision, number);
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+
+    (mantissa.to_string(), exponent[1..].parse().unwrap())
+}
+
+/// `%g` of a non-negative number. Precision is the number of significant digits. Exponential form is
+/// used for small and large exponents. Trailing zeros are removed unless alternate form is requested
+fn general(number: f64, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+    let (_, exponent) = split_exponential(number, precision - 1);
+
+    let result = if exponent >= -4 && exponent < precision as i32 {
+        fixed(
+            number,
+            (precision as i32 - 1 - exponent) as usize,
+            alternate,
+        )
+    } else {
+        exponential(number, precision - 1, alternate)
+    };
+
+    if alternate {
+        return result;
+    }
+
+    let (mantissa, exponent) = result.split_at(result.find('e').unwrap_or(result.len()));
+    if mantissa.contains('.') {
+        format!(
+            "{}{}",
+            mantissa.trim_end_matches('0').trim_end_matches('.'),
+            exponent
+        )
+    } else {
+        result
+    }
+}
+
+/// `%a` of a non-negative number without `0x` prefix, i.e. `1.8p+1`. Without precision the fraction is exact
+fn hexadecimal(number: f64, precision: Option<usize>, alternate: bool) -> String {
+    let bits = number.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+
+    // Subnormal numbers have leading zero and the minimal exponent
+    let (leading, exponent) = match (biased_exponent, fraction) {
+        (0, 0) => (0, 0),
+        (0, _) => (0, -1022),
+        _ => (1, biased_exponent - 1023),
+    };
+
+    // Leading digit with the fraction, so rounding may carry into the leading digit
+    let mut mantissa = (leading << 52) | fraction;
+    let mut digits = FRACTION_DIGITS;
+
+    if let Some(precision) = precision.filter(|precision| *precision < FRACTION_DIGITS) {
+        // Round half to even
+        let shift = (FRACTION_DIGITS - precision) * 4;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        mantissa >>= shift;
+
+        if remainder > half || (remainder == half && mantissa & 1 == 1) {
+            mantissa += 1;
+        }
+        digits = precision;
+    }
+
+    let (leading, fraction) = (
+        mantissa >> (digits * 4),
+        mantissa & ((1 << (digits * 4)) - 1),
+    );
+    let mut hex = if digits > 0 {
+        format!("{:0width$x}", fraction, width = digits)
+    } else {
+        String::new()
+    };
+
+    match precision {
+        None => hex.truncate(hex.trim_end_matches('0').len()),
+        Some(precision) if precision > digits => hex.push_str(&"0".repeat(precision - digits)),
+        _ => (),
+    }
+
+    let point = if hex.is_empty() && !alternate {
+        ""
+    } else {
+        "."
+    };
+    format!(
+        "{}{}{}p{}{}",
+        leading,
+        point,
+        hex,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+/// `%q`: write value as a literal, which can be read back by the interpreter. Floats are written in
+/// hexadecimal form without loss of precision. Returns `false` if the value has no literal form
+pub fn quote(value: &types::Type, result: &mut Vec<u8>) -> bool {
+    match value.unref() {
+        types::Type::String(string) => quote_string(string.as_bytes(), result),
+        // The minimal integer can't be written in decimal form: its absolute value is a float
+        types::Type::Integer(i64::MIN) => result.extend_from_slice(b"0x8000000000000000"),
+        types::Type::Integer(integer) => result.extend_from_slice(integer.to_string().as_bytes()),
+        types::Type::Number(number) if number.is_nan() => result.extend_from_slice(b"(0/0)"),
+        types::Type::Number(number) if number.is_infinite() => {
+            let literal = if *number < 0f64 { "-1e9999" } else { "1e9999" };
+            result.extend_from_slice(literal.as_bytes())
+        }
+        types::Type::Number(number) => {
+            let spec = Spec {
+                conversion: b'a',
+                ..Spec::default()
+            };
+            spec.format_float(*number, result)
+        }
+        types::Type::Nil => result.extend_from_slice(b"nil"),
+        types::Type::Boolean(value) => result.extend_from_slice(value.to_string().as_bytes()),
+        _ => return false,
+    }
+
+    true
+}
+
+/// Write string literal in double quotes. Control characters are written as decimal escapes
+fn quote_string(string: &[u8], result: &mut Vec<u8>) {
+    result.push(b'"');
+
+    for (index, byte) in string.iter().enumerate() {
+        match byte {
+            b'"' | b'\\' | b'\n' => {
+                result.push(b'\\');
+                result.push(*byte);
+            }
+            // Escape must not swallow the following digit
+            byte if byte.is_ascii_control() => {
+                let escape = if string.get(index + 1).is_some_and(u8::is_ascii_digit) {
+                    format!("\\{:03}", byte)
+                } else {
+                    format!("\\{}", byte)
+                };
+                result.extend_from_slice(escape.as_bytes())
+            }
+            byte => result.push(*byte),
+        }
+    }
+
+    result.push(b'"');
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::check_any;
+
+pub fn load(env: &mut environment::Environment) {
+    native::register_library(
+        env,
+        "math",
+        &[("tointeger", tointeger), ("type", type_)],
+    );
+
+    let library = env.get("math").expect("Should never happen").borrow().clone();
+    if let types::Type::Table(table) = library {
+        let mut table = table.borrow_mut();
+        let constants = [
+            ("maxinteger", types::Type::Integer(i64::MAX)),
+            ("mininteger", types::Type::Integer(i64::MIN)),
+        ];
+
+        for (name, value) in constants.iter() {
+            table
+                .set(types::Type::String((*name).into()), value.clone())
+                .unwrap();
+        }
+    }
+}
+
+/// Convert number with an exact integer representation to integer. Other values give `nil`
+fn tointeger(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let result = match check_any("tointeger", &args, 1)? {
+        types::Type::Integer(integer) => types::Type::Integer(*integer),
+        types::Type::Number(number) => types::float_to_integer(*number)
+            .map(types::Type::Integer)
+            .unwrap_or(types::Type::Nil),
+        _ => types::Type::Nil,
+    };
+
+    Ok(VecDeque::from(vec![result]))
+}
+
+/// Number subtype: `integer` or `float`. Other values give `nil`
+fn type_(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let result = match check_any("type", &args, 1)? {
+        types::Type::Integer(_) => types::Type::String("integer".into()),
+        types::Type::Number(_) => types::Type::String("float".into()),
+        _ => types::Type::Nil,
+    };
+
+    Ok(VecDeque::from(vec![result]))
+}
@@ -0,0 +1,124 @@
+pub mod base;
+pub mod debug;
+pub mod format;
+pub mod math;
+pub mod pattern;
+pub mod string;
+pub mod table;
+
+use std::collections::VecDeque;
+
+use crate::interpreter::{environment, types};
+use crate::utils;
+
+/// Load standard library into the global environment
+pub fn load(env: &mut environment::Environment) {
+    base::load(env);
+    debug::load(env);
+    math::load(env);
+    string::load(env);
+    table::load(env);
+}
+
+/// Error message about invalid function argument. Positions start from 1
+pub fn bad_argument(function: &str, position: usize, message: &str) -> String {
+    format!("bad argument #{} to '{}' ({})", position, function, message)
+}
+
+/// Error message about invalid argument type
+fn type_error(function: &str, args: &VecDeque<types::Type>, position: usize, expected: &str) -> String {
+    let got = match args.get(position - 1) {
+        Some(value) => value.type_name(),
+        None => "no value",
+    };
+
+    bad_argument(function, position, &format!("{} expected, got {}", expected, got))
+}
+
+/// Get argument value. Fails if argument is missing
+pub fn check_any<'a>(
+    function: &str,
+    args: &'a VecDeque<types::Type>,
+    position: usize,
+) -> Result<&'a types::Type, String> {
+    match args.get(position - 1) {
+        Some(value) => Ok(value.unref()),
+        None => Err(bad_argument(function, position, "value expected")),
+    }
+}
+
+/// Get table argument
+pub fn check_table(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+) -> Result<utils::Shared<types::Table>, String> {
+    match args.get(position - 1).map(types::Type::unref) {
+        Some(types::Type::Table(table)) => Ok(table.clone()),
+        _ => Err(type_error(function, args, position, "table")),
+    }
+}
+
+/// Get number argument. Strings are converted to numbers
+pub fn check_number(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+) -> Result<f64, String> {
+    args.get(position - 1)
+        .and_then(types::Type::to_float)
+        .ok_or_else(|| type_error(function, args, position, "number"))
+}
+
+/// Get optional number argument or default value if argument is `nil` or missing
+pub fn opt_number(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+    default: f64,
+) -> Result<f64, String> {
+    match args.get(position - 1) {
+        Some(value) if !value.is_nil() => check_number(function, args, position),
+        _ => Ok(default),
+    }
+}
+
+/// Get integer argument. Numbers must have an exact integer representation
+pub fn check_integer(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+) -> Result<i64, String> {
+    check_number(function, args, position)?;
+
+    args[position - 1]
+        .to_integer()
+        .ok_or_else(|| bad_argument(function, position, "number has no integer representation"))
+}
+
+/// Get optional integer argument or default value if argument is `nil` or missing
+pub fn opt_integer(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+    default: i64,
+) -> Result<i64, String> {
+    match args.get(position - 1) {
+        Some(value) if !value.is_nil() => check_integer(function, args, position),
+        _ => Ok(default),
+    }
+}
+
+/// Get string argument. Numbers are converted to strings
+pub fn check_string(
+    function: &str,
+    args: &VecDeque<types::Type>,
+    position: usize,
+) -> Result<utils::ByteString, String> {
+    match args.get(position - 1).map(types::Type::unref) {
+        Some(types::Type::String(string)) => Ok(string.clone()),
+        Some(types::Type::Number(number)) => Ok(types::format_number(*number).into()),
+        Some(types::Type::Integer(integer)) => Ok(integer.to_string().into()),
+        _ => Err(type_error(function, args, position, "string")),
+    }
+}
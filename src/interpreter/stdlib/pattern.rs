@@ -0,0 +1,442 @@
+//! Lua patterns matching. Patterns work with bytes, like in the reference implementation, so character
+//! classes are ASCII classes and multibyte characters are matched byte by byte
+
+/// Escape character of patterns
+const ESCAPE: u8 = b'%';
+/// Patterns without these characters are searched as plain strings
+const SPECIALS: &[u8] = b"^$*+?.([%-";
+const MAX_CAPTURES: usize = 32;
+/// Maximum recursion depth of matching
+const MAX_MATCH_DEPTH: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CaptureLength {
+    /// Capture is open, its end is not matched yet
+    Unfinished,
+    /// Position capture `()`
+    Position,
+    Closed(usize),
+}
+
+/// Captured value: part of the source or position in the source, starting from 1
+#[derive(Debug, Clone, PartialEq)]
+pub enum Capture<'a> {
+    Bytes(&'a [u8]),
+    Position(usize),
+}
+
+/// Matcher state. Matching functions return the end of the match in the source or `None` if it doesn't match
+pub struct Matcher<'a> {
+    source: &'a [u8],
+    pattern: &'a [u8],
+    /// Number of captures, including unfinished
+    level: usize,
+    captures: [(usize, CaptureLength); MAX_CAPTURES],
+    depth: usize,
+}
+
+/// Check if pattern contains special characters. Patterns without them may be searched as plain strings
+pub fn has_specials(pattern: &[u8]) -> bool {
+    pattern.iter().any(|byte| SPECIALS.contains(byte))
+}
+
+/// Plain substring search. Returns start of the first occurrence
+pub fn find_plain(source: &[u8], pattern: &[u8]) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    source.windows(pattern.len()).position(|window| window == pattern)
+}
+
+impl<'a> Matcher<'a> {
+    pub fn new(source: &'a [u8], pattern: &'a [u8]) -> Self {
+        Matcher {
+            source,
+            pattern,
+            level: 0,
+            captures: [(0, CaptureLength::Unfinished); MAX_CAPTURES],
+            depth: MAX_MATCH_DEPTH,
+        }
+    }
+
+    /// Match the pattern, starting from `pattern_start`, at the `start` of the source.
+    /// Returns the end of the match. Captures of the previous match are discarded
+    pub fn matches(&mut self, start: usize, pattern_start: usize) -> Result<Option<usize>, String> {
+        self.level = 0;
+        self.depth = MAX_MATCH_DEPTH;
+        self.do_match(start, pattern_start)
+    }
+
+    /// Number of captures. Pattern without captures has one implicit capture: the whole match
+    pub fn captures_count(&self, whole_match: bool) -> usize {
+        if self.level == 0 && whole_match {
+            1
+        } else {
+            self.level
+        }
+    }
+
+    /// Capture with `index`, starting from 0. Whole match `start..end` is a capture of a pattern without captures
+    pub fn capture(&self, index: usize, start: usize, end: usize) -> Result<Capture<'a>, String> {
+        if index >= self.level {
+            return if index == 0 {
+                Ok(Capture::Bytes(&self.source[start..end]))
+            } else {
+                Err(format!("invalid capture index %{}", index + 1))
+            };
+        }
+
+        match self.captures[index] {
+            (_, CaptureLength::Unfinished) => Err("unfinished capture".to_string()),
+            (position, CaptureLength::Position) => Ok(Capture::Position(position + 1)),
+            (position, CaptureLength::Closed(length)) => {
+                Ok(Capture::Bytes(&self.source[position..position + length]))
+            }
+        }
+    }
+
+    /// All captures of the match or the whole match if there are no captures
+    pub fn captures(&self, start: usize, end: usize) -> Result<Vec<Capture<'a>>, String> {
+        (0..self.captures_count(true))
+            .map(|index| self.capture(index, start, end))
+            .collect()
+    }
+
+    /// Pattern byte. Zero byte after the end, like in C strings
+    fn pattern_at(&self, index: usize) -> u8 {
+        self.pattern.get(index).copied().unwrap_or(0)
+    }
+
+    fn source_at(&self, index: usize) -> u8 {
+        self.source.get(index).copied().unwrap_or(0)
+    }
+
+    fn do_match(&mut self, start: usize, pattern_start: usize) -> Result<Option<usize>, String> {
+        if self.depth == 0 {
+            return Err("pattern too complex".to_string());
+        }
+
+        self.depth -= 1;
+        let result = self.match_loop(start, pattern_start);
+        self.depth += 1;
+
+        result
+    }
+
+    fn match_loop(&mut self, start: usize, pattern_start: usize) -> Result<Option<usize>, String> {
+        let (mut s, mut p) = (start, pattern_start);
+
+        loop {
+            if p == self.pattern.len() {
+                return Ok(Some(s));
+            }
+
+            match self.pattern[p] {
+                b'(' => {
+                    return if self.pattern_at(p + 1) == b')' {
+                        self.start_capture(s, p + 2, CaptureLength::Position)
+                    } else {
+                        self.start_capture(s, p + 1, CaptureLength::Unfinished)
+                    };
+                }
+                b')' => return self.end_capture(s, p + 1),
+                b'$' if p + 1 == self.pattern.len() => {
+                    return Ok(if s == self.source.len() { Some(s) } else { None });
+                }
+                ESCAPE if self.pattern_at(p + 1) == b'b' => match self.match_balance(s, p + 2)? {
+                    Some(end) => {
+                        s = end;
+                        p += 4;
+                        continue;
+                    }
+                    None => return Ok(None),
+                },
+                ESCAPE if self.pattern_at(p + 1) == b'f' => {
+                    p += 2;
+                    if self.pattern_at(p) != b'[' {
+                        return Err("missing '[' after '%f' in pattern".to_string());
+                    }
+
+                    let class_end = self.class_end(p)?;
+                    let previous = if s == 0 { 0 } else { self.source[s - 1] };
+
+                    if !self.match_bracket_class(previous, p, class_end - 1)
+                        && self.match_bracket_class(self.source_at(s), p, class_end - 1)
+                    {
+                        p = class_end;
+                        continue;
+                    }
+                    return Ok(None);
+                }
+                ESCAPE if self.pattern_at(p + 1).is_ascii_digit() => {
+                    match self.match_capture(s, self.pattern_at(p + 1))? {
+                        Some(end) => {
+                            s = end;
+                            p += 2;
+                            continue;
+                        }
+                        None => return Ok(None),
+                    }
+                }
+                _ => {
+                    let class_end = self.class_end(p)?;
+                    let suffix = self.pattern_at(class_end);
+
+                    if !self.single_match(s, p, class_end) {
+                        // Accept empty match
+                        if suffix == b'*' || suffix == b'?' || suffix == b'-' {
+                            p = class_end + 1;
+                            continue;
+                        }
+                        return Ok(None);
+                    }
+
+                    match suffix {
+                        b'?' => match self.do_match(s + 1, class_end + 1)? {
+                            Some(end) => return Ok(Some(end)),
+                            None => {
+                                p = class_end + 1;
+                                continue;
+                            }
+                        },
+                        b'+' => return self.max_expand(s + 1, p, class_end),
+                        b'*' => return self.max_expand(s, p, class_end),
+                        b'-' => return self.min_expand(s, p, class_end),
+                        _ => {
+                            s += 1;
+                            p = class_end;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// End of the single character class, starting at `p`
+    fn class_end(&self, p: usize) -> Result<usize, String> {
+        let mut p = p;
+        let class = self.pattern[p];
+        p += 1;
+
+        match class {
+            ESCAPE => {
+                if p >= self.pattern.len() {
+                    return Err("malformed pattern (ends with '%')".to_string());
+                }
+                Ok(p + 1)
+            }
+            b'[' => {
+                if self.pattern_at(p) == b'^' {
+                    p += 1;
+                }
+
+                // The first character is a part of the set, even if it is `]`
+                loop {
+                    if p >= self.pattern.len() {
+                        return Err("malformed pattern (missing ']')".to_string());
+                    }
+
+                    let byte = self.pattern[p];
+                    p += 1;
+                    if byte == ESCAPE && p < self.pattern.len() {
+                        p += 1;
+                    }
+
+                    if self.pattern_at(p) == b']' {
+                        break;
+                    }
+                }
+
+                Ok(p + 1)
+            }
+            _ => Ok(p),
+        }
+    }
+
+    /// Check if the source character at `s` matches the class `p..class_end`
+    fn single_match(&self, s: usize, p: usize, class_end: usize) -> bool {
+        if s >= self.source.len() {
+            return false;
+        }
+
+        let byte = self.source[s];
+        match self.pattern[p] {
+            b'.' => true,
+            ESCAPE => match_class(byte, self.pattern[p + 1]),
+            b'[' => self.match_bracket_class(byte, p, class_end - 1),
+            class => class == byte,
+        }
+    }
+
+    /// Check if the byte belongs to the set `[...]`. `p` points to `[` and `end` points to `]`
+    fn match_bracket_class(&self, byte: u8, p: usize, end: usize) -> bool {
+        let mut p = p + 1;
+        let negated = self.pattern[p] == b'^';
+        if negated {
+            p += 1;
+        }
+
+        while p < end {
+            let class = self.pattern[p];
+
+            if class == ESCAPE {
+                p += 1;
+                if match_class(byte, self.pattern[p]) {
+                    return !negated;
+                }
+            } else if self.pattern_at(p + 1) == b'-' && p + 2 < end {
+                if class <= byte && byte <= self.pattern[p + 2] {
+                    return !negated;
+                }
+                p += 2;
+            } else if class == byte {
+                return !negated;
+            }
+
+            p += 1;
+        }
+
+        negated
+    }
+
+    /// Greedy repetition: the longest sequence, which allows the rest of the pattern to match
+    fn max_expand(&mut self, s: usize, p: usize, class_end: usize) -> Result<Option<usize>, String> {
+        let mut count = 0;
+        while self.single_match(s + count, p, class_end) {
+            count += 1;
+        }
+
+        loop {
+            if let Some(end) = self.do_match(s + count, class_end + 1)? {
+                return Ok(Some(end));
+            }
+
+            if count == 0 {
+                return Ok(None);
+            }
+            count -= 1;
+        }
+    }
+
+    /// Lazy repetition: the shortest sequence, which allows the rest of the pattern to match
+    fn min_expand(&mut self, s: usize, p: usize, class_end: usize) -> Result<Option<usize>, String> {
+        let mut s = s;
+
+        loop {
+            if let Some(end) = self.do_match(s, class_end + 1)? {
+                return Ok(Some(end));
+            }
+
+            if self.single_match(s, p, class_end) {
+                s += 1;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn start_capture(&mut self, s: usize, p: usize, length: CaptureLength) -> Result<Option<usize>, String> {
+        if self.level >= MAX_CAPTURES {
+            return Err("too many captures".to_string());
+        }
+
+        self.captures[self.level] = (s, length);
+        self.level += 1;
+
+        let result = self.do_match(s, p)?;
+        if result.is_none() {
+            self.level -= 1;
+        }
+
+        Ok(result)
+    }
+
+    fn end_capture(&mut self, s: usize, p: usize) -> Result<Option<usize>, String> {
+        let index = (0..self.level)
+            .rev()
+            .find(|&index| self.captures[index].1 == CaptureLength::Unfinished)
+            .ok_or_else(|| "invalid pattern capture".to_string())?;
+
+        self.captures[index].1 = CaptureLength::Closed(s - self.captures[index].0);
+
+        let result = self.do_match(s, p)?;
+        if result.is_none() {
+            self.captures[index].1 = CaptureLength::Unfinished;
+        }
+
+        Ok(result)
+    }
+
+    /// `%bxy` matches balanced sequence, starting with `x` and ending with `y`
+    fn match_balance(&self, s: usize, p: usize) -> Result<Option<usize>, String> {
+        if p + 1 >= self.pattern.len() {
+            return Err("malformed pattern (missing arguments to '%b')".to_string());
+        }
+
+        let (open, close) = (self.pattern[p], self.pattern[p + 1]);
+        if s >= self.source.len() || self.source[s] != open {
+            return Ok(None);
+        }
+
+        let mut depth = 1;
+        for index in s + 1..self.source.len() {
+            let byte = self.source[index];
+
+            if byte == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(Some(index + 1));
+                }
+            } else if byte == open {
+                depth += 1;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Back reference `%1`-`%9` matches the same text as the capture
+    fn match_capture(&self, s: usize, digit: u8) -> Result<Option<usize>, String> {
+        let index = (digit as usize).wrapping_sub(b'1' as usize);
+
+        let length = match self.captures.get(index) {
+            Some((_, CaptureLength::Closed(length))) if index < self.level => *length,
+            Some((_, CaptureLength::Position)) if index < self.level => return Ok(None),
+            _ => return Err(format!("invalid capture index %{}", index.wrapping_add(1))),
+        };
+
+        let start = self.captures[index].0;
+        let captured = &self.source[start..start + length];
+
+        if self.source.len() - s >= length && &self.source[s..s + length] == captured {
+            Ok(Some(s + length))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Check if the byte belongs to the class `%x`. Upper case class is a complement of the lower case one.
+/// Other characters match themselves
+fn match_class(byte: u8, class: u8) -> bool {
+    let matches = match class.to_ascii_lowercase() {
+        b'a' => byte.is_ascii_alphabetic(),
+        b'c' => byte.is_ascii_control(),
+        b'd' => byte.is_ascii_digit(),
+        b'g' => byte.is_ascii_graphic(),
+        b'l' => byte.is_ascii_lowercase(),
+        b'p' => byte.is_ascii_punctuation(),
+        b's' => byte.is_ascii_whitespace() || byte == b'\x0b',
+        b'u' => byte.is_ascii_uppercase(),
+        b'w' => byte.is_ascii_alphanumeric(),
+        b'x' => byte.is_ascii_hexdigit(),
+        _ => return class == byte,
+    };
+
+    if class.is_ascii_uppercase() {
+        !matches
+    } else {
+        matches
+    }
+}
@@ -0,0 +1,481 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use crate::error;
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::base::{to_string, to_string_meta};
+use super::format;
+use super::pattern::{self, Capture, Matcher};
+use super::{bad_argument, check_any, check_integer, check_number, check_string, opt_integer};
+
+/// Maximum length of strings, produced by `string.rep`
+const MAX_STRING_LENGTH: usize = i32::MAX as usize;
+
+pub fn load(env: &mut environment::Environment) {
+    native::register_library(
+        env,
+        "string",
+        &[
+            ("byte", byte),
+            ("char", char),
+            ("find", find),
+            ("format", format),
+            ("gmatch", gmatch),
+            ("gsub", gsub),
+            ("len", len),
+            ("lower", lower),
+            ("match", match_),
+            ("rep", rep),
+            ("reverse", reverse),
+            ("sub", sub),
+            ("upper", upper),
+        ],
+    );
+
+    // Strings share metatable with the library as `__index`, so methods may be called with `s:upper()`
+    let library = env.get("string").expect("Should never happen").borrow().clone();
+    let mut metatable = types::Table::new(env.next_global_id());
+    metatable
+        .set(types::Type::String("__index".into()), library)
+        .unwrap();
+
+    env.set_string_metatable(utils::Shared::new(metatable));
+}
+
+/// Convert string position to absolute one. Negative positions count from the end of the string
+fn absolute_position(position: i64, length: usize) -> i64 {
+    if position >= 0 {
+        position
+    } else if position.unsigned_abs() > length as u64 {
+        0
+    } else {
+        length as i64 + position + 1
+    }
+}
+
+fn capture_value(capture: Capture) -> types::Type {
+    match capture {
+        Capture::Bytes(bytes) => types::Type::String(bytes.into()),
+        Capture::Position(position) => types::Type::Integer(position as i64),
+    }
+}
+
+/// Values of captures of the match `start..end`
+fn capture_values(matcher: &Matcher, start: usize, end: usize) -> Result<VecDeque<types::Type>, String> {
+    Ok(matcher.captures(start, end)?.into_iter().map(capture_value).collect())
+}
+
+/// Byte codes of characters from `i` to `j`. By default returns the first byte
+fn byte(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("byte", &args, 1)?;
+    let start = absolute_position(opt_integer("byte", &args, 2, 1)?, string.len());
+    let end = absolute_position(opt_integer("byte", &args, 3, start)?, string.len());
+
+    let (start, end) = (start.max(1), end.min(string.len() as i64));
+    if start > end {
+        return Ok(VecDeque::new());
+    }
+
+    Ok(string.as_bytes()[start as usize - 1..end as usize]
+        .iter()
+        .map(|byte| types::Type::Integer(*byte as i64))
+        .collect())
+}
+
+/// String from byte codes
+fn char(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let mut bytes = Vec::with_capacity(args.len());
+
+    for position in 1..=args.len() {
+        match check_integer("char", &args, position)? {
+            code @ 0..=255 => bytes.push(code as u8),
+            _ => return Err(bad_argument("char", position, "value out of range").into()),
+        }
+    }
+
+    Ok(VecDeque::from(vec![types::Type::String(bytes.into())]))
+}
+
+/// Find the first match of the pattern. Returns start and end of the match and captures
+fn find(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    find_pattern("find", args, true)
+}
+
+/// Find the first match of the pattern. Returns captures or the whole match
+fn match_(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    find_pattern("match", args, false)
+}
+
+/// Common implementation of `find` and `match`
+fn find_pattern(name: &str, args: VecDeque<types::Type>, find: bool) -> native::NativeResult {
+    let source = check_string(name, &args, 1)?;
+    let pattern = check_string(name, &args, 2)?;
+    let (source, pattern) = (source.as_bytes(), pattern.as_bytes());
+
+    let init = absolute_position(opt_integer(name, &args, 3, 1)?, source.len()).max(1) as usize;
+    if init > source.len() + 1 {
+        return Ok(VecDeque::from(vec![types::Type::Nil]));
+    }
+
+    let plain = args.get(3).map(types::Type::as_bool).unwrap_or(false);
+    if find && (plain || !pattern::has_specials(pattern)) {
+        return Ok(match pattern::find_plain(&source[init - 1..], pattern) {
+            Some(position) => {
+                let start = init + position;
+                VecDeque::from(vec![
+                    types::Type::Integer(start as i64),
+                    types::Type::Integer((start + pattern.len() - 1) as i64),
+                ])
+            }
+            None => VecDeque::from(vec![types::Type::Nil]),
+        });
+    }
+
+    let anchor = pattern.first() == Some(&b'^');
+    let pattern_start = if anchor { 1 } else { 0 };
+    let mut matcher = Matcher::new(source, pattern);
+
+    for start in init - 1..=source.len() {
+        if let Some(end) = matcher.matches(start, pattern_start)? {
+            if !find {
+                return Ok(capture_values(&matcher, start, end)?);
+            }
+
+            let mut result = VecDeque::from(vec![
+                types::Type::Integer(start as i64 + 1),
+                types::Type::Integer(end as i64),
+            ]);
+            for index in 0..matcher.captures_count(false) {
+                result.push_back(capture_value(matcher.capture(index, start, end)?));
+            }
+            return Ok(result);
+        }
+
+        if anchor {
+            break;
+        }
+    }
+
+    Ok(VecDeque::from(vec![types::Type::Nil]))
+}
+
+/// Format arguments according to printf-style template. `%s` converts values with `tostring` rules
+fn format(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let template = check_string("format", &args, 1)?;
+    let template = template.as_bytes();
+    let mut result = Vec::new();
+    let mut position = 1;
+    let mut index = 0;
+
+    while index < template.len() {
+        let byte = template[index];
+        index += 1;
+
+        if byte != b'%' {
+            result.push(byte);
+            continue;
+        } else if template.get(index) == Some(&b'%') {
+            result.push(b'%');
+            index += 1;
+            continue;
+        }
+
+        let (spec, length) = format::Spec::parse(&template[index..])?;
+        index += length;
+        position += 1;
+
+        match spec.conversion {
+            b'c' => spec.format_char(check_integer("format", &args, position)? as u8, &mut result),
+            b'd' | b'i' | b'u' | b'o' | b'x' | b'X' => {
+                spec.format_integer(check_integer("format", &args, position)?, &mut result)
+            }
+            b'q' => {
+                if !format::quote(check_any("format", &args, position)?, &mut result) {
+                    return Err(bad_argument("format", position, "value has no literal form").into());
+                }
+            }
+            b's' => {
+                let string = to_string_meta(check_any("format", &args, position)?, env)?;
+                if spec.has_modifiers() && string.contains(&0) {
+                    return Err(bad_argument("format", position, "string contains zeros").into());
+                }
+                spec.format_string(string.as_bytes(), &mut result)
+            }
+            _ => spec.format_float(check_number("format", &args, position)?, &mut result),
+        }
+    }
+
+    Ok(VecDeque::from(vec![types::Type::String(result.into())]))
+}
+
+/// Iterator over all matches of the pattern. Each call returns captures of the next match
+fn gmatch(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let source = check_string("gmatch", &args, 1)?;
+    let pattern = check_string("gmatch", &args, 2)?;
+    let init = absolute_position(opt_integer("gmatch", &args, 3, 1)?, source.len()).max(1) as usize;
+
+    // Position of the next search and the end of the last match. Empty match can't end, where
+    // the previous match ended
+    let state = Cell::new((init - 1, None));
+
+    let iterator = native::function(&mut env.borrow_mut(), "gmatch_iterator", move |_, _| {
+        let (mut start, last_match) = state.get();
+        let mut matcher = Matcher::new(source.as_bytes(), pattern.as_bytes());
+
+        while start <= source.len() {
+            match matcher.matches(start, 0)? {
+                Some(end) if Some(end) != last_match => {
+                    state.set((end, Some(end)));
+                    return Ok(capture_values(&matcher, start, end)?);
+                }
+                _ => start += 1,
+            }
+        }
+
+        state.set((start, last_match));
+        Ok(VecDeque::from(vec![types::Type::Nil]))
+    });
+
+    Ok(VecDeque::from(vec![iterator]))
+}
+
+/// Replace matches of the pattern with the replacement string, table value or function result.
+/// Returns the new string and number of matches
+fn gsub(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let source = check_string("gsub", &args, 1)?;
+    let pattern = check_string("gsub", &args, 2)?;
+    let (source, pattern) = (source.as_bytes(), pattern.as_bytes());
+
+    let replacement = match args.get(2).map(types::Type::unref) {
+        Some(
+            value @ types::Type::String(_)
+            | value @ types::Type::Number(_)
+            | value @ types::Type::Integer(_)
+            | value @ types::Type::Table(_)
+            | value @ types::Type::Function { .. }
+            | value @ types::Type::NativeFunction { .. },
+        ) => value.clone(),
+        value => {
+            let got = value.map(types::Type::type_name).unwrap_or("no value");
+            let message = format!("string/function/table expected, got {}", got);
+            return Err(bad_argument("gsub", 3, &message).into());
+        }
+    };
+    let max_replacements = opt_integer("gsub", &args, 4, source.len() as i64 + 1)?;
+
+    let anchor = pattern.first() == Some(&b'^');
+    let pattern_start = if anchor { 1 } else { 0 };
+    let mut matcher = Matcher::new(source, pattern);
+
+    let mut result = Vec::with_capacity(source.len());
+    let (mut start, mut last_match, mut count) = (0, None, 0);
+
+    while count < max_replacements {
+        match matcher.matches(start, pattern_start)? {
+            Some(end) if Some(end) != last_match => {
+                count += 1;
+                replace(env, &matcher, &replacement, source, start, end, &mut result)?;
+                start = end;
+                last_match = Some(end);
+            }
+            _ if start < source.len() => {
+                result.push(source[start]);
+                start += 1;
+            }
+            _ => break,
+        }
+
+        if anchor {
+            break;
+        }
+    }
+
+    result.extend_from_slice(&source[start..]);
+    Ok(VecDeque::from(vec![
+        types::Type::String(result.into()),
+        types::Type::Integer(count),
+    ]))
+}
+
+/// Add replacement of the match `start..end` to the result
+fn replace(
+    env: &mut utils::Shared<environment::Environment>,
+    matcher: &Matcher,
+    replacement: &types::Type,
+    source: &[u8],
+    start: usize,
+    end: usize,
+    result: &mut Vec<u8>,
+) -> Result<(), error::Error> {
+    let matched = &source[start..end];
+
+    let value = match replacement {
+        types::Type::Table(table) => {
+            let key = capture_value(matcher.capture(0, start, end)?);
+            table.borrow().get(&key)
+        }
+        types::Type::Function { .. } | types::Type::NativeFunction { .. } => {
+            let captures = capture_values(matcher, start, end)?;
+            native::call(replacement.clone(), captures, env)?
+                .pop_front()
+                .unwrap_or(types::Type::Nil)
+        }
+        // String replacement may refer to captures with `%1`-`%9` and to the whole match with `%0`
+        _ => {
+            let replacement = to_string(replacement);
+            let mut bytes = replacement.iter().copied();
+
+            while let Some(byte) = bytes.next() {
+                if byte != b'%' {
+                    result.push(byte);
+                    continue;
+                }
+
+                match bytes.next() {
+                    Some(b'%') => result.push(b'%'),
+                    Some(b'0') => result.extend_from_slice(matched),
+                    Some(digit @ b'1'..=b'9') => {
+                        let capture = matcher.capture((digit - b'1') as usize, start, end)?;
+                        result.extend_from_slice(to_string(&capture_value(capture)).as_bytes());
+                    }
+                    _ => return Err("invalid use of '%' in replacement string".to_string().into()),
+                }
+            }
+
+            return Ok(());
+        }
+    };
+
+    // False and nil keep the original match
+    match value.unref() {
+        types::Type::Nil | types::Type::Boolean(false) => result.extend_from_slice(matched),
+        types::Type::String(_) | types::Type::Number(_) | types::Type::Integer(_) => {
+            result.extend_from_slice(to_string(&value).as_bytes())
+        }
+        value => return Err(format!("invalid replacement value (a {})", value.type_name()).into()),
+    }
+
+    Ok(())
+}
+
+/// String length in bytes
+fn len(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("len", &args, 1)?;
+
+    Ok(VecDeque::from(vec![types::Type::Integer(string.len() as i64)]))
+}
+
+/// Convert ASCII letters to lower case
+fn lower(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("lower", &args, 1)?;
+
+    Ok(VecDeque::from(vec![types::Type::String(string.to_ascii_lowercase().into())]))
+}
+
+/// String, repeated `n` times and separated with `sep`
+fn rep(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("rep", &args, 1)?;
+    let count = check_integer("rep", &args, 2)?;
+    let separator = match args.get(2) {
+        Some(value) if !value.is_nil() => check_string("rep", &args, 3)?,
+        _ => utils::ByteString::default(),
+    };
+
+    if count <= 0 {
+        return Ok(VecDeque::from(vec![types::Type::String(utils::ByteString::default())]));
+    }
+
+    let length = match (string.len() + separator.len())
+        .checked_mul(count as usize)
+        .filter(|length| *length <= MAX_STRING_LENGTH)
+    {
+        // Separator isn't added after the last copy
+        Some(length) => length - separator.len(),
+        None => return Err("resulting string too large".to_string().into()),
+    };
+
+    // Repeating an empty string many times must not iterate over the copies
+    if length == 0 {
+        return Ok(VecDeque::from(vec![types::Type::String(utils::ByteString::default())]));
+    }
+
+    let mut result = Vec::with_capacity(length);
+    result.extend_from_slice(&string);
+    for _ in 1..count {
+        result.extend_from_slice(&separator);
+        result.extend_from_slice(&string);
+    }
+
+    Ok(VecDeque::from(vec![types::Type::String(result.into())]))
+}
+
+/// String with bytes in reverse order
+fn reverse(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("reverse", &args, 1)?;
+    let bytes: Vec<u8> = string.iter().rev().copied().collect();
+
+    Ok(VecDeque::from(vec![types::Type::String(bytes.into())]))
+}
+
+/// Substring from `i` to `j` inclusive. Negative positions count from the end of the string
+fn sub(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("sub", &args, 1)?;
+    let start = absolute_position(check_integer("sub", &args, 2)?, string.len()).max(1);
+    let end = absolute_position(opt_integer("sub", &args, 3, -1)?, string.len()).min(string.len() as i64);
+
+    let result = if start <= end {
+        types::Type::String(string[start as usize - 1..end as usize].into())
+    } else {
+        types::Type::String(utils::ByteString::default())
+    };
+
+    Ok(VecDeque::from(vec![result]))
+}
+
+/// Convert ASCII letters to upper case
+fn upper(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let string = check_string("upper", &args, 1)?;
+
+    Ok(VecDeque::from(vec![types::Type::String(string.to_ascii_uppercase().into())]))
+}
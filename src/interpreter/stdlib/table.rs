@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::{check_table, opt_integer};
+
+pub fn load(env: &mut environment::Environment) {
+    native::register_library(
+        env,
+        "table",
+        &[("pack", pack), ("unpack", unpack)],
+    );
+}
+
+/// Pack arguments into a table with `n` field, containing number of arguments
+fn pack(
+    env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let mut table = types::Table::new(env.borrow_mut().next_global_id());
+    let count = args.len();
+
+    for (index, value) in args.into_iter().enumerate() {
+        table.set(types::Type::Integer((index + 1) as i64), value)?;
+    }
+    table.set(
+        types::Type::String("n".into()),
+        types::Type::Integer(count as i64),
+    )?;
+
+    Ok(VecDeque::from(vec![types::Type::Table(utils::Shared::new(table))]))
+}
+
+/// Return table elements from `i` to `j`. By default from 1 to the length of the table
+fn unpack(
+    _env: &mut utils::Shared<environment::Environment>,
+    args: VecDeque<types::Type>,
+) -> native::NativeResult {
+    let table = check_table("unpack", &args, 1)?;
+    let table = table.borrow();
+    let first = opt_integer("unpack", &args, 2, 1)?;
+    let last = opt_integer("unpack", &args, 3, table.border() as i64)?;
+
+    if first > last {
+        return Ok(VecDeque::new());
+    }
+
+    if !matches!(last.checked_sub(first), Some(count) if count < 1_000_000) {
+        return Err("too many results to unpack".to_string().into());
+    }
+
+    Ok((first..=last)
+        .map(|index| table.get(&types::Type::Integer(index)))
+        .collect())
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use crate::interpreter::types::{float_to_integer, Type};
+use crate::utils;
+
+/// Array part never grows beyond 2^MAX_ARRAY_BITS elements
+const MAX_ARRAY_BITS: usize = 30;
+
+/// Lua table. Like in the reference implementation, it has an array part, which keeps values of
+/// integer keys `1..n`, and a hash part for the rest of the keys. Sizes of both parts are recomputed
+/// only when a new key doesn't fit into a full hash part, so appending to a sequence is O(1) amortized
+pub struct Table {
+    /// For comparison
+    pub id: u64,
+    /// Values of keys `1..=array.len()`. Absent fields are kept as `nil`
+    array: Vec<Type>,
+    /// Hash part entries in insertion order. Removed fields keep their keys with `nil` value until the
+    /// next rehash, so `next` can continue traversal after the current field is cleared
+    entries: Vec<(Type, Type)>,
+    /// Position of each hash part key in `entries`
+    index: HashMap<Type, usize>,
+    /// Number of entries hash part holds before rehash
+    capacity: usize,
+    /// Metatable is a regular table, which may be shared between several tables
+    pub metatable: Option<utils::Shared<Table>>,
+}
+
+impl Table {
+    pub fn new(id: u64) -> Self {
+        Table {
+            id,
+            array: Vec::new(),
+            entries: Vec::new(),
+            index: HashMap::new(),
+            capacity: 0,
+            metatable: None,
+        }
+    }
+
+    /// Float keys with integer values are converted to integers, so `t[1]` and `t[1.0]` are the same field
+    fn normalize_key(key: &Type) -> Type {
+        match key.unref() {
+            Type::Number(number) => match float_to_integer(*number) {
+                Some(integer) => Type::Integer(integer),
+                None => Type::Number(*number),
+            },
+            key => key.clone(),
+        }
+    }
+
+    /// Position of the key in the array part
+    fn array_index(&self, key: &Type) -> Option<usize> {
+        match key {
+            Type::Integer(key) if *key >= 1 && *key as u64 <= self.array.len() as u64 => {
+                Some(*key as usize - 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get field value without invoking metamethods. Fields with `nil` value are considered absent
+    pub fn get(&self, key: &Type) -> Type {
+        match key.unref() {
+            Type::Integer(key) => self.get_integer(*key),
+            Type::Number(_) => self.get_normalized(&Table::normalize_key(key)),
+            key => self.get_normalized(key),
+        }
+    }
+
+    fn get_integer(&self, key: i64) -> Type {
+        if key >= 1 && key as u64 <= self.array.len() as u64 {
+            self.array[key as usize - 1].clone()
+        } else {
+            self.get_normalized(&Type::Integer(key))
+        }
+    }
+
+    fn get_normalized(&self, key: &Type) -> Type {
+        match self.array_index(key) {
+            Some(index) => self.array[index].clone(),
+            None => match self.index.get(key) {
+                Some(&position) => self.entries[position].1.clone(),
+                None => Type::Nil,
+            },
+        }
+    }
+
+    /// Set field value without invoking metamethods. Assigning `nil` removes the field
+    pub fn set(&mut self, key: Type, value: Type) -> Result<(), String> {
+        match key.unref() {
+            Type::Nil => return Err("table index is nil".to_string()),
+            Type::Number(number) if number.is_nan() => return Err("table index is NaN".to_string()),
+            _ => (),
+        }
+
+        let key = Table::normalize_key(&key);
+
+        if let Some(index) = self.array_index(&key) {
+            self.array[index] = value;
+            return Ok(());
+        }
+
+        if let Some(&position) = self.index.get(&key) {
+            self.entries[position].1 = value;
+            return Ok(());
+        }
+
+        // Removing absent field changes nothing
+        if value.is_nil() {
+            return Ok(());
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.rehash(&key);
+
+            // The key may belong to the array part after resize
+            if let Some(index) = self.array_index(&key) {
+                self.array[index] = value;
+                return Ok(());
+            }
+        }
+
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+
+        Ok(())
+    }
+
+    /// Recompute sizes of the array and hash parts to fit all present fields and the new key.
+    /// Array size is the largest power of two `n`, such that more than `n/2` of keys `1..n` are present
+    fn rehash(&mut self, new_key: &Type) {
+        // Number of integer keys in slices (2^(i - 1), 2^i]
+        let mut slices = [0usize; MAX_ARRAY_BITS + 1];
+        let mut total = 0;
+        let mut count = |key: i64| {
+            if (1..=1 << MAX_ARRAY_BITS).contains(&key) {
+                slices[ceil_log2(key as usize)] += 1;
+                total += 1;
+            }
+        };
+
+        for (index, value) in self.array.iter().enumerate() {
+            if !value.is_nil() {
+                count(index as i64 + 1);
+            }
+        }
+        for (key, value) in self.entries.iter() {
+            if let (Type::Integer(key), false) = (key, value.is_nil()) {
+                count(*key);
+            }
+        }
+        if let Type::Integer(key) = new_key {
+            count(*key);
+        }
+
+        let mut array_size = 0;
+        let mut accumulated = 0;
+        for (bits, count) in slices.iter().enumerate() {
+            let size = 1 << bits;
+            if size / 2 >= total {
+                break;
+            }
+
+            accumulated += count;
+            if accumulated > size / 2 {
+                array_size = size;
+            }
+        }
+
+        self.resize(array_size, new_key);
+    }
+
+    fn resize(&mut self, array_size: usize, new_key: &Type) {
+        let mut entries = std::mem::take(&mut self.entries);
+        self.index.clear();
+
+        // Fields, which don't fit into a shrunk array, go to the hash part
+        if array_size < self.array.len() {
+            let moved = self.array.drain(array_size..).enumerate();
+            entries.extend(
+                moved.map(|(index, value)| (Type::Integer((array_size + index + 1) as i64), value)),
+            );
+        }
+        self.array.resize(array_size, Type::Nil);
+
+        for (key, value) in entries.into_iter().filter(|(_, value)| !value.is_nil()) {
+            match self.array_index(&key) {
+                Some(index) => self.array[index] = value,
+                None => {
+                    self.index.insert(key.clone(), self.entries.len());
+                    self.entries.push((key, value));
+                }
+            }
+        }
+
+        let size = self.entries.len() + self.array_index(new_key).is_none() as usize;
+        self.capacity = if size == 0 {
+            0
+        } else {
+            size.next_power_of_two()
+        };
+    }
+
+    /// Table border: an integer `n`, such that `t[n]` is present and `t[n + 1]` is absent, or 0 if `t[1]`
+    /// is absent. When a table has several borders (a sequence with holes), any of them may be returned
+    pub fn border(&self) -> usize {
+        let size = self.array.len();
+
+        if size > 0 && self.array[size - 1].is_nil() {
+            // Binary search in the array part
+            let (mut low, mut high) = (0, size);
+            while high - low > 1 {
+                let middle = (low + high) / 2;
+                if self.array[middle - 1].is_nil() {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            return low;
+        }
+
+        if self.entries.is_empty() {
+            return size;
+        }
+
+        // Unbound search in the hash part: find absent key, doubling the index
+        let (mut low, mut high) = (size, size + 1);
+        while !self.get_integer(high as i64).is_nil() {
+            low = high;
+            if high > i64::MAX as usize / 2 {
+                // Malicious table: resort to linear search
+                let mut border = 1;
+                while !self.get_integer(border as i64).is_nil() {
+                    border += 1;
+                }
+                return border - 1;
+            }
+            high *= 2;
+        }
+
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if self.get_integer(middle as i64).is_nil() {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        low
+    }
+
+    /// Field following the key in the table traversal order: array part first, then hash part.
+    /// `nil` key starts the traversal. Returns `None` after the last field
+    pub fn next(&self, key: &Type) -> Result<Option<(Type, Type)>, String> {
+        let key = Table::normalize_key(key);
+
+        let start = match self.array_index(&key) {
+            _ if key.is_nil() => 0,
+            Some(index) => index + 1,
+            None => match self.index.get(&key) {
+                Some(&position) => self.array.len() + position + 1,
+                None => return Err("invalid key to 'next'".to_string()),
+            },
+        };
+
+        for index in start..self.array.len() {
+            if !self.array[index].is_nil() {
+                return Ok(Some((
+                    Type::Integer(index as i64 + 1),
+                    self.array[index].clone(),
+                )));
+            }
+        }
+
+        let start = start.saturating_sub(self.array.len());
+        Ok(self.entries[start..]
+            .iter()
+            .find(|(_, value)| !value.is_nil())
+            .cloned())
+    }
+
+    /// Get metamethod from the table metatable
+    pub fn metamethod(&self, name: &str) -> Option<Type> {
+        let metatable = self.metatable.as_ref()?;
+        let metamethod = metatable.borrow().get(&Type::String(name.into()));
+
+        if metamethod.is_nil() {
+            None
+        } else {
+            Some(metamethod)
+        }
+    }
+}
+
+/// Smallest `n`, such that `2^n >= value`
+fn ceil_log2(value: usize) -> usize {
+    (std::mem::size_of::<usize>() * 8) - (value - 1).leading_zeros() as usize
+}
+
+/// Hash part is printed as a map of present fields
+struct HashPart<'a>(&'a [(Type, Type)]);
+
+impl ::std::fmt::Debug for HashPart<'_> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .filter(|(_, value)| !value.is_nil())
+                    .map(|(key, value)| (key, value)),
+            )
+            .finish()
+    }
+}
+
+/// Debug prints only metatable id, because table may be a metatable of itself
+impl ::std::fmt::Debug for Table {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(
+            f,
+            "Table {{ id: {}, array: {:?}, hash: {:?}, metatable: {:?} }}",
+            self.id,
+            self.array,
+            HashPart(&self.entries),
+            self.metatable
+                .as_ref()
+                .map(|metatable| metatable.borrow().id)
+        )
+    }
+}
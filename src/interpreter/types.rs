@@ -1,27 +1,33 @@
 use std::cell::RefCell;
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::ast::expressions;
-use crate::interpreter::environment;
+use crate::ast::lexer::numerals;
+use crate::error;
+use crate::interpreter::expressions::functions;
+use crate::interpreter::stdlib::format;
+use crate::interpreter::{self, environment, native};
 use crate::utils;
 
+pub use crate::interpreter::table::Table;
+
+#[derive(Clone)]
 pub enum Type {
     Nil,
     Boolean(bool),
+    /// Float number
     Number(f64),
-    String(String),
+    /// Integer number. Both floats and integers have `number` type in Lua
+    Integer(i64),
+    String(utils::ByteString),
     /// Reference to an existing value
     Reference(Rc<RefCell<Type>>),
     Vector(VecDeque<Type>),
-    Table {
-        /// For comparison
-        id: u64,
-        map: HashMap<Type, Rc<RefCell<Type>>>,
-        metatable: HashMap<String, Type>,
-        border: usize,
-    },
+    /// Tables are shared objects. Copy of the value references the same table
+    Table(utils::Shared<Table>),
     Function {
         /// For comparison
         id: u64,
@@ -31,21 +37,44 @@ pub enum Type {
         // XXX: Capture only vars function needs?
         env: utils::Shared<environment::Environment>,
     },
+    /// Function implemented in Rust
+    NativeFunction {
+        /// For comparison
+        id: u64,
+        name: String,
+        function: Rc<native::NativeFunction>,
+    },
 }
 
 impl Type {
-    pub fn call(&self, _arguments: Vec<&Type>) -> Type {
-        unimplemented!();
+    /// Call function value from the interpreter. Tables with `__call` metamethod may be called as well
+    pub fn call(
+        &self,
+        exp: &dyn interpreter::Eval,
+        arguments: Vec<&Type>,
+        env: &mut utils::Shared<environment::Environment>,
+    ) -> error::Result<VecDeque<Type>> {
+        let arguments = arguments.into_iter().map(Type::value).collect();
+        functions::call(exp, self.value(), arguments, env)
     }
 
-    pub fn as_bool(&self) -> bool {
-        match self {
-            Type::Nil => false,
-            Type::Boolean(false) => false,
-            _ => true,
+    /// Type name as returned by Lua `type` function
+    pub fn type_name(&self) -> &'static str {
+        match self.unref() {
+            Type::Nil => "nil",
+            Type::Boolean(_) => "boolean",
+            Type::Number(_) | Type::Integer(_) => "number",
+            Type::String(_) => "string",
+            Type::Table(_) => "table",
+            Type::Function { .. } | Type::NativeFunction { .. } => "function",
+            Type::Vector(_) | Type::Reference(_) => "vector",
         }
     }
 
+    pub fn as_bool(&self) -> bool {
+        !matches!(self.unref(), Type::Nil | Type::Boolean(false))
+    }
+
     /// Check if type is nil. We often have special cases for nils
     pub fn is_nil(&self) -> bool {
         match self {
@@ -55,13 +84,140 @@ impl Type {
         }
     }
 
-    /// Create reference to an object or clone reference
-    pub fn into_reference(self) -> Rc<RefCell<Self>> {
-        match self {
-            Type::Reference(typeref) => typeref,
-            _ => Rc::new(RefCell::new(self)),
+    /// Get referenced value. Values may contain chains of references, so we follow them to the end
+    pub fn unref(&self) -> &Type {
+        let mut value = self;
+
+        while let Type::Reference(reference) = value {
+            // Same as `match_type`, we can't return `Ref` here, because it's a temporary
+            value = unsafe { &*reference.as_ptr() };
+        }
+
+        value
+    }
+
+    /// Convert value for arithmetic operation. Strings are converted to numbers
+    pub fn to_number(&self) -> Option<Type> {
+        match self.unref() {
+            Type::Number(number) => Some(Type::Number(*number)),
+            Type::Integer(integer) => Some(Type::Integer(*integer)),
+            Type::String(string) => std::str::from_utf8(string).ok().and_then(parse_number),
+            _ => None,
+        }
+    }
+
+    /// Convert value to float. Strings are converted to numbers
+    pub fn to_float(&self) -> Option<f64> {
+        match self.to_number()? {
+            Type::Number(number) => Some(number),
+            Type::Integer(integer) => Some(integer as f64),
+            _ => None,
         }
     }
+
+    /// Convert value to integer. Floats must have an exact integer representation
+    pub fn to_integer(&self) -> Option<i64> {
+        match self.to_number()? {
+            Type::Number(number) => float_to_integer(number),
+            Type::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    /// Copy of the referenced value. Tables and functions are copied as references to the same object
+    pub fn value(&self) -> Type {
+        self.unref().clone()
+    }
+
+    /// Value metatable. Only tables may have metatables
+    pub fn metatable(&self) -> Option<utils::Shared<Table>> {
+        match self.unref() {
+            Type::Table(table) => table.borrow().metatable.clone(),
+            _ => None,
+        }
+    }
+
+    /// Get metamethod from the value metatable
+    pub fn metamethod(&self, name: &str) -> Option<Type> {
+        match self.unref() {
+            Type::Table(table) => table.borrow().metamethod(name),
+            _ => None,
+        }
+    }
+}
+
+/// Convert float to integer if it has an exact integer representation
+pub fn float_to_integer(number: f64) -> Option<i64> {
+    // 2^63 is the first float out of integer range. Float can't represent `i64::MAX`
+    if number.fract() == 0f64 && (-9_223_372_036_854_775_808f64..9_223_372_036_854_775_808f64).contains(&number) {
+        Some(number as i64)
+    } else {
+        None
+    }
+}
+
+/// Compare numbers of any subtype exactly. Large integers can't be converted to floats without
+/// loosing precision, so mixed comparison converts float to integer instead. None if float is NaN
+pub fn compare_numbers(left: &Type, right: &Type) -> Option<Ordering> {
+    fn integer_to_float(integer: i64, number: f64) -> Option<Ordering> {
+        if number.is_nan() {
+            None
+        } else if number >= 9_223_372_036_854_775_808f64 {
+            Some(Ordering::Less)
+        } else if number < -9_223_372_036_854_775_808f64 {
+            Some(Ordering::Greater)
+        } else {
+            // Integer part decides first, then fraction
+            let floor = number.floor();
+            Some(integer.cmp(&(floor as i64)).then(if number > floor {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }))
+        }
+    }
+
+    match (left.unref(), right.unref()) {
+        (Type::Integer(left), Type::Integer(right)) => Some(left.cmp(right)),
+        (Type::Number(left), Type::Number(right)) => left.partial_cmp(right),
+        (Type::Integer(left), Type::Number(right)) => integer_to_float(*left, *right),
+        (Type::Number(left), Type::Integer(right)) => {
+            integer_to_float(*right, *left).map(Ordering::reverse)
+        }
+        _ => None,
+    }
+}
+
+/// Format float the same way reference implementation does (`%.14g`). Floats, which look like
+/// integers, get `.0` suffix, so they may be distinguished from integers
+pub fn format_number(number: f64) -> String {
+    let (spec, _) = format::Spec::parse(b".14g").unwrap();
+    let mut result = Vec::new();
+    spec.format_float(number, &mut result);
+
+    let mut result = String::from_utf8(result).unwrap();
+    if result.chars().all(|chr| chr == '-' || chr.is_ascii_digit()) {
+        result.push_str(".0");
+    }
+
+    result
+}
+
+/// Convert string to a number according to Lua rules. Leading and trailing whitespaces are allowed.
+/// Integer numerals give integers
+pub fn parse_number(string: &str) -> Option<Type> {
+    let string = string.trim();
+    let (negative, unsigned) = match string.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, string.strip_prefix('+').unwrap_or(string)),
+    };
+
+    Some(match numerals::parse(unsigned)? {
+        numerals::Numeral::Integer(integer) if negative => Type::Integer(integer.wrapping_neg()),
+        numerals::Numeral::Integer(integer) => Type::Integer(integer),
+        numerals::Numeral::Float(number) if negative => Type::Number(-number),
+        numerals::Numeral::Float(number) => Type::Number(number),
+    })
 }
 
 #[cfg(test)]
@@ -74,14 +230,24 @@ impl ::std::cmp::PartialEq<&'static str> for Type {
 impl ::std::cmp::PartialEq for Type {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Type::Nil, Type::Nil) => true,
             (Type::Boolean(left), Type::Boolean(right)) => left == right,
             (Type::Number(left), Type::Number(right)) => left == right,
+            (Type::Integer(left), Type::Integer(right)) => left == right,
+            (Type::Integer(_), Type::Number(_)) | (Type::Number(_), Type::Integer(_)) => {
+                compare_numbers(self, other) == Some(Ordering::Equal)
+            }
             (Type::String(left), Type::String(right)) => left == right,
             (Type::Reference(left), right) => right.eq(left.borrow().deref()),
             (left, Type::Reference(right)) => left.eq(right.borrow().deref()),
             (Type::Vector(left), Type::Vector(right)) => left == right,
-            (Type::Table { id: left, .. }, Type::Table { id: right, .. }) => left == right,
+            // Table may be mutably borrowed, when it is used as a key in itself
+            (Type::Table(left), Type::Table(right)) => left.ptr_eq(right),
             (Type::Function { id: left, .. }, Type::Function { id: right, .. }) => left == right,
+            (
+                Type::NativeFunction { id: left, .. },
+                Type::NativeFunction { id: right, .. },
+            ) => left == right,
             _ => false,
         }
     }
@@ -89,17 +255,24 @@ impl ::std::cmp::PartialEq for Type {
 
 impl ::std::cmp::Eq for Type {}
 
+/// Hash must agree with equality: floats with integer values hash as integers,
+/// so `-0.0`, `0.0` and `0` have the same hash. NaN is never equal to itself and can't be a table key
 impl ::std::hash::Hash for Type {
     fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
         match self {
             Type::Nil => 1.hash(state),
             Type::Boolean(value) => value.hash(state),
-            Type::Number(value) => value.to_string().hash(state),
+            Type::Number(value) => match float_to_integer(*value) {
+                Some(integer) => integer.hash(state),
+                None => value.to_bits().hash(state),
+            },
+            Type::Integer(value) => value.hash(state),
             Type::String(value) => value.hash(state),
             Type::Reference(value) => value.borrow().hash(state),
             Type::Vector(vec) => vec.hash(state),
-            Type::Table { id, .. } => id.hash(state),
+            Type::Table(table) => table.address().hash(state),
             Type::Function { id, .. } => id.hash(state),
+            Type::NativeFunction { id, .. } => id.hash(state),
         }
     }
 }
@@ -109,7 +282,8 @@ impl ::std::fmt::Display for Type {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         match self {
             Type::Function { id, .. } => write!(f, "function ({:x})", id),
-            Type::Table { id, .. } => write!(f, "table ({:x})", id),
+            Type::NativeFunction { id, name, .. } => write!(f, "function {} ({:x})", name, id),
+            Type::Table(table) => write!(f, "table ({:x})", table.borrow().id),
             Type::Reference(value) => value.borrow().fmt(f),
             _ => write!(f, "{:?}", self),
         }
@@ -123,19 +297,11 @@ impl ::std::fmt::Debug for Type {
             Type::Nil => write!(f, "Nil"),
             Type::Boolean(value) => write!(f, "Boolean({:?})", value),
             Type::Number(value) => write!(f, "Number({:?})", value),
+            Type::Integer(value) => write!(f, "Integer({:?})", value),
             Type::String(value) => write!(f, "String({:?})", value),
             Type::Reference(value) => write!(f, "Reference({:?})", value),
             Type::Vector(vec) => write!(f, "Vector({:?})", vec),
-            Type::Table {
-                id,
-                map,
-                metatable,
-                border,
-            } => write!(
-                f,
-                "Table {{ id: {}, map: {:?}, metatable: {:?}, border: {} }}",
-                id, map, metatable, border
-            ),
+            Type::Table(table) => table.borrow().fmt(f),
             Type::Function {
                 id,
                 parameters,
@@ -151,6 +317,9 @@ impl ::std::fmt::Debug for Type {
                 body,
                 env.borrow().id()
             ),
+            Type::NativeFunction { id, name, .. } => {
+                write!(f, "NativeFunction {{ id: {:?}, name: {:?} }}", id, name)
+            }
         }
     }
 }
@@ -159,14 +328,14 @@ impl ::std::fmt::Debug for Type {
 #[macro_export]
 macro_rules! match_type {
     (($($typ:expr),+), $($pat:pat => $result:expr),+) => {{
-        let typs = ($(if let $crate::interpreter::types::Type::Reference(value) = $typ { unsafe { &*value.as_ptr() } } else { $typ }),+);
+        let typs = ($($crate::interpreter::types::Type::unref($typ)),+);
 
         match typs {$(
             $pat => $result
         ), +}}
     };
     ($typ:expr, $($pat:pat => $result:expr),+) => {{
-        let typ = if let $crate::interpreter::types::Type::Reference(value) = $typ { unsafe { &*value.as_ptr() } } else { $typ };
+        let typ = $crate::interpreter::types::Type::unref($typ);
 
         #[allow(clippy::single_match)]
         match typ {$(
@@ -193,8 +362,8 @@ impl ::std::convert::AsRef<f64> for Type {
     }
 }
 
-impl ::std::convert::AsRef<String> for Type {
-    fn as_ref(&self) -> &String {
+impl ::std::convert::AsRef<utils::ByteString> for Type {
+    fn as_ref(&self) -> &utils::ByteString {
         match_type!(&self,
             Type::String(val) => val,
             _ => panic!("Cannot convert lua value {} to a string", self)
@@ -210,12 +379,3 @@ impl ::std::convert::AsRef<VecDeque<Type>> for Type {
         )
     }
 }
-
-impl ::std::convert::AsRef<HashMap<Type, Rc<RefCell<Type>>>> for Type {
-    fn as_ref(&self) -> &HashMap<Type, Rc<RefCell<Type>>> {
-        match_type!(&self,
-            Type::Table { map, .. } => map,
-            _ => panic!("Cannot convert lua value {} to a hashmap", self)
-        )
-    }
-}
\ No newline at end of file
@@ -1,6 +1,8 @@
 // #![feature(trace_macros)]
 // Because I like `new` functions, but they push element on stack, not return it
 #![allow(clippy::new_ret_no_self)]
+// Lua values are used as table keys, while references inside them are interior mutable
+#![allow(clippy::mutable_key_type)]
 
 pub mod utils;
 #[macro_use]
@@ -8,46 +10,22 @@ pub mod ast;
 #[macro_use]
 pub mod interpreter;
 pub mod error;
+pub mod cli;
+pub mod repl;
 
 #[cfg(test)]
 mod test;
 
-use std::time::Instant;
+/// Stack size of the interpreter thread. Lua calls recurse on the Rust stack, so it must fit
+/// `interpreter::environment::MAX_CALL_DEPTH` nested calls even in debug builds
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
-// To avoid warnings in tests
-#[allow(dead_code, unused_variables)]
 fn main() {
-    let start = Instant::now();
+    let args: Vec<String> = std::env::args().collect();
+    let interpreter = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || cli::run(&args))
+        .expect("Failed to start interpreter thread");
 
-    // let ast = ast::AST::new(
-    //     "
-    //   function a.b:fib(n)
-    //     N=N+1
-    //     if n<2 then
-    //       return n
-    //     else
-    //       return a.b.fib(n-1) + a.b.fib(n-2)
-    //     end
-    //   end".to_owned(),
-    // );
-
-    let ast = ast::AST::new("y = 3 for i = 0, 10000000 do y = y + i end".to_string());
-
-    print!("AST {:?}", ast);
-
-    let mut elapsed = Instant::now() - start;
-    println!(
-        "Parsed input in {}.{:09} seconds",
-        elapsed.as_secs(),
-        elapsed.subsec_nanos()
-    );
-
-    ast.eval();
-
-    elapsed = Instant::now() - start;
-    println!(
-        "Evaluated in {}.{:09} seconds",
-        elapsed.as_secs(),
-        elapsed.subsec_nanos()
-    );
+    std::process::exit(interpreter.join().unwrap_or(1))
 }
@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::ast;
+use crate::cli;
+use crate::error;
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+pub const PROMPT: &str = "> ";
+pub const CONTINUATION_PROMPT: &str = ">> ";
+
+/// Maximum number of saved history entries
+const HISTORY_SIZE: usize = 1000;
+
+/// Input history, saved to a file. Each line is saved as soon as it is entered, so the history
+/// survives interpreter crashes
+pub struct History {
+    path: Option<PathBuf>,
+    entries: VecDeque<String>,
+}
+
+impl History {
+    /// History from `MAUL_HISTORY` file or `~/.maul_history` by default
+    pub fn load_default() -> Self {
+        let path = env::var_os("MAUL_HISTORY")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".maul_history")));
+
+        match path {
+            Some(path) => History::load(path),
+            None => History::default(),
+        }
+    }
+
+    /// Load history from the file. Missing file means empty history
+    pub fn load(path: PathBuf) -> Self {
+        let mut entries: VecDeque<String> = fs::read_to_string(&path)
+            .map(|content| content.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        // Keep the file from growing forever
+        if entries.len() > HISTORY_SIZE {
+            entries.drain(..entries.len() - HISTORY_SIZE);
+            let content: String = entries.iter().map(|entry| format!("{}\n", entry)).collect();
+            let _ = fs::write(&path, content);
+        }
+
+        History {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    pub fn entries(&self) -> &VecDeque<String> {
+        &self.entries
+    }
+
+    /// Add line to the history and append it to the history file. Failure to write the history
+    /// must not interrupt the session, so write errors are ignored
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+
+        self.entries.push_back(line.to_string());
+        if self.entries.len() > HISTORY_SIZE {
+            self.entries.pop_front();
+        }
+
+        if let Some(ref path) = self.path {
+            let file = fs::OpenOptions::new().create(true).append(true).open(path);
+            if let Ok(mut file) = file {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+}
+
+/// History, which is not saved
+impl Default for History {
+    fn default() -> Self {
+        History {
+            path: None,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+/// Read-eval-print loop. All lines are executed in the same root environment,
+/// so globals survive errors
+pub struct Repl {
+    env: utils::Shared<environment::Environment>,
+    /// Lines of incomplete chunk
+    buffer: String,
+}
+
+impl Repl {
+    pub fn new(env: utils::Shared<environment::Environment>) -> Self {
+        Repl {
+            env,
+            buffer: String::new(),
+        }
+    }
+
+    pub fn env(&self) -> &utils::Shared<environment::Environment> {
+        &self.env
+    }
+
+    /// Prompt for the next line
+    pub fn prompt(&self) -> &'static str {
+        if self.buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        }
+    }
+
+    /// Parse the line, joined with previous incomplete lines. Bare expressions are evaluated
+    /// as `return exp`, so their values may be echoed.
+    /// Returns None if the chunk is incomplete and needs continuation lines
+    pub fn parse_line(&mut self, line: &str) -> Option<error::Result<ast::AST>> {
+        if !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(line);
+
+        if let Ok(ast) = ast::AST::with_name(format!("return {}", self.buffer), cli::STDIN_CHUNK_NAME) {
+            self.buffer.clear();
+            return Some(Ok(ast));
+        }
+
+        match ast::AST::with_name(self.buffer.clone(), cli::STDIN_CHUNK_NAME) {
+            Err(ref error) if error.is_incomplete() => None,
+            result => {
+                self.buffer.clear();
+                Some(result)
+            }
+        }
+    }
+
+    /// Evaluate the line. Returns None if the chunk is incomplete, or values of the chunk
+    pub fn eval_line(&mut self, line: &str) -> Option<error::Result<VecDeque<types::Type>>> {
+        self.parse_line(line)
+            .map(|ast| ast.and_then(|ast| ast.exec(&mut self.env, VecDeque::new())))
+    }
+
+    /// Print values with global `print` function, as the reference interpreter does
+    pub fn echo(&mut self, values: VecDeque<types::Type>) -> error::Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let print = self
+            .env
+            .borrow_mut()
+            .get("print")
+            .map(|print| print.borrow().clone())
+            .unwrap_or(types::Type::Nil);
+        native::call(print, values, &mut self.env)?;
+
+        Ok(())
+    }
+
+    /// Run the loop until the end of input. Errors are reported with the program name
+    pub fn run(&mut self, input: &mut dyn BufRead, history: &mut History, program: &str) {
+        loop {
+            print!("{}", self.prompt());
+            let _ = io::stdout().flush();
+
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                Ok(0) => {
+                    println!();
+                    return;
+                }
+                Ok(_) => (),
+                Err(error) => {
+                    eprintln!("{}: {}", program, error);
+                    return;
+                }
+            }
+
+            let line = line.trim_end_matches(['\n', '\r']);
+            history.add(line);
+
+            let result = match self.eval_line(line) {
+                Some(result) => result.and_then(|values| self.echo(values)),
+                None => continue,
+            };
+
+            if let Err(error) = result {
+                cli::print_error(program, &error);
+            }
+        }
+    }
+}
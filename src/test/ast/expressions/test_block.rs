@@ -4,49 +4,49 @@ use crate::ast::rules;
 #[test]
 fn test_do_block() {
     assert_eq!(parse_string("do one = one + 8 end", rules::stat),
-        r#"[Single(DoBlock(Block { statements: [Assignment { varlist: [Id("one")], explist: [Binop(PLUS, Id("one"), Number(8.0))] }], retstat: None }))]"#);
+        r#"[Single(DoBlock(Block { statements: [Assignment { varlist: [Id("one")], explist: [Binop(PLUS, Id("one"), Integer(8))] }], retstat: None }))]"#);
 }
 
 #[test]
 fn test_while_block() {
     assert_eq!(parse_string("while true do one = one * 8; return 10 end", rules::stat),
-        r#"[Single(WhileBlock { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("one")], explist: [Binop(MUL, Id("one"), Number(8.0))] }, Terminal(SEMICOLONS)], retstat: Some(Return(Some(Expressions([Number(10.0)])))) } })]"#);
+        r#"[Single(WhileBlock { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("one")], explist: [Binop(MUL, Id("one"), Integer(8))] }, Terminal(SEMICOLONS)], retstat: Some(Return(Some(Expressions([Integer(10)])))) } })]"#);
 }
 
 #[test]
 fn test_repeat_block() {
     assert_eq!(parse_string("repeat one = 42; break until false", rules::stat),
-        r#"[Single(RepeatBlock { block: Block { statements: [Assignment { varlist: [Id("one")], explist: [Number(42.0)] }, Terminal(SEMICOLONS), Break], retstat: None }, condition: Boolean(false) })]"#);
+        r#"[Single(RepeatBlock { block: Block { statements: [Assignment { varlist: [Id("one")], explist: [Integer(42)] }, Terminal(SEMICOLONS), Break], retstat: None }, condition: Boolean(false) })]"#);
 }
 
 #[test]
 fn test_simple_if_block() {
     assert_eq!(parse_string("if true then x =7 end", rules::stat),
-        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(7.0)] }], retstat: None } }], else_block: None })]"#);
+        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(7)] }], retstat: None } }], else_block: None })]"#);
 }
 
 #[test]
 fn test_if_elseif_block() {
     assert_eq!(parse_string("if true then x =7 elseif false then x= 8 end", rules::stat),
-        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(7.0)] }], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(8.0)] }], retstat: None } }], else_block: None })]"#);
+        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(7)] }], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(8)] }], retstat: None } }], else_block: None })]"#);
 }
 
 #[test]
 fn test_if_else_block() {
     assert_eq!(parse_string("if true then x =7 else x= 8 end", rules::stat),
-        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(7.0)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(8.0)] }], retstat: None }) })]"#);
+        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(7)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(8)] }], retstat: None }) })]"#);
 }
 
 #[test]
 fn test_if_elseif_else_block() {
     assert_eq!(parse_string("if true then x =7 elseif false then x= 8 else x = 1 end", rules::stat),
-        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(7.0)] }], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(8.0)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(1.0)] }], retstat: None }) })]"#);
+        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(7)] }], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(8)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(1)] }], retstat: None }) })]"#);
 }
 
 #[test]
 fn test_empty_if_blocks() {
     assert_eq!(parse_string("if true then elseif false then x= 8 else x = 1 end", rules::stat),
-        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(8.0)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Number(1.0)] }], retstat: None }) })]"#);
+        r#"[Single(IfBlock { conditions: [IfCondition { condition: Boolean(true), block: Block { statements: [], retstat: None } }, IfCondition { condition: Boolean(false), block: Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(8)] }], retstat: None } }], else_block: Some(Block { statements: [Assignment { varlist: [Id("x")], explist: [Integer(1)] }], retstat: None }) })]"#);
 }
 
 #[test]
@@ -61,9 +61,9 @@ fn test_invalid_if() {
 #[test]
 fn test_numerical_for() {
     assert_eq!(parse_string("for x = 7, x == 6 do break end", rules::stat),
-        r#"[Single(NumericalForBlock { var_name: String("x"), init_value: Number(7.0), limit: Binop(EQ, Id("x"), Number(6.0)), step: None, block: Block { statements: [Break], retstat: None } })]"#);
+        r#"[Single(NumericalForBlock { var_name: String("x"), init_value: Integer(7), limit: Binop(EQ, Id("x"), Integer(6)), step: None, block: Block { statements: [Break], retstat: None } })]"#);
     assert_eq!(parse_string("for x = 7, x == 6, -1 do break end", rules::stat),
-        r#"[Single(NumericalForBlock { var_name: String("x"), init_value: Number(7.0), limit: Binop(EQ, Id("x"), Number(6.0)), step: Some(Unop(MINUS, Number(1.0))), block: Block { statements: [Break], retstat: None } })]"#);
+        r#"[Single(NumericalForBlock { var_name: String("x"), init_value: Integer(7), limit: Binop(EQ, Id("x"), Integer(6)), step: Some(Unop(MINUS, Integer(1))), block: Block { statements: [Break], retstat: None } })]"#);
 }
 
 #[test]
@@ -73,7 +73,7 @@ fn test_generic_for() {
     assert_eq!(parse_string("for x, y in xlist do break end", rules::stat),
         r#"[Single(GenericForBlock { namelist: [String("x"), String("y")], explist: [Id("xlist")], block: Block { statements: [Break], retstat: None } })]"#);
     assert_eq!(parse_string("for x in xlist, 0, 1 do break end", rules::stat),
-        r#"[Single(GenericForBlock { namelist: [String("x")], explist: [Id("xlist"), Number(0.0), Number(1.0)], block: Block { statements: [Break], retstat: None } })]"#);
+        r#"[Single(GenericForBlock { namelist: [String("x")], explist: [Id("xlist"), Integer(0), Integer(1)], block: Block { statements: [Break], retstat: None } })]"#);
     assert_eq!(parse_string("for x,y in xlist, 0, 1 do break end", rules::stat),
-        r#"[Single(GenericForBlock { namelist: [String("x"), String("y")], explist: [Id("xlist"), Number(0.0), Number(1.0)], block: Block { statements: [Break], retstat: None } })]"#);
+        r#"[Single(GenericForBlock { namelist: [String("x"), String("y")], explist: [Id("xlist"), Integer(0), Integer(1)], block: Block { statements: [Break], retstat: None } })]"#);
 }
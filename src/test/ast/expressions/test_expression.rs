@@ -25,7 +25,7 @@ fn test_explist() {
     );
     assert_eq!(
         parse_string("nil, false, 42", rules::explist),
-        "[Repetition([Nil, Boolean(false), Number(42.0)])]"
+        "[Repetition([Nil, Boolean(false), Integer(42)])]"
     );
 }
 
@@ -33,11 +33,11 @@ fn test_explist() {
 fn test_exp_unop() {
     assert_eq!(
         parse_string("-3", rules::exp),
-        "[Single(Unop(MINUS, Number(3.0)))]"
+        "[Single(Unop(MINUS, Integer(3)))]"
     );
     assert_eq!(
         parse_string("#7", rules::exp),
-        "[Single(Unop(HASH, Number(7.0)))]"
+        "[Single(Unop(HASH, Integer(7)))]"
     );
     assert_eq!(
         parse_string("~false", rules::exp),
@@ -49,15 +49,15 @@ fn test_exp_unop() {
 fn test_exp_binop() {
     assert_eq!(
         parse_string("1 - 3", rules::exp),
-        "[Single(Binop(MINUS, Number(1.0), Number(3.0)))]"
+        "[Single(Binop(MINUS, Integer(1), Integer(3)))]"
     );
     assert_eq!(
         parse_string("1 - 3 + 4", rules::exp),
-        "[Single(Binop(PLUS, Binop(MINUS, Number(1.0), Number(3.0)), Number(4.0)))]"
+        "[Single(Binop(PLUS, Binop(MINUS, Integer(1), Integer(3)), Integer(4)))]"
     );
     assert_eq!(
         parse_string("-1 - -3", rules::exp),
-        "[Single(Binop(MINUS, Unop(MINUS, Number(1.0)), Unop(MINUS, Number(3.0))))]"
+        "[Single(Binop(MINUS, Unop(MINUS, Integer(1)), Unop(MINUS, Integer(3))))]"
     );
 }
 
@@ -90,3 +90,12 @@ fn test_exp_functiondef() {
         ))
     )
 }*/
+
+#[test]
+fn test_parentheses() {
+    assert_eq!(
+        parse_string("(f())", rules::exp),
+        r#"[Single(Parentheses(Funcall { object: Id("f"), args: [], method: None }))]"#
+    );
+    assert_eq!(parse_string("(Key)", rules::exp), r#"[Single(Id("Key"))]"#);
+}
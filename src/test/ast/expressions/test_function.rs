@@ -69,7 +69,7 @@ fn test_invalid_functions2() {
 #[test]
 fn test_functioncall() {
     assert_eq!(parse_string("func(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Id("func"), args: [Number(1.0), Number(5.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Id("func"), args: [Integer(1), Integer(5)], method: None })]"#);
 
     assert_eq!(
         parse_string("func()", rules::functioncall),
@@ -80,7 +80,7 @@ fn test_functioncall() {
         r#"[Single(Funcall { object: Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [], method: None }, args: [], method: None })]"#);
 
     assert_eq!(parse_string("obj:method(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Id("obj"), args: [Number(1.0), Number(5.0)], method: Some(String("method")) })]"#);
+        r#"[Single(Funcall { object: Id("obj"), args: [Integer(1), Integer(5)], method: Some(String("method")) })]"#);
 
     assert_eq!(
         parse_string("obj:method()", rules::functioncall),
@@ -88,13 +88,13 @@ fn test_functioncall() {
     );
 
     assert_eq!(parse_string("obj.func(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [Number(1.0), Number(5.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [Integer(1), Integer(5)], method: None })]"#);
 
     assert_eq!(parse_string("obj.func()", rules::functioncall),
         r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [], method: None })]"#);
 
     assert_eq!(parse_string(r#"obj["func"](1, 5)"#, rules::functioncall),
-        r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [Number(1.0), Number(5.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [Integer(1), Integer(5)], method: None })]"#);
 
     assert_eq!(parse_string(r#"obj["func"]()"#, rules::functioncall),
         r#"[Single(Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [], method: None })]"#);
@@ -117,10 +117,34 @@ fn test_invalid_funccall2() {
     parse_string("a(1).b", rules::functioncall);
 }
 
+#[test]
+fn test_functioncall_statement() {
+    assert_eq!(parse_string("func(1)", rules::stat),
+        r#"[Single(Funcall { object: Id("func"), args: [Integer(1)], method: None })]"#);
+
+    assert_eq!(parse_string("obj.func():method()", rules::stat),
+        r#"[Single(Funcall { object: Funcall { object: Indexing { object: Id("obj"), index: String("func") }, args: [], method: None }, args: [], method: Some(String("method")) })]"#);
+
+    assert_eq!(parse_string("func().x, y = 1", rules::stat),
+        r#"[Single(Assignment { varlist: [Indexing { object: Funcall { object: Id("func"), args: [], method: None }, index: String("x") }, Id("y")], explist: [Integer(1)] })]"#);
+}
+
+#[test]
+#[should_panic(expected = "syntax error near <eof>")]
+fn test_invalid_functioncall_statement() {
+    parse_string("obj.field", rules::stat);
+}
+
+#[test]
+#[should_panic(expected = "Cannot assign to Funcall")]
+fn test_invalid_assignment_statement() {
+    parse_string("x, func() = 1, 2", rules::stat);
+}
+
 #[test]
 fn test_functioncall_rec_prefixexp() {
     assert_eq!(parse_string("(true)(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Boolean(true), args: [Number(1.0), Number(5.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Boolean(true), args: [Integer(1), Integer(5)], method: None })]"#);
 
     assert_eq!(
         parse_string("(true)()", rules::functioncall),
@@ -128,34 +152,34 @@ fn test_functioncall_rec_prefixexp() {
     );
 
     assert_eq!(parse_string("(true).func(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Indexing { object: Boolean(true), index: String("func") }, args: [Number(1.0), Number(5.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Indexing { object: Boolean(true), index: String("func") }, args: [Integer(1), Integer(5)], method: None })]"#);
 
     assert_eq!(parse_string(r#"(true)["func"]()"#, rules::functioncall),
         r#"[Single(Funcall { object: Indexing { object: Boolean(true), index: String("func") }, args: [], method: None })]"#);
 
     assert_eq!(parse_string("(true):method(1, 5)", rules::functioncall),
-        r#"[Single(Funcall { object: Boolean(true), args: [Number(1.0), Number(5.0)], method: Some(String("method")) })]"#);
+        r#"[Single(Funcall { object: Boolean(true), args: [Integer(1), Integer(5)], method: Some(String("method")) })]"#);
 }
 
 #[test]
 fn test_functioncall_rec_args() {
     assert_eq!(parse_string("func(1, 5)(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Funcall { object: Id("func"), args: [Number(1.0), Number(5.0)], method: None }, args: [Number(3.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Funcall { object: Id("func"), args: [Integer(1), Integer(5)], method: None }, args: [Integer(3)], method: None })]"#);
 
     assert_eq!(parse_string("func()(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Funcall { object: Id("func"), args: [], method: None }, args: [Number(3.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Funcall { object: Id("func"), args: [], method: None }, args: [Integer(3)], method: None })]"#);
 
     assert_eq!(parse_string("obj:method(1, 5)(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [Number(1.0), Number(5.0)], method: Some(String("method")) }, args: [Number(3.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [Integer(1), Integer(5)], method: Some(String("method")) }, args: [Integer(3)], method: None })]"#);
 
     assert_eq!(parse_string("obj:method()(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [], method: Some(String("method")) }, args: [Number(3.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [], method: Some(String("method")) }, args: [Integer(3)], method: None })]"#);
 
     assert_eq!(parse_string("obj.func1(1, 5).func2(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Indexing { object: Funcall { object: Indexing { object: Id("obj"), index: String("func1") }, args: [Number(1.0), Number(5.0)], method: None }, index: String("func2") }, args: [Number(3.0)], method: None })]"#);
+        r#"[Single(Funcall { object: Indexing { object: Funcall { object: Indexing { object: Id("obj"), index: String("func1") }, args: [Integer(1), Integer(5)], method: None }, index: String("func2") }, args: [Integer(3)], method: None })]"#);
 
     assert_eq!(parse_string("obj:method1():method2(3)", rules::functioncall),
-        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [], method: Some(String("method1")) }, args: [Number(3.0)], method: Some(String("method2")) })]"#);
+        r#"[Single(Funcall { object: Funcall { object: Id("obj"), args: [], method: Some(String("method1")) }, args: [Integer(3)], method: Some(String("method2")) })]"#);
 }
 
 #[test]
@@ -169,7 +193,7 @@ fn test_closure() {
     assert_eq!(parse_string("function (b, c, ...) break; end", rules::functiondef),
         r#"[Single(Closure { params: [String("b"), String("c")], varargs: true, body: Block { statements: [Break, Terminal(SEMICOLONS)], retstat: None } })]"#);
     assert_eq!(parse_string("function (t, a, b, c) return 7; end", rules::functiondef),
-        r#"[Single(Closure { params: [String("t"), String("a"), String("b"), String("c")], varargs: false, body: Block { statements: [], retstat: Some(Return(Some(Expressions([Number(7.0)])))) } })]"#);
+        r#"[Single(Closure { params: [String("t"), String("a"), String("b"), String("c")], varargs: false, body: Block { statements: [], retstat: Some(Return(Some(Expressions([Integer(7)])))) } })]"#);
 }
 
 #[test]
@@ -190,7 +214,7 @@ fn test_functiondef() {
 #[test]
 fn test_local_function() {
     assert_eq!(parse_string("local function f () break end", rules::stat),
-        r#"[Single(Local(Assignment { varlist: [Id("f")], explist: [Closure { params: [], varargs: false, body: Block { statements: [Break], retstat: None } }] }))]"#);
+        r#"[Single(LocalFunction { name: "f", closure: Closure { params: [], varargs: false, body: Block { statements: [Break], retstat: None } } })]"#);
 }
 
 #[test]
@@ -212,5 +236,5 @@ fn test_fib() {
          return a.b.fib(n-1) + a.b.fib(n-2) \
        end \
      end", rules::chunk),
-       r#"[Single(Block { statements: [Assignment { varlist: [Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }], explist: [Closure { params: [String("self"), String("n")], varargs: false, body: Block { statements: [Assignment { varlist: [Id("N")], explist: [Binop(PLUS, Id("N"), Number(1.0))] }, IfBlock { conditions: [IfCondition { condition: Binop(LESS, Id("n"), Number(2.0)), block: Block { statements: [], retstat: Some(Return(Some(Expressions([Id("n")])))) } }], else_block: Some(Block { statements: [], retstat: Some(Return(Some(Expressions([Binop(PLUS, Funcall { object: Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }, args: [Binop(MINUS, Id("n"), Number(1.0))], method: None }, Funcall { object: Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }, args: [Binop(MINUS, Id("n"), Number(2.0))], method: None })])))) }) }], retstat: None } }] }], retstat: None })]"#);
+       r#"[Single(Block { statements: [Assignment { varlist: [Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }], explist: [Closure { params: [String("self"), String("n")], varargs: false, body: Block { statements: [Assignment { varlist: [Id("N")], explist: [Binop(PLUS, Id("N"), Integer(1))] }, IfBlock { conditions: [IfCondition { condition: Binop(LESS, Id("n"), Integer(2)), block: Block { statements: [], retstat: Some(Return(Some(Expressions([Id("n")])))) } }], else_block: Some(Block { statements: [], retstat: Some(Return(Some(Expressions([Binop(PLUS, Funcall { object: Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }, args: [Binop(MINUS, Id("n"), Integer(1))], method: None }, Funcall { object: Indexing { object: Indexing { object: Id("a"), index: String("b") }, index: String("fib") }, args: [Binop(MINUS, Id("n"), Integer(2))], method: None })])))) }) }], retstat: None } }] }], retstat: None })]"#);
 }
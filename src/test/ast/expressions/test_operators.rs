@@ -17,11 +17,11 @@ use crate::ast::rules;
 fn test_operator_simple() {
     assert_eq!(
         parse_string("1 ^ 5", rules::exp),
-        "[Single(Binop(POW, Number(1.0), Number(5.0)))]"
+        "[Single(Binop(POW, Integer(1), Integer(5)))]"
     );
     assert_eq!(
         parse_string("1 * 5", rules::exp),
-        "[Single(Binop(MUL, Number(1.0), Number(5.0)))]"
+        "[Single(Binop(MUL, Integer(1), Integer(5)))]"
     );
     assert_eq!(
         parse_string("true or false", rules::exp),
@@ -33,11 +33,11 @@ fn test_operator_simple() {
 fn test_operator_rep() {
     assert_eq!(
         parse_string("1 ^ 5 ^ 3", rules::exp),
-        "[Single(Binop(POW, Binop(POW, Number(1.0), Number(5.0)), Number(3.0)))]"
+        "[Single(Binop(POW, Binop(POW, Integer(1), Integer(5)), Integer(3)))]"
     );
     assert_eq!(
         parse_string("1 * 5 / 2", rules::exp),
-        "[Single(Binop(DIV, Binop(MUL, Number(1.0), Number(5.0)), Number(2.0)))]"
+        "[Single(Binop(DIV, Binop(MUL, Integer(1), Integer(5)), Integer(2)))]"
     );
     assert_eq!(
         parse_string("true or false or true", rules::exp),
@@ -49,17 +49,17 @@ fn test_operator_rep() {
 fn test_operator_precedence() {
     assert_eq!(
         parse_string("1 ^ 5 * 3", rules::exp),
-        "[Single(Binop(MUL, Binop(POW, Number(1.0), Number(5.0)), Number(3.0)))]"
+        "[Single(Binop(MUL, Binop(POW, Integer(1), Integer(5)), Integer(3)))]"
     );
     assert_eq!(
         parse_string("1 * 5 ^ 3", rules::exp),
-        "[Single(Binop(MUL, Number(1.0), Binop(POW, Number(5.0), Number(3.0))))]"
+        "[Single(Binop(MUL, Integer(1), Binop(POW, Integer(5), Integer(3))))]"
     );
-    assert_eq!(parse_string("1 * 5 + 3 * 9", rules::exp), "[Single(Binop(PLUS, Binop(MUL, Number(1.0), Number(5.0)), Binop(MUL, Number(3.0), Number(9.0))))]");
-    assert_eq!(parse_string("1 - 5 * 3 - 9", rules::exp), "[Single(Binop(MINUS, Binop(MINUS, Number(1.0), Binop(MUL, Number(5.0), Number(3.0))), Number(9.0)))]");
-    assert_eq!(parse_string("1 + 5 * 3 * 9", rules::exp), "[Single(Binop(PLUS, Number(1.0), Binop(MUL, Binop(MUL, Number(5.0), Number(3.0)), Number(9.0))))]");
-    assert_eq!(parse_string("1 * 5 * 3 - 9", rules::exp), "[Single(Binop(MINUS, Binop(MUL, Binop(MUL, Number(1.0), Number(5.0)), Number(3.0)), Number(9.0)))]");
-    assert_eq!(parse_string("1 - 5 ^ 3 * 9", rules::exp), "[Single(Binop(MINUS, Number(1.0), Binop(MUL, Binop(POW, Number(5.0), Number(3.0)), Number(9.0))))]");
+    assert_eq!(parse_string("1 * 5 + 3 * 9", rules::exp), "[Single(Binop(PLUS, Binop(MUL, Integer(1), Integer(5)), Binop(MUL, Integer(3), Integer(9))))]");
+    assert_eq!(parse_string("1 - 5 * 3 - 9", rules::exp), "[Single(Binop(MINUS, Binop(MINUS, Integer(1), Binop(MUL, Integer(5), Integer(3))), Integer(9)))]");
+    assert_eq!(parse_string("1 + 5 * 3 * 9", rules::exp), "[Single(Binop(PLUS, Integer(1), Binop(MUL, Binop(MUL, Integer(5), Integer(3)), Integer(9))))]");
+    assert_eq!(parse_string("1 * 5 * 3 - 9", rules::exp), "[Single(Binop(MINUS, Binop(MUL, Binop(MUL, Integer(1), Integer(5)), Integer(3)), Integer(9)))]");
+    assert_eq!(parse_string("1 - 5 ^ 3 * 9", rules::exp), "[Single(Binop(MINUS, Integer(1), Binop(MUL, Binop(POW, Integer(5), Integer(3)), Integer(9))))]");
 }
 
 #[test]
@@ -12,7 +12,7 @@ fn test_simple_statement() {
 fn test_return_statement() {
     assert_eq!(
         parse_string("return nil, false, 42;", rules::retstat),
-        "[Single(Return(Some(Expressions([Nil, Boolean(false), Number(42.0)]))))]"
+        "[Single(Return(Some(Expressions([Nil, Boolean(false), Integer(42)]))))]"
     );
 
     assert_eq!(
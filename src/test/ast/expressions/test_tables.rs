@@ -13,24 +13,32 @@ fn test_table_fields() {
     );
     assert_eq!(
         parse_string("7", rules::field),
-        "[Single(TableField { key: None, value: Number(7.0) })]"
+        "[Single(TableField { key: None, value: Integer(7) })]"
+    );
+    assert_eq!(
+        parse_string("Key", rules::field),
+        r#"[Single(TableField { key: None, value: Id("Key") })]"#
+    );
+    assert_eq!(
+        parse_string("f()", rules::field),
+        r#"[Single(TableField { key: None, value: Funcall { object: Id("f"), args: [], method: None } })]"#
     );
 }
 
 #[test]
 fn test_table() {
     assert_eq!(parse_string(r#"{["Key"] = true, Key = false, 7}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
     assert_eq!(parse_string(r#"{["Key"] = true, Key = false, 7,}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
     assert_eq!(parse_string(r#"{["Key"] = true; Key = false; 7}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
     assert_eq!(parse_string(r#"{["Key"] = true; Key = false; 7;}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
     assert_eq!(parse_string(r#"{["Key"] = true; Key = false, 7}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
     assert_eq!(parse_string(r#"{["Key"] = true, Key = false; 7}"#, rules::tableconstructor),
-        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Number(7.0) }]))]"#);
+        r#"[Single(Table([TableField { key: Some(String("Key")), value: Boolean(true) }, TableField { key: Some(String("Key")), value: Boolean(false) }, TableField { key: None, value: Integer(7) }]))]"#);
 }
 
 #[test]
@@ -48,20 +48,20 @@ fn test_varlist() {
 fn test_assignment() {
     assert_eq!(
         parse_string("var = 7", rules::stat),
-        r#"[Single(Assignment { varlist: [Id("var")], explist: [Number(7.0)] })]"#
+        r#"[Single(Assignment { varlist: [Id("var")], explist: [Integer(7)] })]"#
     );
-    assert_eq!(parse_string("var1, var2 = 7, false", rules::stat), r#"[Single(Assignment { varlist: [Id("var1"), Id("var2")], explist: [Number(7.0), Boolean(false)] })]"#);
+    assert_eq!(parse_string("var1, var2 = 7, false", rules::stat), r#"[Single(Assignment { varlist: [Id("var1"), Id("var2")], explist: [Integer(7), Boolean(false)] })]"#);
     assert_eq!(parse_string(r#"var1.data, var2["key"] = 7, false"#, rules::stat),
-        r#"[Single(Assignment { varlist: [Indexing { object: Id("var1"), index: String("data") }, Indexing { object: Id("var2"), index: String("key") }], explist: [Number(7.0), Boolean(false)] })]"#);
+        r#"[Single(Assignment { varlist: [Indexing { object: Id("var1"), index: String("data") }, Indexing { object: Id("var2"), index: String("key") }], explist: [Integer(7), Boolean(false)] })]"#);
     assert_eq!(parse_string("var1, var2, var3[nil].func = -7, object:method(), 11 - 3 + 5", rules::stat),
-        r#"[Single(Assignment { varlist: [Id("var1"), Id("var2"), Indexing { object: Indexing { object: Id("var3"), index: Nil }, index: String("func") }], explist: [Unop(MINUS, Number(7.0)), Funcall { object: Id("object"), args: [], method: Some(String("method")) }, Binop(PLUS, Binop(MINUS, Number(11.0), Number(3.0)), Number(5.0))] })]"#);
+        r#"[Single(Assignment { varlist: [Id("var1"), Id("var2"), Indexing { object: Indexing { object: Id("var3"), index: Nil }, index: String("func") }], explist: [Unop(MINUS, Integer(7)), Funcall { object: Id("object"), args: [], method: Some(String("method")) }, Binop(PLUS, Binop(MINUS, Integer(11), Integer(3)), Integer(5))] })]"#);
 }
 
 #[test]
 fn test_varlist_more_vars() {
     assert_eq!(
         parse_string("var1, var2 = 7", rules::stat),
-        r#"[Single(Assignment { varlist: [Id("var1"), Id("var2")], explist: [Number(7.0)] })]"#
+        r#"[Single(Assignment { varlist: [Id("var1"), Id("var2")], explist: [Integer(7)] })]"#
     );
 }
 
@@ -69,14 +69,14 @@ fn test_varlist_more_vars() {
 fn test_varlist_more_expressions() {
     assert_eq!(
         parse_string("var1 = 7, false", rules::stat),
-        r#"[Single(Assignment { varlist: [Id("var1")], explist: [Number(7.0), Boolean(false)] })]"#
+        r#"[Single(Assignment { varlist: [Id("var1")], explist: [Integer(7), Boolean(false)] })]"#
     );
 }
 
 #[test]
 fn test_local_assignment() {
-    assert_eq!(parse_string("local var1, var2 = 7, false", rules::stat), r#"[Single(Local(Assignment { varlist: [String("var1"), String("var2")], explist: [Number(7.0), Boolean(false)] }))]"#);
-    assert_eq!(parse_string("local var1, var2 = 7", rules::stat), r#"[Single(Local(Assignment { varlist: [String("var1"), String("var2")], explist: [Number(7.0)] }))]"#);
+    assert_eq!(parse_string("local var1, var2 = 7, false", rules::stat), r#"[Single(Local(Assignment { varlist: [String("var1"), String("var2")], explist: [Integer(7), Boolean(false)] }))]"#);
+    assert_eq!(parse_string("local var1, var2 = 7", rules::stat), r#"[Single(Local(Assignment { varlist: [String("var1"), String("var2")], explist: [Integer(7)] }))]"#);
     assert_eq!(
         parse_string("local var1, var2", rules::stat),
         r#"[Single(Local(Assignment { varlist: [String("var1"), String("var2")], explist: [] }))]"#
@@ -1,3 +1,5 @@
+// Used by `exp!` and `sexp!` macros
+#[allow(unused_imports)]
 pub use crate::ast::expressions::Expression;
 use crate::ast::parser;
 use crate::ast::stack;
@@ -26,6 +28,10 @@ where
 
     func(&mut parser, &mut stack);
 
+    if let Some(error) = parser.error() {
+        panic!("{}", error);
+    }
+
     assert!(
         parser.peek().is_none(),
         "Parser contains tokens after parsing"
@@ -1,4 +1,5 @@
 mod expressions;
 mod test_lexer;
 mod test_parser;
+mod test_span;
 mod test_stack;
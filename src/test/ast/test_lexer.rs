@@ -1,5 +1,7 @@
 #![cfg(test)]
 
+use crate::ast::lexer::numerals::{self, Numeral};
+use crate::ast::lexer::Lexer;
 use crate::ast::lexer::tokens::*;
 use crate::ast::parser;
 
@@ -60,21 +62,40 @@ fn test_keywords() {
     assert_eq!(parser.next(), None);
 }
 
+#[test]
+fn test_underscore_identifiers() {
+    let mut parser = ParseWrapper::new("__index _G a_1");
+
+    assert_eq!(
+        parser.next(),
+        Some(Token::new(TokenType::Id(String::from("__index")), 1, 7))
+    );
+    assert_eq!(
+        parser.next(),
+        Some(Token::new(TokenType::Id(String::from("_G")), 1, 10))
+    );
+    assert_eq!(
+        parser.next(),
+        Some(Token::new(TokenType::Id(String::from("a_1")), 1, 14))
+    );
+    assert_eq!(parser.next(), None);
+}
+
 #[test]
 fn test_strings() {
     let mut parser = ParseWrapper::new(r#""Hello" "world""!""#);
 
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::String(String::from("Hello")), 1, 7))
+        Some(Token::new(TokenType::String("Hello".into()), 1, 7))
     );
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::String(String::from("world")), 1, 15))
+        Some(Token::new(TokenType::String("world".into()), 1, 15))
     );
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::String(String::from("!")), 1, 18))
+        Some(Token::new(TokenType::String("!".into()), 1, 18))
     );
     assert_eq!(parser.next(), None);
 }
@@ -86,7 +107,7 @@ fn test_invalid_strings() {
 
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::String(String::from("Hello")), 1, 8))
+        Some(Token::new(TokenType::String("Hello".into()), 1, 8))
     );
 }
 
@@ -96,11 +117,11 @@ fn test_numbers() {
 
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::Number(3f64), 1, 1))
+        Some(Token::new(TokenType::Integer(3), 1, 1))
     );
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::Number(43f64), 1, 4))
+        Some(Token::new(TokenType::Integer(43), 1, 4))
     );
     assert_eq!(
         parser.next(),
@@ -108,7 +129,252 @@ fn test_numbers() {
     );
     assert_eq!(
         parser.next(),
-        Some(Token::new(TokenType::Number(777f64), 1, 14))
+        Some(Token::new(TokenType::Integer(777), 1, 14))
+    );
+    assert_eq!(parser.next(), None);
+}
+
+/// Token types of the whole source
+fn token_types(source: &str) -> Vec<TokenType> {
+    let mut parser = ParseWrapper::new(source);
+    let mut result = Vec::new();
+
+    while let Some(token) = parser.next() {
+        result.push(token.token)
+    }
+
+    if let Some(error) = parser.parser.error() {
+        panic!("{}", error)
+    }
+
+    result
+}
+
+fn string(value: &str) -> TokenType {
+    TokenType::String(value.into())
+}
+
+fn id(value: &str) -> TokenType {
+    TokenType::Id(value.to_string())
+}
+
+#[test]
+fn test_single_quoted_strings() {
+    assert_eq!(
+        token_types(r#"'Hello' 'say "hi"' "it's""#),
+        vec![string("Hello"), string(r#"say "hi""#), string("it's")]
+    );
+}
+
+#[test]
+fn test_escape_sequences() {
+    assert_eq!(
+        token_types(r#""\a\b\f\n\r\t\v\\\"\'""#),
+        vec![string("\x07\x08\x0c\n\r\t\x0b\\\"'")]
+    );
+    assert_eq!(
+        token_types(r#""\65\066\0067" '\x41\x6a' "\u{48}\u{e9}\u{20AC}\u{1F600}""#),
+        vec![string("AB\x067"), string("Aj"), string("Hé€😀")]
+    );
+    // UTF-8 sequence may be written byte by byte
+    assert_eq!(token_types(r#""\xC3\xA9 \195\169""#), vec![string("é é")]);
+    assert_eq!(
+        token_types("\"line\\\nbreak\" \"skip \\z   \n\t  spaces\""),
+        vec![string("line\nbreak"), string("skip spaces")]
+    );
+}
+
+#[test]
+fn test_binary_escapes() {
+    // Escapes may produce bytes, which are not valid UTF-8. They are kept as is
+    assert_eq!(
+        token_types(r#""\xff" "\200\0" "\u{110000}" "\u{7FFFFFFF}""#),
+        vec![
+            TokenType::String(vec![0xff].into()),
+            TokenType::String(vec![200, 0].into()),
+            TokenType::String(vec![0xf4, 0x90, 0x80, 0x80].into()),
+            TokenType::String(vec![0xfd, 0xbf, 0xbf, 0xbf, 0xbf, 0xbf].into()),
+        ]
+    );
+}
+
+#[test]
+fn test_long_strings() {
+    assert_eq!(
+        token_types("[[simple]] [==[with ]] and ]=] inside]==] [[\nfirst newline skipped\r\nCRLF]]"),
+        vec![
+            string("simple"),
+            string("with ]] and ]=] inside"),
+            string("first newline skipped\nCRLF"),
+        ]
+    );
+    assert_eq!(
+        token_types("t[ [[key]] ]"),
+        vec![
+            id("t"),
+            TokenType::Keyword(Keyword::LSBRACKET),
+            string("key"),
+            TokenType::Keyword(Keyword::RSBRACKET),
+        ]
     );
+}
+
+#[test]
+fn test_comments() {
+    assert_eq!(
+        token_types("a -- line comment\nb --[[ long\ncomment ]] c --[==[ ]] ]==] d --[ not long\ne - -f"),
+        vec![
+            id("a"),
+            id("b"),
+            id("c"),
+            id("d"),
+            id("e"),
+            TokenType::Keyword(Keyword::MINUS),
+            TokenType::Keyword(Keyword::MINUS),
+            id("f"),
+        ]
+    );
+}
+
+#[test]
+fn test_whitespaces() {
+    let mut parser = ParseWrapper::new("a\r\nb\n\rc\rd\x0b\x0ce");
+
+    assert_eq!(parser.next(), Some(Token::new(id("a"), 1, 1)));
+    assert_eq!(parser.next(), Some(Token::new(id("b"), 2, 1)));
+    assert_eq!(parser.next(), Some(Token::new(id("c"), 3, 1)));
+    assert_eq!(parser.next(), Some(Token::new(id("d"), 4, 1)));
+    assert_eq!(parser.next(), Some(Token::new(id("e"), 4, 4)));
+    assert_eq!(parser.next(), None);
+}
+
+#[test]
+fn test_shebang() {
+    let mut parser = ParseWrapper::new("#!/usr/bin/env maul\nx");
+
+    assert_eq!(parser.next(), Some(Token::new(id("x"), 2, 1)));
     assert_eq!(parser.next(), None);
 }
+
+#[test]
+fn test_lexical_errors() {
+    let error = |source: &str| {
+        let mut parser = parser::Parser::new(source.to_string());
+        while parser.peek().is_some() {
+            parser.shift()
+        }
+        parser.take_error().expect("Source must be invalid").to_string()
+    };
+
+    assert_eq!(error("x = 'abc"), "Lexical error at 1:4: Unmatched single quotes");
+    assert_eq!(error("x = \"abc\ndef\""), "Lexical error at 1:8: Unfinished string");
+    assert_eq!(error(r#""\q""#), r#"Lexical error at 1:2: Invalid escape sequence '\q'"#);
+    assert_eq!(error(r#""\300""#), r#"Lexical error at 1:5: Decimal escape '\300' too large"#);
+    assert_eq!(
+        error(r#""\xZZ""#),
+        r#"Lexical error at 1:3: Hexadecimal digit expected in '\x' escape"#
+    );
+    assert_eq!(
+        error(r#""\u{80000000}""#),
+        "Lexical error at 1:12: UTF-8 value too large"
+    );
+    assert_eq!(error("[==x"), "Lexical error at 1:0: Invalid long string delimiter");
+    assert_eq!(
+        error("x = [[abc"),
+        "Lexical error at 1:4: Unmatched long string brackets"
+    );
+    assert_eq!(
+        error("--[=[ comment"),
+        "Lexical error at 1:0: Unmatched long comment brackets"
+    );
+}
+
+#[test]
+fn test_lexing_after_errors() {
+    // Invalid symbol, string or delimiter is skipped and lexing continues
+    let results: Vec<_> = Lexer::new(r#"a @ b "\q\"" c [=x d"#.to_string())
+        .map(|result| result.map(|token| token.token).map_err(|error| error.to_string()))
+        .collect();
+
+    assert_eq!(
+        results,
+        vec![
+            Ok(id("a")),
+            Err("Lexical error at 1:2: Unexpected symbol '@'".to_string()),
+            Ok(id("b")),
+            Err(r#"Lexical error at 1:8: Invalid escape sequence '\q'"#.to_string()),
+            Ok(id("c")),
+            Err("Lexical error at 1:15: Invalid long string delimiter".to_string()),
+            Ok(id("x")),
+            Ok(id("d")),
+        ]
+    );
+}
+
+#[test]
+fn test_numerals() {
+    assert_eq!(
+        token_types("0xFF 0XaB 1e10 3.5E-2 0x1p4 .5 5. 0x.8 0xA.8P1 1E+2 007"),
+        vec![
+            TokenType::Integer(255),
+            TokenType::Integer(171),
+            TokenType::Number(1e10),
+            TokenType::Number(0.035),
+            TokenType::Number(16.0),
+            TokenType::Number(0.5),
+            TokenType::Number(5.0),
+            TokenType::Number(0.5),
+            TokenType::Number(21.0),
+            TokenType::Number(100.0),
+            TokenType::Integer(7),
+        ]
+    );
+    assert_eq!(
+        token_types("a.b[1]..x"),
+        vec![
+            id("a"),
+            TokenType::Keyword(Keyword::DOT),
+            id("b"),
+            TokenType::Keyword(Keyword::LSBRACKET),
+            TokenType::Integer(1),
+            TokenType::Keyword(Keyword::RSBRACKET),
+            TokenType::Keyword(Keyword::DOT2),
+            id("x"),
+        ]
+    );
+}
+
+#[test]
+fn test_numeral_limits() {
+    assert_eq!(numerals::parse("9223372036854775807"), Some(Numeral::Integer(i64::MAX)));
+    // Decimal integer overflow gives float
+    assert_eq!(
+        numerals::parse("9223372036854775808"),
+        Some(Numeral::Float(9223372036854775808.0))
+    );
+    // Hexadecimal integers wrap around
+    assert_eq!(numerals::parse("0xffffffffffffffff"), Some(Numeral::Integer(-1)));
+    assert_eq!(numerals::parse("0x10000000000000001"), Some(Numeral::Integer(1)));
+    assert_eq!(numerals::parse("1e500"), Some(Numeral::Float(f64::INFINITY)));
+    assert_eq!(numerals::parse("0x1p-2"), Some(Numeral::Float(0.25)));
+
+    for invalid in &["", ".", "e1", "1e", "1e+", "0x", "0x.p1", "1.2.3", "inf", "nan", "1f", "0x1e+1"] {
+        assert_eq!(numerals::parse(invalid), None, "{} must be invalid", invalid);
+    }
+}
+
+#[test]
+fn test_malformed_numbers() {
+    let error = |source: &str| {
+        let mut parser = parser::Parser::new(source.to_string());
+        while parser.peek().is_some() {
+            parser.shift()
+        }
+        parser.take_error().expect("Source must be invalid").to_string()
+    };
+
+    assert_eq!(error("x = 3..2"), "Lexical error at 1:8: Malformed number '3..2'");
+    assert_eq!(error("x = 0xfg"), "Lexical error at 1:8: Malformed number '0xfg'");
+    assert_eq!(error("x = 12abc"), "Lexical error at 1:9: Malformed number '12abc'");
+    assert_eq!(error("x = 1e"), "Lexical error at 1:6: Malformed number '1e'");
+}
@@ -1,2 +1,208 @@
+use crate::ast::lexer::tokens::{Keyword, TokenType};
+use crate::ast::parser::Diagnostic;
+use crate::ast::AST;
+use crate::interpreter::types;
+
+/// Messages of all errors, found by the recovering parser
+fn diagnostics(source: &str) -> Vec<String> {
+    let (_, diagnostics) = AST::new_recovering(source.to_string());
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.error.to_string())
+        .collect()
+}
+
 #[test]
 fn test_simple() {}
+
+#[test]
+fn test_recover_valid_source() {
+    let (ast, diagnostics) = AST::new_recovering("x = 1 return x + 1".to_string());
+    assert_eq!(diagnostics, vec![]);
+
+    let result = ast
+        .exec(&mut AST::environment(), Default::default())
+        .unwrap();
+    assert_eq!(result, vec![types::Type::Integer(2)]);
+}
+
+#[test]
+fn test_recover_multiple_errors() {
+    assert_eq!(
+        diagnostics(
+            r#"local x = = 1
+               local y = 2 +
+               if x then z = ) end"#
+        ),
+        vec![
+            "Syntax error at 1:11: expression expected near '='",
+            "Syntax error at 3:17: expression expected near 'if'",
+            "Syntax error at 3:30: expression expected near ')'",
+        ]
+    );
+}
+
+#[test]
+fn test_recover_diagnostic_fields() {
+    let (_, diagnostics) = AST::new_recovering("x = 1\ny = * 2".to_string());
+    assert_eq!(diagnostics.len(), 1);
+
+    let Diagnostic {
+        expected, found, ..
+    } = &diagnostics[0];
+    assert_eq!(expected.as_deref(), Some("expression"));
+    assert_eq!(found, &Some(TokenType::Keyword(Keyword::MUL)));
+    assert_eq!(diagnostics[0].position(), (2, 5));
+}
+
+#[test]
+fn test_recover_nested_blocks() {
+    // Errors inside function bodies don't break the enclosing statements
+    assert_eq!(
+        diagnostics(
+            r#"function f()
+                 local a = (1
+               end
+               while true do
+                 for i = 1 do end
+               end"#
+        ),
+        vec![
+            "Syntax error at 3:18: ')' expected near 'end'",
+            "Syntax error at 5:29: ',' expected near 'do'",
+        ]
+    );
+
+    let (_, diagnostics) = AST::new_recovering("f(function() x = (1 end)".to_string());
+    let Diagnostic {
+        expected, found, ..
+    } = &diagnostics[0];
+    assert_eq!(expected.as_deref(), Some("')'"));
+    assert_eq!(found, &Some(TokenType::Keyword(Keyword::END)));
+}
+
+#[test]
+fn test_recover_failed_block_header() {
+    // Body of a statement with a broken header is skipped up to its `end`, and errors after it are reported
+    assert_eq!(
+        diagnostics("if x = 1 then\n  y = 2\nend\nprint(1\n"),
+        vec![
+            "Syntax error at 1:6: 'then' expected near '='",
+            "Syntax error at 4:7: ')' expected near <eof>",
+        ]
+    );
+
+    assert_eq!(
+        diagnostics(
+            "function f(a b)\n  return a\nend\n\
+             while x = 1 do\n  if y then z = 1 end\nend\n\
+             for i = 1 do\n  print(i)\nend\n\
+             x = = 1\n"
+        ),
+        vec![
+            "Syntax error at 1:14: ')' expected near 'b'",
+            "Syntax error at 4:9: 'do' expected near '='",
+            "Syntax error at 7:12: ',' expected near 'do'",
+            "Syntax error at 10:5: expression expected near '='",
+        ]
+    );
+
+    assert_eq!(
+        diagnostics("if a then\n  b()\nelseif c = 1 then\n  d()\nelse\n  e()\nend\nf(\n"),
+        vec![
+            "Syntax error at 3:10: 'then' expected near '='",
+            "Syntax error at 8:2: ')' expected near <eof>",
+        ]
+    );
+}
+
+#[test]
+fn test_recover_name_at_line_start() {
+    // Statements, which start with a name on a new line, are not skipped
+    assert_eq!(
+        diagnostics("local t = {1, 2\nprint(t\ny = = 2"),
+        vec![
+            "Syntax error at 2:5: '}' expected near 'print'",
+            "Syntax error at 3:1: ')' expected near 'y'",
+            "Syntax error at 3:5: expression expected near '='",
+        ]
+    );
+}
+
+#[test]
+fn test_recover_unmatched_end() {
+    assert_eq!(
+        diagnostics("x = 1 end y = 2 until"),
+        vec![
+            "Syntax error at 1:9: statement expected near 'end'",
+            "Syntax error at 1:21: statement expected near 'until'",
+        ]
+    );
+}
+
+#[test]
+fn test_recover_end_of_input() {
+    assert_eq!(
+        diagnostics("if x then y = 1"),
+        vec!["Syntax error at 1:15: 'end' expected near <eof>"]
+    );
+
+    // End of input is reported at the last token, not after the trailing lines
+    assert_eq!(
+        diagnostics("x = 1\nlocal function f()\n  return 1\n\n"),
+        vec!["Syntax error at 3:10: 'end' expected near <eof>"]
+    );
+
+    let (_, diagnostics) = AST::new_recovering("while x do".to_string());
+    let Diagnostic {
+        expected, found, ..
+    } = &diagnostics[0];
+    assert_eq!(expected.as_deref(), Some("'end'"));
+    assert_eq!(found, &None);
+}
+
+#[test]
+fn test_recover_lexical_error() {
+    // Lexer skips the invalid symbol or string and continues
+    assert_eq!(
+        diagnostics("x = @\nlocal y = = 1\nlocal z = \"\\q\" .. 'a'\nlocal w = = 2"),
+        vec![
+            "Lexical error at 1:4: Unexpected symbol '@'",
+            "Syntax error at 2:11: expression expected near '='",
+            "Lexical error at 3:12: Invalid escape sequence '\\q'",
+            "Syntax error at 4:11: expression expected near '='",
+        ]
+    );
+
+    // Unfinished string consumes the rest of the input
+    assert_eq!(
+        diagnostics("x = = 1\ny = [[abc\nz = = 2"),
+        vec![
+            "Syntax error at 1:5: expression expected near '='",
+            "Lexical error at 2:4: Unmatched long string brackets",
+        ]
+    );
+}
+
+#[test]
+fn test_recover_partial_ast() {
+    // Statements with errors are skipped up to the next statement keyword, the rest of the chunk
+    // is still executable
+    let (ast, diagnostics) =
+        AST::new_recovering("x = 1\ny = = 2 w = 3\nlocal z = x + 1 return z".to_string());
+    assert_eq!(diagnostics.len(), 1);
+
+    let result = ast
+        .exec(&mut AST::environment(), Default::default())
+        .unwrap();
+    assert_eq!(result, vec![types::Type::Integer(2)]);
+}
+
+#[test]
+fn test_first_error_without_recovery() {
+    let error = AST::new("x = = 1\ny = = 2".to_string()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Syntax error at 1:5: expression expected near '='"
+    );
+}
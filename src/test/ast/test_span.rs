@@ -0,0 +1,78 @@
+use std::rc::Rc;
+
+use crate::ast::expressions::Expression;
+use crate::ast::parser::Parser;
+use crate::ast::rules;
+use crate::ast::span::{Position, Span};
+use crate::ast::stack::Stack;
+
+/// Parse the expression and return its span as `(start row, start column, end row, end column)`
+fn expression_span(source: &str) -> (usize, usize, usize, usize) {
+    let mut parser = Parser::new(source.to_string());
+    let mut stack = Stack::default();
+
+    assert!(rules::exp(&mut parser, &mut stack), "Expression must be valid");
+    let expression: Box<dyn Expression> = stack.pop_single();
+    let span = expression.span().expect("Expression must have a span");
+
+    (span.start.row, span.start.column, span.end.row, span.end.column)
+}
+
+#[test]
+fn test_span_merge() {
+    let chunk: Rc<str> = Rc::from("test");
+    let left = Span::new(chunk.clone(), Position::new(1, 5), Position::new(1, 7));
+    let right = Span::new(chunk.clone(), Position::new(2, 1), Position::new(2, 3));
+
+    let merged = left.merge(&right);
+    assert_eq!(merged.start, Position::new(1, 5));
+    assert_eq!(merged.end, Position::new(2, 3));
+    assert_eq!(right.merge(&left), merged);
+
+    assert_eq!(Span::merge_options(None, Some(right.clone())), Some(right));
+    assert_eq!(Span::merge_options(None, None), None);
+}
+
+#[test]
+fn test_span_display() {
+    let span = Span::new(Rc::from("script.lua"), Position::new(12, 3), Position::new(14, 1));
+    assert_eq!(span.to_string(), "script.lua:12");
+}
+
+#[test]
+fn test_span_primitives() {
+    assert_eq!(expression_span("42"), (1, 1, 1, 2));
+    assert_eq!(expression_span("  \"Hello\""), (1, 3, 1, 9));
+    assert_eq!(expression_span("name"), (1, 1, 1, 4));
+}
+
+#[test]
+fn test_span_operators() {
+    assert_eq!(expression_span("a + b * 2"), (1, 1, 1, 9));
+    assert_eq!(expression_span("-x"), (1, 1, 1, 2));
+    assert_eq!(expression_span("not\n  done"), (1, 1, 2, 6));
+}
+
+#[test]
+fn test_span_postfix_expressions() {
+    assert_eq!(expression_span("a.b.c"), (1, 1, 1, 5));
+    assert_eq!(expression_span("t[1]"), (1, 1, 1, 4));
+    assert_eq!(expression_span("f(1,\n  2)"), (1, 1, 2, 4));
+    assert_eq!(expression_span("obj:method()"), (1, 1, 1, 12));
+}
+
+#[test]
+fn test_span_constructors() {
+    assert_eq!(expression_span("{ 1, 2 }"), (1, 1, 1, 8));
+    assert_eq!(expression_span("function() end"), (1, 1, 1, 14));
+}
+
+#[test]
+fn test_chunk_name() {
+    let mut parser = Parser::new("x".to_string()).with_chunk_name("script.lua");
+    let mut stack = Stack::default();
+
+    assert!(rules::exp(&mut parser, &mut stack));
+    let expression = stack.pop_single();
+    assert_eq!(expression.span().unwrap().to_string(), "script.lua:1");
+}
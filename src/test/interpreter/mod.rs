@@ -1,9 +1,18 @@
+mod test_base;
 mod test_blocks;
+mod test_debug;
 mod test_functions;
+mod test_labels;
+mod test_math;
+mod test_metamethods;
+mod test_metatables;
+mod test_multiple_values;
+mod test_native;
 mod test_operators;
 mod test_primitives;
 mod test_tables;
 mod test_types;
 mod test_variables;
 mod test_statements;
+mod test_string;
 pub mod utils;
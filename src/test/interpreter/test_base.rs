@@ -0,0 +1,323 @@
+use super::utils::{interpret_stdlib, variable};
+
+#[test]
+fn test_type() {
+    let env = interpret_stdlib(
+        r#"a, b, c, d = type(nil), type(true), type(5), type("x")
+           e, f, g = type({}), type(type), type(function () end)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("nil")"#);
+    assert_eq!(variable(&env, "b"), r#"String("boolean")"#);
+    assert_eq!(variable(&env, "c"), r#"String("number")"#);
+    assert_eq!(variable(&env, "d"), r#"String("string")"#);
+    assert_eq!(variable(&env, "e"), r#"String("table")"#);
+    assert_eq!(variable(&env, "f"), r#"String("function")"#);
+    assert_eq!(variable(&env, "g"), r#"String("function")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #1 to 'type' (value expected)")]
+fn test_type_no_value() {
+    interpret_stdlib("x = type()");
+}
+
+#[test]
+fn test_tostring() {
+    let env = interpret_stdlib(
+        r#"a, b, c = tostring(nil), tostring(false), tostring(10)
+           d, e, f = tostring(0.1), tostring(10 ^ 20), tostring(-2.5)
+           g = tostring("str")"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("nil")"#);
+    assert_eq!(variable(&env, "b"), r#"String("false")"#);
+    assert_eq!(variable(&env, "c"), r#"String("10")"#);
+    assert_eq!(variable(&env, "d"), r#"String("0.1")"#);
+    assert_eq!(variable(&env, "e"), r#"String("1e+20")"#);
+    assert_eq!(variable(&env, "f"), r#"String("-2.5")"#);
+    assert_eq!(variable(&env, "g"), r#"String("str")"#);
+}
+
+#[test]
+fn test_tostring_floats() {
+    let env = interpret_stdlib(
+        r#"a, b, c = tostring(1.0), tostring(-0.0), tostring(2^53)
+           d, e, f = tostring(1e14), tostring(123456789012345.0), tostring(3 / 2)
+           g, h = tostring(math.maxinteger), 10 // 2 .. "|" .. 10 / 2"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("1.0")"#);
+    assert_eq!(variable(&env, "b"), r#"String("-0.0")"#);
+    assert_eq!(variable(&env, "c"), r#"String("9.007199254741e+15")"#);
+    assert_eq!(variable(&env, "d"), r#"String("1e+14")"#);
+    assert_eq!(variable(&env, "e"), r#"String("1.2345678901234e+14")"#);
+    assert_eq!(variable(&env, "f"), r#"String("1.5")"#);
+    assert_eq!(variable(&env, "g"), r#"String("9223372036854775807")"#);
+    assert_eq!(variable(&env, "h"), r#"String("5|5.0")"#);
+}
+
+#[test]
+fn test_tostring_metamethod() {
+    let env = interpret_stdlib(
+        r#"point = setmetatable({x = 1, y = 2}, {__tostring = function(p) return "(" .. p.x .. ", " .. p.y .. ")" end})
+           a = tostring(point)
+           b = tostring(setmetatable({}, {__tostring = function() return 42 end}))
+           c, d = pcall(tostring, setmetatable({}, {__tostring = function() return true end}))"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("(1, 2)")"#);
+    assert_eq!(variable(&env, "b"), r#"String("42")"#);
+    assert_eq!(variable(&env, "c"), "Boolean(false)");
+    assert_eq!(variable(&env, "d"), r#"String("'__tostring' must return a string")"#);
+}
+
+#[test]
+fn test_tonumber() {
+    let env = interpret_stdlib(
+        r#"a, b, c = tonumber(" 10 "), tonumber("0x1F"), tonumber("1.5")
+           d, e, f = tonumber("z", 36), tonumber("-ff", 16), tonumber("2", 2)
+           g, h = tonumber("hello"), tonumber({})"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(10)");
+    assert_eq!(variable(&env, "b"), "Integer(31)");
+    assert_eq!(variable(&env, "c"), "Number(1.5)");
+    assert_eq!(variable(&env, "d"), "Integer(35)");
+    assert_eq!(variable(&env, "e"), "Integer(-255)");
+    assert_eq!(variable(&env, "f"), "Nil");
+    assert_eq!(variable(&env, "g"), "Nil");
+    assert_eq!(variable(&env, "h"), "Nil");
+}
+
+#[test]
+fn test_tonumber_numerals() {
+    let env = interpret_stdlib(
+        r#"a, b, c = tonumber("0x1p4"), tonumber(".5"), tonumber(" -0x10 ")
+           d, e, f = tonumber("1E2"), tonumber("1e"), tonumber("0x")
+           g = "0xA" + 1"#,
+    );
+    assert_eq!(variable(&env, "a"), "Number(16.0)");
+    assert_eq!(variable(&env, "b"), "Number(0.5)");
+    assert_eq!(variable(&env, "c"), "Integer(-16)");
+    assert_eq!(variable(&env, "d"), "Number(100.0)");
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "f"), "Nil");
+    assert_eq!(variable(&env, "g"), "Integer(11)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #2 to 'tonumber' (base out of range)")]
+fn test_tonumber_invalid_base() {
+    interpret_stdlib(r#"x = tonumber("10", 99)"#);
+}
+
+#[test]
+fn test_assert() {
+    let env = interpret_stdlib(r#"a, b = assert(5, "message")"#);
+    assert_eq!(variable(&env, "a"), "Integer(5)");
+    assert_eq!(variable(&env, "b"), r#"String("message")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: assertion failed!")]
+fn test_assert_failed() {
+    interpret_stdlib("assert(false)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: custom message")]
+fn test_assert_message() {
+    interpret_stdlib(r#"assert(nil, "custom message")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: something went wrong")]
+fn test_error() {
+    interpret_stdlib(r#"error("something went wrong")"#);
+}
+
+#[test]
+fn test_error_level() {
+    let env = interpret_stdlib(
+        r#"function fail(level)
+             error("failure", level)
+           end
+
+           function check(level)
+             local ok, message = pcall(fail, level)
+             return message
+           end
+
+           a = check()
+           b = check(1)
+           c = check(2)
+           d = check(0)
+           e = check(10)
+           ok, f = pcall(error)"#,
+    );
+
+    assert_eq!(variable(&env, "a"), r#"String("chunk:2: failure")"#);
+    assert_eq!(variable(&env, "b"), r#"String("chunk:2: failure")"#);
+    // `fail` is called by native `pcall`, so the second level is the line of `pcall` call
+    assert_eq!(variable(&env, "c"), r#"String("chunk:6: failure")"#);
+    assert_eq!(variable(&env, "d"), r#"String("failure")"#);
+    assert_eq!(variable(&env, "e"), r#"String("failure")"#);
+    assert_eq!(variable(&env, "f"), "Nil");
+}
+
+#[test]
+fn test_error_values() {
+    let env = interpret_stdlib(
+        r#"ok, a = pcall(error, {code = 42})
+           code = a.code
+           ok, b = pcall(error, 42)
+           ok, c = pcall(assert, false, {code = 7})
+           ok, d = pcall(assert, nil)"#,
+    );
+
+    assert_eq!(variable(&env, "ok"), "Boolean(false)");
+    assert_eq!(variable(&env, "code"), "Integer(42)");
+    assert_eq!(variable(&env, "b"), "Integer(42)");
+    assert!(variable(&env, "c").starts_with("Table {"));
+    assert_eq!(variable(&env, "d"), r#"String("assertion failed!")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: (error object is a table value)")]
+fn test_error_table_uncaught() {
+    interpret_stdlib("error({})");
+}
+
+#[test]
+fn test_pcall() {
+    let env = interpret_stdlib(
+        r#"function divide(a, b)
+             if b == 0 then
+               error("division by zero", 0)
+             end
+             return a / b, a % b
+           end
+
+           ok, a, b = pcall(divide, 7, 2)
+           failed, message = pcall(divide, 1, 0)
+           runtime, runtime_message = pcall(function() return nil + 1 end)
+           not_function, call_message = pcall(42)
+           nested, nested_value = pcall(pcall, error, "inner")"#,
+    );
+
+    assert_eq!(variable(&env, "ok"), "Boolean(true)");
+    assert_eq!(variable(&env, "a"), "Number(3.5)");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+    assert_eq!(variable(&env, "failed"), "Boolean(false)");
+    assert_eq!(variable(&env, "message"), r#"String("division by zero")"#);
+    assert_eq!(variable(&env, "runtime"), "Boolean(false)");
+    assert_eq!(
+        variable(&env, "runtime_message"),
+        r#"String("chunk:10: attempt to perform arithmetic on a nil value")"#
+    );
+    assert_eq!(variable(&env, "not_function"), "Boolean(false)");
+    assert_eq!(
+        variable(&env, "call_message"),
+        r#"String("attempt to call a number value")"#
+    );
+    // Outer `pcall` succeeds, because inner one catches the error
+    assert_eq!(variable(&env, "nested"), "Boolean(true)");
+    assert_eq!(variable(&env, "nested_value"), "Boolean(false)");
+}
+
+#[test]
+fn test_xpcall() {
+    let env = interpret_stdlib(
+        r#"function handler(error)
+             return "handled: " .. error.message, "ignored"
+           end
+
+           ok, a = xpcall(function(x) return x * 2 end, handler, 21)
+           failed, b, c = xpcall(function() error({message = "oops"}) end, handler)
+           broken, d = xpcall(error, function() error("handler failed", 0) end, "original")"#,
+    );
+
+    assert_eq!(variable(&env, "ok"), "Boolean(true)");
+    assert_eq!(variable(&env, "a"), "Integer(42)");
+    assert_eq!(variable(&env, "failed"), "Boolean(false)");
+    assert_eq!(variable(&env, "b"), r#"String("handled: oops")"#);
+    assert_eq!(variable(&env, "c"), "Nil");
+    assert_eq!(variable(&env, "broken"), "Boolean(false)");
+    assert_eq!(variable(&env, "d"), r#"String("handler failed")"#);
+}
+
+#[test]
+fn test_select() {
+    let env = interpret_stdlib(
+        r##"a = select("#", 1, 2, 3)
+           b, c = select(2, "x", "y", "z")
+           d = select(-1, "x", "y", "z")
+           e = select(5, "x")"##,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), r#"String("y")"#);
+    assert_eq!(variable(&env, "c"), r#"String("z")"#);
+    assert_eq!(variable(&env, "d"), r#"String("z")"#);
+    assert_eq!(variable(&env, "e"), "Nil");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #1 to 'select' (index out of range)")]
+fn test_select_invalid_index() {
+    interpret_stdlib(r#"x = select(-3, "x")"#);
+}
+
+#[test]
+fn test_ipairs() {
+    let env = interpret_stdlib(
+        r#"t = {10, 20}
+           f, s, i = ipairs(t)
+           i1, v1 = f(s, i)
+           i2, v2 = f(s, i1)
+           i3 = f(s, i2)"#,
+    );
+    assert_eq!(variable(&env, "i1"), "Integer(1)");
+    assert_eq!(variable(&env, "v1"), "Integer(10)");
+    assert_eq!(variable(&env, "i2"), "Integer(2)");
+    assert_eq!(variable(&env, "v2"), "Integer(20)");
+    assert_eq!(variable(&env, "i3"), "Nil");
+}
+
+#[test]
+fn test_next() {
+    let env = interpret_stdlib(
+        r#"t = {x = 5}
+           f, s, k = pairs(t)
+           k1, v1 = f(s, k)
+           k2 = next(t, k1)
+           e = next({})"#,
+    );
+    assert_eq!(variable(&env, "k1"), r#"String("x")"#);
+    assert_eq!(variable(&env, "v1"), "Integer(5)");
+    assert_eq!(variable(&env, "k2"), "Nil");
+    assert_eq!(variable(&env, "e"), "Nil");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #1 to 'pairs' (table expected, got nil)")]
+fn test_pairs_not_table() {
+    interpret_stdlib("x = pairs(nil)");
+}
+
+#[test]
+fn test_raw_functions() {
+    let env = interpret_stdlib(
+        r#"t = {1, 2}
+           rawset(t, 3, "three")
+           a, b = rawget(t, 3), rawlen(t)
+           c, d = rawequal(t, t), rawequal(t, {})
+           e = rawlen("hello")"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("three")"#);
+    assert_eq!(variable(&env, "b"), "Integer(3)");
+    assert_eq!(variable(&env, "c"), "Boolean(true)");
+    assert_eq!(variable(&env, "d"), "Boolean(false)");
+    assert_eq!(variable(&env, "e"), "Integer(5)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: table index is nil")]
+fn test_rawset_nil_key() {
+    interpret_stdlib("rawset({}, nil, 1)");
+}
@@ -1,60 +1,324 @@
 use crate::ast::rules;
 
-use super::utils::interpret_rule;
+use super::utils::{interpret_rule, interpret_stdlib, variable};
 
 #[test]
 fn test_do_block() {
     let (_val, env) = interpret_rule("y = 3; do y = 5 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(5.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(5) }}"#);
 }
 
 #[test]
 fn test_do_block_local() {
     let (_val, env) = interpret_rule("y = 3; do local y = 5 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(3.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(3) }}"#);
 }
 
 #[test]
 fn test_while() {
     let (_val, env) = interpret_rule("y = 3; while y ~= 5 do y = y + 1 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(5.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(5) }}"#);
 }
 
 #[test]
 fn test_if() {
     let (_val, env) = interpret_rule("y = 3; if 5 == 5 then y = 5 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(5.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(5) }}"#);
 
     let (_val, env) = interpret_rule("y = 3; if 5 ~= 5 then y = 5 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(3.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(3) }}"#);
 
     let (_val, env) = interpret_rule("y = 3; if 5 ~= 5 then y = 5 else y = 7 end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(7.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(7) }}"#);
 
     let (_val, env) = interpret_rule(
         "y = 3; if 5 ~= 5 then y = 5 elseif 5 == 5 then y = 7 end",
         rules::block,
     );
-    assert_eq!(env, r#"{"y": RefCell { value: Number(7.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(7) }}"#);
 
     let (_val, env) = interpret_rule(
         "y = 3; if 5 == 5 then y = 5 elseif 5 == 5 then y = 7 end",
         rules::block,
     );
-    assert_eq!(env, r#"{"y": RefCell { value: Number(5.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(5) }}"#);
 
     let (_val, env) = interpret_rule(
         "y = 3; if 5 ~= 5 then y = 5 elseif 3 == 5 then y = 7 else y = -1 end",
         rules::block,
     );
-    assert_eq!(env, r#"{"y": RefCell { value: Number(-1.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(-1) }}"#);
+}
+
+#[test]
+fn test_if_block_scope() {
+    let env = interpret_stdlib(
+        r#"x = 1
+           if true then local x = 2 y = x end
+           if false then else local x = 3 z = x end
+           a = x"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "y"), "Integer(2)");
+    assert_eq!(variable(&env, "z"), "Integer(3)");
+}
+
+#[test]
+fn test_while_block_scope() {
+    // Every iteration has its own locals, closures capture variables of their own iteration
+    let env = interpret_stdlib(
+        r#"functions = {}
+           i = 0
+           while i < 3 do
+               i = i + 1
+               leaked = leaked or v
+               local v = i
+               functions[i] = function() return v end
+           end
+           a, b, c = functions[1](), functions[2](), functions[3]()"#,
+    );
+    assert_eq!(variable(&env, "leaked"), "Nil");
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+}
+
+#[test]
+fn test_repeat_block_scope() {
+    // `until` condition sees locals of the loop body
+    let env = interpret_stdlib(
+        r#"functions = {}
+           i = 0
+           repeat
+               i = i + 1
+               local done = i >= 3
+               functions[i] = function() return i, done end
+           until done or i > 10
+           a, b = functions[1]()
+           c = done"#,
+    );
+    assert_eq!(variable(&env, "i"), "Integer(3)");
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Boolean(false)");
+    assert_eq!(variable(&env, "c"), "Nil");
+}
+
+#[test]
+fn test_local_function_block_scope() {
+    // Local functions of a block are not visible outside, and every iteration creates its own function
+    let env = interpret_stdlib(
+        r#"functions = {}
+           if true then local function f() end end
+           while true do local function g() end break end
+           repeat local function h() return 1 end until h()
+           for i = 1, 2 do
+               local function n() return i end
+               functions[i] = n
+           end
+           for _, v in ipairs({3}) do local function p() end end
+           a, b, c, d, e = f, g, h, n, p
+           x, y = functions[1](), functions[2]()"#,
+    );
+    assert_eq!(variable(&env, "a"), "Nil");
+    assert_eq!(variable(&env, "b"), "Nil");
+    assert_eq!(variable(&env, "c"), "Nil");
+    assert_eq!(variable(&env, "d"), "Nil");
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "x"), "Integer(1)");
+    assert_eq!(variable(&env, "y"), "Integer(2)");
+}
+
+#[test]
+fn test_break_and_goto_in_nested_blocks() {
+    let env = interpret_stdlib(
+        r#"for i = 1, 10 do
+               do
+                   if i == 3 then break end
+               end
+               a = i
+           end
+           b = 0
+           while true do
+               b = b + 1
+               do break end
+           end
+           c = 0
+           for i = 1, 5 do
+               if i % 2 == 0 then goto continue end
+               c = c + i
+               ::continue::
+           end
+           function f(x)
+               if x then local y = x return y end
+               return "none"
+           end
+           d, e = f(5), f()"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(2)");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+    assert_eq!(variable(&env, "c"), "Integer(9)");
+    assert_eq!(variable(&env, "d"), "Integer(5)");
+    assert_eq!(variable(&env, "e"), r#"String("none")"#);
 }
 
 #[test]
 fn test_numerical_for() {
     let (_val, env) = interpret_rule("y = 3 for i = 0, 10 do y = y + i end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(48.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(58) }}"#);
 
     let (_val, env) = interpret_rule("y = 3 for i = 9, -1, -1 do y = y + i end", rules::block);
-    assert_eq!(env, r#"{"y": RefCell { value: Number(48.0) }}"#);
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(47) }}"#);
+}
+
+#[test]
+fn test_numerical_for_limits() {
+    let env = interpret_stdlib(
+        r#"function collect(init, limit, step)
+               local result = ""
+               for i = init, limit, step do result = result .. i .. " " end
+               return result
+           end
+           a, b, c = collect(1, 10, 2), collect(10, 1, 1), collect(10, 1, -3)
+           d, e = collect(0, 1, 0.25), collect(1, 3.5, 1)
+           f, g = collect(3, 1.5, -1), collect("1", "2", 1)
+           h = collect(1, 0, 1)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("1 3 5 7 9 ")"#);
+    assert_eq!(variable(&env, "b"), r#"String("")"#);
+    assert_eq!(variable(&env, "c"), r#"String("10 7 4 1 ")"#);
+    assert_eq!(variable(&env, "d"), r#"String("0.0 0.25 0.5 0.75 1.0 ")"#);
+    assert_eq!(variable(&env, "e"), r#"String("1 2 3 ")"#);
+    assert_eq!(variable(&env, "f"), r#"String("3 2 ")"#);
+    assert_eq!(variable(&env, "g"), r#"String("1.0 2.0 ")"#);
+    assert_eq!(variable(&env, "h"), r#"String("")"#);
+}
+
+#[test]
+fn test_numerical_for_integer_overflow() {
+    let env = interpret_stdlib(
+        r#"a, b, c = 0, 0, 0
+           for i = math.maxinteger - 2, math.maxinteger do a = a + 1 end
+           for i = math.mininteger + 2, math.mininteger, -1 do b = b + 1 end
+           for i = math.maxinteger - 1, 1e100 do c = c + 1 end
+           d = 0
+           for i = 1, -1e100 do d = d + 1 end"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(3)");
+    assert_eq!(variable(&env, "c"), "Integer(2)");
+    assert_eq!(variable(&env, "d"), "Integer(0)");
 }
+
+#[test]
+fn test_numerical_for_errors() {
+    let env = interpret_stdlib(
+        r#"a, b = pcall(function() for i = 1, 10, 0 do end end)
+           c, d = pcall(function() for i = 1, "x" do end end)
+           e, f = pcall(function() for i = {}, 1 do end end)
+           g, h = pcall(function() for i = 1, 2, 0.0 do end end)"#,
+    );
+    assert_eq!(variable(&env, "b"), r#"String("chunk:1: 'for' step is zero")"#);
+    assert_eq!(variable(&env, "d"), r#"String("chunk:2: 'for' limit must be a number")"#);
+    assert_eq!(variable(&env, "f"), r#"String("chunk:3: 'for' initial value must be a number")"#);
+    assert_eq!(variable(&env, "h"), r#"String("chunk:4: 'for' step is zero")"#);
+}
+
+#[test]
+fn test_numerical_for_fresh_variable() {
+    let env = interpret_stdlib(
+        r#"functions = {}
+           for i = 1, 3 do functions[i] = function() return i end end
+           a, b, c = functions[1](), functions[2](), functions[3]()
+           d = 0
+           for i = 1, 3 do i = i * 10 d = d + i end"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Integer(60)");
+}
+
+#[test]
+fn test_generic_for() {
+    let env = interpret_stdlib(
+        r#"a, b = 0, 0
+           for i, v in ipairs({10, 20, 30}) do a = a + i * v end
+           for k, v in pairs({x = 1, y = 2, z = 3}) do b = b + v end
+           words = ""
+           for word in string.gmatch("one two", "%a+") do words = words .. word .. ";" end
+           function range(n)
+               local i = 0
+               return function() i = i + 1 if i <= n then return i end end
+           end
+           c = 0
+           for i in range(4) do c = c + i end
+           d, e = nil, nil
+           for x, y, z in function(_, control) if control < 1 then return control + 1 end end, nil, 0 do d, e = x, z end"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(140)");
+    assert_eq!(variable(&env, "b"), "Integer(6)");
+    assert_eq!(variable(&env, "words"), r#"String("one;two;")"#);
+    assert_eq!(variable(&env, "c"), "Integer(10)");
+    assert_eq!(variable(&env, "d"), "Integer(1)");
+    assert_eq!(variable(&env, "e"), "Nil");
+}
+
+#[test]
+fn test_generic_for_state_and_control() {
+    let env = interpret_stdlib(
+        r#"calls = ""
+           function iterator(state, control)
+               calls = calls .. state .. control .. ","
+               if control < 3 then return control + 1 end
+           end
+           for i in iterator, "s", 0 do end"#,
+    );
+    assert_eq!(variable(&env, "calls"), r#"String("s0,s1,s2,s3,")"#);
+}
+
+#[test]
+fn test_generic_for_break_and_return() {
+    let env = interpret_stdlib(
+        r#"a = 0
+           for i, v in ipairs({1, 2, 3, 4}) do if v == 3 then break end a = a + v end
+           function find(list, value)
+               for i, v in ipairs(list) do if v == value then return i end end
+               return 0
+           end
+           b, c = find({5, 6, 7}, 6), find({5, 6, 7}, 8)
+           d = 0
+           for _, x in ipairs({1, 2, 3}) do
+               for _, y in ipairs({1, 2, 3}) do
+                   if y == 2 then goto continue end
+                   d = d + x * y
+                   ::continue::
+               end
+           end"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Integer(0)");
+    assert_eq!(variable(&env, "d"), "Integer(24)");
+}
+
+#[test]
+fn test_generic_for_fresh_locals() {
+    let env = interpret_stdlib(
+        r#"functions = {}
+           for i, v in ipairs({"a", "b", "c"}) do
+               functions[i] = function() return v end
+               v = v .. "!"
+           end
+           a, b, c = functions[1](), functions[2](), functions[3]()"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("a!")"#);
+    assert_eq!(variable(&env, "b"), r#"String("b!")"#);
+    assert_eq!(variable(&env, "c"), r#"String("c!")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to call a number value (for iterator 'for iterator')")]
+fn test_generic_for_not_callable() {
+    interpret_stdlib("for x in 5 do end");
+}
+
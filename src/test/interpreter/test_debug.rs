@@ -0,0 +1,75 @@
+use super::utils::{interpret_stdlib, variable};
+
+#[test]
+fn test_traceback() {
+    let env = interpret_stdlib(
+        r#"local function f()
+             return debug.traceback("message")
+           end
+           function g() return f() end
+           result = g()"#,
+    );
+    assert_eq!(
+        variable(&env, "result"),
+        r#"String("message\nstack traceback:\n\tchunk:2: in function 'f'\n\tchunk:4: in function 'g'\n\tchunk:5: in main chunk\n\t[C]: in ?")"#
+    );
+}
+
+#[test]
+fn test_traceback_level() {
+    let env = interpret_stdlib(
+        r#"t = {}
+           function t.f() return debug.traceback(nil, 2) end
+           function t:g() return self.f() end
+           a = t:g()
+           b = debug.traceback(42, 0)"#,
+    );
+    assert_eq!(
+        variable(&env, "a"),
+        r#"String("stack traceback:\n\tchunk:3: in method 'g'\n\tchunk:4: in main chunk\n\t[C]: in ?")"#
+    );
+    assert_eq!(
+        variable(&env, "b"),
+        r#"String("42\nstack traceback:\n\t[C]: in function 'debug.traceback'\n\tchunk:5: in main chunk\n\t[C]: in ?")"#
+    );
+}
+
+#[test]
+fn test_traceback_non_string_message() {
+    let env = interpret_stdlib("t = {} result = debug.traceback(t) == t");
+    assert_eq!(variable(&env, "result"), "Boolean(true)");
+}
+
+#[test]
+fn test_traceback_message_handler() {
+    // Handler is called after the error, but gets the traceback of the failed function
+    let env = interpret_stdlib(
+        r#"ok, result = xpcall(function()
+             local x = nil
+             return x.y
+           end, debug.traceback)"#,
+    );
+    assert_eq!(variable(&env, "ok"), "Boolean(false)");
+    assert_eq!(
+        variable(&env, "result"),
+        r#"String("chunk:3: attempt to index a nil value (local 'x')\nstack traceback:\n\tchunk:3: in function <chunk:1>\n\t[C]: in function 'xpcall'\n\tchunk:1: in main chunk\n\t[C]: in ?")"#
+    );
+}
+
+#[test]
+fn test_traceback_long() {
+    // Only the innermost and the outermost calls are shown
+    let env = interpret_stdlib(
+        r#"function f(n) if n == 0 then return debug.traceback() end return (f(n - 1)) end
+           result = f(30)"#,
+    );
+    let lines = [
+        r#"String("stack traceback:\n\tchunk:1: in function 'f'"#,
+        r#"\n\tchunk:1: in function 'f'\n\t...\t(skipping 12 levels)\n\tchunk:1: in function 'f'"#,
+        r#"\n\tchunk:2: in main chunk\n\t[C]: in ?")"#,
+    ];
+    let result = variable(&env, "result");
+    assert!(result.starts_with(lines[0]));
+    assert!(result.contains(lines[1]));
+    assert!(result.ends_with(lines[2]));
+}
@@ -1,6 +1,6 @@
 use crate::ast::rules;
 
-use super::utils::{interpret_rule, interpret_rule_env};
+use super::utils::{interpret_rule, interpret_rule_env, interpret_stdlib, variable};
 
 #[test]
 fn test_closure_eval() {
@@ -17,7 +17,7 @@ fn test_function_eval() {
     assert_eq!(env, r#"{"t": RefCell { value: Function { id: 1, parameters: [], varargs: true, body: Block { statements: [Break], retstat: None }, env: 0 } }}"#);
 
     let (_val, env) = interpret_rule("t = {}; function t:f(b, c, ...) break end", rules::block);
-    assert_eq!(env, r#"{"t": RefCell { value: Table { id: 1, map: {String("f"): RefCell { value: Function { id: 2, parameters: ["self", "b", "c"], varargs: true, body: Block { statements: [Break], retstat: None }, env: 0 } }}, metatable: {}, border: 0 } }}"#);
+    assert_eq!(env, r#"{"t": RefCell { value: Table { id: 1, array: [], hash: {String("f"): Function { id: 2, parameters: ["self", "b", "c"], varargs: true, body: Block { statements: [Break], retstat: None }, env: 0 }}, metatable: None } }}"#);
 }
 
 #[test]
@@ -25,7 +25,7 @@ fn test_functioncall_eval() {
     let (_, mut env) = interpret_rule("function sum1(x) return x + 1; end", rules::stat);
 
     let (val, _) = interpret_rule_env("sum1(5)", rules::functioncall, &mut env);
-    assert_eq!(val, "Number(6.0)");
+    assert_eq!(val, "Integer(6)");
 }
 
 #[test]
@@ -34,18 +34,45 @@ fn test_method_eval() {
     let (_, mut env) = interpret_rule_env("function tab:sum(x) return x + self.x; end", rules::stat, &mut env);
 
     let (val, _) = interpret_rule_env("tab:sum(5)", rules::functioncall, &mut env);
-    assert_eq!(val, "Number(10.0)");
+    assert_eq!(val, "Integer(10)");
 }
 
 #[test]
 fn test_varags_eval() {
-    let (_, mut env) = interpret_rule("function args(...) return arg; end", rules::stat);
+    let (_, mut env) = interpret_rule("function args(...) return ...; end", rules::stat);
 
     let (val, _) = interpret_rule_env("args(5)", rules::functioncall, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Vector([Number(5.0)]) })");
+    assert_eq!(val, "Integer(5)");
 
-    let (_, mut env) = interpret_rule("function args(a, b, ...) return arg; end", rules::stat);
+    let (_, mut env) = interpret_rule("function args(a, b, ...) local c, d = ...; return d; end", rules::stat);
 
     let (val, _) = interpret_rule_env("args(1, 2, 3, 4)", rules::functioncall, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Vector([Number(3.0), Number(4.0)]) })");
+    assert_eq!(val, "Integer(4)");
+}
+
+#[test]
+fn test_recursion_limit() {
+    // Deep recursion needs the stack of the interpreter thread
+    let results = std::thread::Builder::new()
+        .stack_size(crate::STACK_SIZE)
+        .spawn(|| {
+            let env = interpret_stdlib(
+                r#"local function infinite() return infinite() end
+                   local function depth(n) if n == 0 then return 0 end return 1 + depth(n - 1) end
+                   local looped = setmetatable({}, {__index = function(t, k) return t[k] end})
+                   a, b = pcall(infinite)
+                   c, d = pcall(function() return looped.x end)
+                   e = depth(1000)"#,
+            );
+            ["a", "b", "c", "d", "e"].iter().map(|name| variable(&env, name)).collect::<Vec<_>>()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(results[0], "Boolean(false)");
+    assert_eq!(results[1], r#"String("chunk:1: stack overflow")"#);
+    assert_eq!(results[2], "Boolean(false)");
+    assert_eq!(results[3], r#"String("chunk:3: stack overflow")"#);
+    assert_eq!(results[4], "Integer(1000)");
 }
@@ -0,0 +1,66 @@
+use crate::ast::rules;
+
+use super::utils::interpret_rule;
+
+#[test]
+fn test_goto_continue() {
+    let (_val, env) = interpret_rule(
+        "y = 0 for i = 1, 6 do if i % 2 == 0 then goto continue end y = y + i ::continue:: end",
+        rules::block,
+    );
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(9) }}"#);
+
+    let (_val, env) = interpret_rule(
+        "y = 0 while y < 10 do y = y + 1 do goto continue end y = 100 ::continue:: end",
+        rules::block,
+    );
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(10) }}"#);
+}
+
+#[test]
+fn test_goto_backward() {
+    let (_val, env) = interpret_rule(
+        "y = 0 ::top:: y = y + 1 if y < 5 then goto top end",
+        rules::block,
+    );
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(5) }}"#);
+}
+
+#[test]
+fn test_goto_out_of_loops() {
+    let (_val, env) = interpret_rule(
+        "y = 0 while true do repeat y = y + 1 if y == 3 then goto done end until false end y = 100 ::done::",
+        rules::block,
+    );
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(3) }}"#);
+}
+
+#[test]
+fn test_goto_local_at_block_end() {
+    let (_val, env) = interpret_rule(
+        "y = 0 do goto skip local x = 5 y = x ::skip:: ; end",
+        rules::block,
+    );
+    assert_eq!(env, r#"{"y": RefCell { value: Integer(0) }}"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: `goto skip` jumps into the scope of a local")]
+fn test_goto_into_local_scope() {
+    interpret_rule("do goto skip local x = 5 ::skip:: x = 1 end", rules::block);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: No visible label 'inner' for `goto`")]
+fn test_goto_into_nested_block() {
+    interpret_rule(
+        "function f() goto inner do ::inner:: end end x = f()",
+        rules::block,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: No visible label 'outer' for `goto`")]
+fn test_goto_out_of_function() {
+    interpret_rule("function f() goto outer end ::outer:: x = f()", rules::block);
+}
@@ -0,0 +1,49 @@
+use super::utils::{interpret_stdlib, variable};
+
+#[test]
+fn test_math_type() {
+    let env = interpret_stdlib(
+        r#"a, b, c = math.type(1), math.type(1.0), math.type(2^53)
+           d, e = math.type("1"), math.type(nil)
+           f, g = math.type(1 // 1), math.type(1 / 1)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("integer")"#);
+    assert_eq!(variable(&env, "b"), r#"String("float")"#);
+    assert_eq!(variable(&env, "c"), r#"String("float")"#);
+    assert_eq!(variable(&env, "d"), "Nil");
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "f"), r#"String("integer")"#);
+    assert_eq!(variable(&env, "g"), r#"String("float")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #1 to 'type' (value expected)")]
+fn test_math_type_no_value() {
+    interpret_stdlib("x = math.type()");
+}
+
+#[test]
+fn test_math_tointeger() {
+    let env = interpret_stdlib(
+        r#"a, b, c = math.tointeger(3.0), math.tointeger(3.5), math.tointeger(2^63)
+           d, e = math.tointeger(-7), math.tointeger("8")"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Nil");
+    assert_eq!(variable(&env, "c"), "Nil");
+    assert_eq!(variable(&env, "d"), "Integer(-7)");
+    assert_eq!(variable(&env, "e"), "Nil");
+}
+
+#[test]
+fn test_math_integer_limits() {
+    let env = interpret_stdlib(
+        r#"a, b = math.maxinteger, math.mininteger
+           c = math.maxinteger + 1 == math.mininteger
+           d = math.mininteger // -1"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(9223372036854775807)");
+    assert_eq!(variable(&env, "b"), "Integer(-9223372036854775808)");
+    assert_eq!(variable(&env, "c"), "Boolean(true)");
+    assert_eq!(variable(&env, "d"), "Integer(-9223372036854775808)");
+}
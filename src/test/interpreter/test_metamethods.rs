@@ -0,0 +1,162 @@
+use crate::interpreter::environment;
+use crate::utils;
+
+use super::utils::{interpret_stdlib, variable};
+
+const VECTOR: &str = r#"
+    Vector = {}
+    Vector.__index = Vector
+
+    function Vector.new(x, y)
+        return setmetatable({x = x, y = y}, Vector)
+    end
+
+    function Vector.__add(a, b) return Vector.new(a.x + b.x, a.y + b.y) end
+    function Vector.__sub(a, b) return Vector.new(a.x - b.x, a.y - b.y) end
+    function Vector.__unm(a) return Vector.new(-a.x, -a.y) end
+    function Vector.__len(a) return a.x * a.x + a.y * a.y end
+    function Vector.__eq(a, b) return a.x == b.x and a.y == b.y end
+    function Vector.__lt(a, b) return #a < #b end
+    function Vector.__concat(a, b) return "vector" end
+"#;
+
+fn interpret_vector(source_code: &str) -> utils::Shared<environment::Environment> {
+    interpret_stdlib(&format!("{}\n{}", VECTOR, source_code))
+}
+
+#[test]
+fn test_arithmetic_metamethods() {
+    let env = interpret_vector(
+        r#"v = Vector.new(1, 2) + Vector.new(3, 4)
+           w = Vector.new(1, 2) - Vector.new(3, 4)
+           n = -Vector.new(1, 2)
+           x, y, a, b, c, d = v.x, v.y, w.x, w.y, n.x, n.y"#,
+    );
+    assert_eq!(variable(&env, "x"), "Integer(4)");
+    assert_eq!(variable(&env, "y"), "Integer(6)");
+    assert_eq!(variable(&env, "a"), "Integer(-2)");
+    assert_eq!(variable(&env, "b"), "Integer(-2)");
+    assert_eq!(variable(&env, "c"), "Integer(-1)");
+    assert_eq!(variable(&env, "d"), "Integer(-2)");
+}
+
+#[test]
+fn test_all_arithmetic_events() {
+    let env = interpret_stdlib(
+        r#"mt = {}
+           function event(name)
+               mt["__" .. name] = function (a, b) return name end
+           end
+           event("add") event("sub") event("mul") event("div") event("mod") event("pow") event("idiv")
+           event("band") event("bor") event("bxor") event("shl") event("shr") event("concat")
+           t = setmetatable({}, mt)
+           a, b, c, d, e, f, g = t + 1, t - 1, t * 1, t / 1, t % 1, t ^ 1, t // 1
+           h, i, j, k, l, m = t & 1, t | 1, t ~ 1, t << 1, t >> 1, t .. 1"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("add")"#);
+    assert_eq!(variable(&env, "b"), r#"String("sub")"#);
+    assert_eq!(variable(&env, "c"), r#"String("mul")"#);
+    assert_eq!(variable(&env, "d"), r#"String("div")"#);
+    assert_eq!(variable(&env, "e"), r#"String("mod")"#);
+    assert_eq!(variable(&env, "f"), r#"String("pow")"#);
+    assert_eq!(variable(&env, "g"), r#"String("idiv")"#);
+    assert_eq!(variable(&env, "h"), r#"String("band")"#);
+    assert_eq!(variable(&env, "i"), r#"String("bor")"#);
+    assert_eq!(variable(&env, "j"), r#"String("bxor")"#);
+    assert_eq!(variable(&env, "k"), r#"String("shl")"#);
+    assert_eq!(variable(&env, "l"), r#"String("shr")"#);
+    assert_eq!(variable(&env, "m"), r#"String("concat")"#);
+}
+
+#[test]
+fn test_right_operand_metamethod() {
+    let env = interpret_vector(
+        r#"mt = {__add = function (a, b) return a end, __concat = function (a, b) return a .. "!" end}
+           t = setmetatable({}, mt)
+           a = 5 + t
+           b = "hello" .. t"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(5)");
+    assert_eq!(variable(&env, "b"), r#"String("hello!")"#);
+}
+
+#[test]
+fn test_unary_metamethods() {
+    let env = interpret_stdlib(
+        r#"t = setmetatable({}, {__bnot = function (a, b) return rawequal(a, b) end,
+                                 __len = function () return 42 end})
+           a, b = ~t, #t"#,
+    );
+    assert_eq!(variable(&env, "a"), "Boolean(true)");
+    assert_eq!(variable(&env, "b"), "Integer(42)");
+}
+
+#[test]
+fn test_comparison_metamethods() {
+    let env = interpret_vector(
+        r#"a, b = Vector.new(1, 1), Vector.new(2, 2)
+           lt, gt, le, ge = a < b, a > b, a <= b, a >= b
+           eq, ne = a == Vector.new(1, 1), a ~= Vector.new(1, 1)
+           same = a == a
+           other = a == 1"#,
+    );
+    assert_eq!(variable(&env, "lt"), "Boolean(true)");
+    assert_eq!(variable(&env, "gt"), "Boolean(false)");
+    // `__le` falls back to `not (b < a)`
+    assert_eq!(variable(&env, "le"), "Boolean(true)");
+    assert_eq!(variable(&env, "ge"), "Boolean(false)");
+    assert_eq!(variable(&env, "eq"), "Boolean(true)");
+    assert_eq!(variable(&env, "ne"), "Boolean(false)");
+    assert_eq!(variable(&env, "same"), "Boolean(true)");
+    assert_eq!(variable(&env, "other"), "Boolean(false)");
+}
+
+#[test]
+fn test_table_equality_without_metamethod() {
+    let env = interpret_stdlib(
+        r#"t = {}
+           a, b = t == t, t == {}"#,
+    );
+    assert_eq!(variable(&env, "a"), "Boolean(true)");
+    assert_eq!(variable(&env, "b"), "Boolean(false)");
+}
+
+#[test]
+fn test_native_metamethod() {
+    let env = interpret_stdlib(
+        r#"t = setmetatable({}, {__index = type, __call = rawequal})
+           a, b = t(t), t.x"#,
+    );
+    assert_eq!(variable(&env, "a"), "Boolean(true)");
+    assert_eq!(variable(&env, "b"), r#"String("table")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform arithmetic on a table value")]
+fn test_arithmetic_without_metamethod() {
+    interpret_stdlib("x = setmetatable({}, {}) + 1");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to compare two table values")]
+fn test_comparison_without_metamethod() {
+    interpret_stdlib("x = {} < {}");
+}
+
+#[test]
+fn test_pairs_metamethod() {
+    let env = interpret_stdlib(
+        r#"Set = {}
+           Set.__pairs = function(set)
+               return function(items, item) return (next(items, item)) end, set.items, nil
+           end
+           set = setmetatable({items = {apple = true, pear = true}, size = 2}, Set)
+           count, names = 0, {}
+           for item, extra in pairs(set) do count = count + 1 names[item] = extra == nil end
+           a, b, c = count, names.apple, names.size"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(2)");
+    assert_eq!(variable(&env, "b"), "Boolean(true)");
+    assert_eq!(variable(&env, "c"), "Nil");
+}
+
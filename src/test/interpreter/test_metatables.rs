@@ -0,0 +1,165 @@
+use super::utils::{interpret_stdlib, variable};
+
+#[test]
+fn test_getmetatable() {
+    let env = interpret_stdlib(
+        r#"mt = {}
+           t = setmetatable({}, mt)
+           a = rawequal(getmetatable(t), mt)
+           b = getmetatable({})
+           c = getmetatable(5)
+           setmetatable(t, nil)
+           d = getmetatable(t)"#,
+    );
+    assert_eq!(variable(&env, "a"), "Boolean(true)");
+    assert_eq!(variable(&env, "b"), "Nil");
+    assert_eq!(variable(&env, "c"), "Nil");
+    assert_eq!(variable(&env, "d"), "Nil");
+}
+
+#[test]
+fn test_shared_metatable() {
+    let env = interpret_stdlib(
+        r#"mt = {}
+           a = setmetatable({}, mt)
+           b = setmetatable({}, mt)
+           mt.__index = {x = 1}
+           x, y = a.x, b.x"#,
+    );
+    assert_eq!(variable(&env, "x"), "Integer(1)");
+    assert_eq!(variable(&env, "y"), "Integer(1)");
+}
+
+#[test]
+fn test_protected_metatable() {
+    let env = interpret_stdlib(
+        r#"t = setmetatable({}, {__metatable = "locked"})
+           x = getmetatable(t)"#,
+    );
+    assert_eq!(variable(&env, "x"), r#"String("locked")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:2: cannot change a protected metatable")]
+fn test_protected_metatable_change() {
+    interpret_stdlib(
+        r#"t = setmetatable({}, {__metatable = false})
+           setmetatable(t, {})"#,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #2 to 'setmetatable' (nil or table expected)")]
+fn test_setmetatable_invalid() {
+    interpret_stdlib("setmetatable({}, 5)");
+}
+
+#[test]
+fn test_index_function() {
+    let env = interpret_stdlib(
+        r#"t = setmetatable({x = 1}, {__index = function (t, key) return key .. "!" end})
+           a, b = t.x, t.y"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), r#"String("y!")"#);
+}
+
+#[test]
+fn test_index_chain() {
+    let env = interpret_stdlib(
+        r#"base = {x = 1}
+           middle = setmetatable({y = 2}, {__index = base})
+           t = setmetatable({}, {__index = middle})
+           a, b, c = t.x, t.y, t.z"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Nil");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:3: '__index' chain too long; possibly a loop")]
+fn test_index_loop() {
+    interpret_stdlib(
+        r#"t = setmetatable({}, {})
+           getmetatable(t).__index = t
+           x = t.y"#,
+    );
+}
+
+#[test]
+fn test_newindex_function() {
+    let env = interpret_stdlib(
+        r#"log = {}
+           t = setmetatable({x = 1}, {__newindex = function (t, key, value) rawset(log, key, value) end})
+           t.x = 2
+           t.y = 3
+           a, b, c = t.x, rawget(t, "y"), log.y"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(2)");
+    assert_eq!(variable(&env, "b"), "Nil");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+}
+
+#[test]
+fn test_newindex_table() {
+    let env = interpret_stdlib(
+        r#"storage = {}
+           t = setmetatable({}, {__newindex = storage})
+           t.x = 1
+           a, b = rawget(t, "x"), storage.x"#,
+    );
+    assert_eq!(variable(&env, "a"), "Nil");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+}
+
+#[test]
+fn test_call() {
+    let env = interpret_stdlib(
+        r#"t = setmetatable({value = 10}, {__call = function (self, a, b) return self.value + a + b end})
+           x = t(1, 2)"#,
+    );
+    assert_eq!(variable(&env, "x"), "Integer(13)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to call a table value (global 't')")]
+fn test_call_without_metamethod() {
+    interpret_stdlib("t = setmetatable({}, {}) t()");
+}
+
+#[test]
+fn test_methods_inheritance() {
+    let env = interpret_stdlib(
+        r#"Account = {}
+           Account.__index = Account
+
+           function Account.new(balance)
+               return setmetatable({balance = balance}, Account)
+           end
+
+           function Account:deposit(value)
+               self.balance = self.balance + value
+           end
+
+           account = Account.new(10)
+           account:deposit(5)
+           x = account.balance"#,
+    );
+    assert_eq!(variable(&env, "x"), "Integer(15)");
+}
+
+#[test]
+fn test_tables_are_shared() {
+    let env = interpret_stdlib(
+        r#"a = {}
+           b = a
+           b.x = 1
+           z = a.x
+           x = 1
+           y = x
+           x = 5"#,
+    );
+    assert_eq!(variable(&env, "z"), "Integer(1)");
+    assert_eq!(variable(&env, "y"), "Integer(1)");
+}
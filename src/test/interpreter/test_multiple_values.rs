@@ -0,0 +1,151 @@
+use crate::interpreter::environment;
+use crate::utils;
+
+use super::utils::{interpret_stdlib, variable};
+
+const FUNCTIONS: &str = r##"
+    function three() return 1, 2, 3 end
+    function none() end
+    function id(...) return ... end
+    function count(...) return select("#", ...) end
+"##;
+
+fn interpret_functions(source_code: &str) -> utils::Shared<environment::Environment> {
+    interpret_stdlib(&format!("{}\n{}", FUNCTIONS, source_code))
+}
+
+#[test]
+fn test_multiple_assignment() {
+    let env = interpret_functions("a, b, c, d = three()");
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Nil");
+}
+
+#[test]
+fn test_truncation() {
+    let env = interpret_functions(
+        r#"a, b, c = three(), 10
+           d, e = (three())
+           f = three() + 10"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(10)");
+    assert_eq!(variable(&env, "c"), "Nil");
+    assert_eq!(variable(&env, "d"), "Integer(1)");
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "f"), "Integer(11)");
+}
+
+#[test]
+fn test_arguments_expansion() {
+    let env = interpret_functions(
+        r#"a = count(three())
+           b = count(three(), three())
+           c = count((three()))
+           d = count(none())
+           e = count(none(), nil)
+           f = count()"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(4)");
+    assert_eq!(variable(&env, "c"), "Integer(1)");
+    assert_eq!(variable(&env, "d"), "Integer(0)");
+    assert_eq!(variable(&env, "e"), "Integer(2)");
+    assert_eq!(variable(&env, "f"), "Integer(0)");
+}
+
+#[test]
+fn test_table_constructor_expansion() {
+    let env = interpret_functions(
+        r#"a = #{three()}
+           b = #{three(), three()}
+           c = #{(three())}
+           d = #{three(), x = 1}
+           t = {[1] = "a", "b"}
+           e = t[1]"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(4)");
+    assert_eq!(variable(&env, "c"), "Integer(1)");
+    assert_eq!(variable(&env, "d"), "Integer(1)");
+    assert_eq!(variable(&env, "e"), r#"String("b")"#);
+}
+
+#[test]
+fn test_varargs() {
+    let env = interpret_functions(
+        r#"a, b, c = id(1, nil, 3)
+           d = count(nil, nil)
+           function second(...) local x, y = ... return y end
+           e = second("x", "y", "z")
+           function rest(first, ...) return count(...) end
+           f = rest(1, 2, 3)
+           g = rest()"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Nil");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Integer(2)");
+    assert_eq!(variable(&env, "e"), r#"String("y")"#);
+    assert_eq!(variable(&env, "f"), "Integer(2)");
+    assert_eq!(variable(&env, "g"), "Integer(0)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:7: Cannot use '...' outside a vararg function")]
+fn test_varargs_outside_vararg_function() {
+    interpret_functions(
+        r#"function f(a) return ... end
+           f(1)"#,
+    );
+}
+
+#[test]
+fn test_return_stops_function() {
+    let env = interpret_functions(
+        r#"function f(x)
+               if x then
+                   return "if"
+               end
+               return "end"
+           end
+           a, b = f(true), f(false)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("if")"#);
+    assert_eq!(variable(&env, "b"), r#"String("end")"#);
+}
+
+#[test]
+fn test_table_pack() {
+    let env = interpret_functions(
+        r#"t = table.pack(1, nil, 3)
+           a, b, c = t.n, t[1], t[3]
+           d = table.pack().n"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Integer(0)");
+}
+
+#[test]
+fn test_table_unpack() {
+    let env = interpret_functions(
+        r#"a, b, c = table.unpack({1, 2, 3})
+           d, e = table.unpack({1, 2, 3}, 2)
+           f, g = table.unpack({1, 2, 3}, 2, 5)
+           h = count(table.unpack({1, 2, 3}, 3, 1))
+           i = count(table.unpack(table.pack(three())))"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Integer(2)");
+    assert_eq!(variable(&env, "e"), "Integer(3)");
+    assert_eq!(variable(&env, "f"), "Integer(2)");
+    assert_eq!(variable(&env, "g"), "Integer(3)");
+    assert_eq!(variable(&env, "h"), "Integer(0)");
+    assert_eq!(variable(&env, "i"), "Integer(3)");
+}
@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+
+use crate::ast::rules;
+use crate::interpreter::{environment, native, types};
+use crate::utils;
+
+use super::utils::{interpret_rule_env, variable};
+
+fn native_env() -> utils::Shared<environment::Environment> {
+    let mut env = environment::Environment::new(None, environment::BreakFlag::None);
+
+    native::register(&mut env, "sum", |_, args| {
+        let mut result = 0i64;
+        for arg in args {
+            result += match_type!(&arg,
+                types::Type::Integer(number) => *number,
+                _ => return Err(format!("Cannot sum {}", arg).into())
+            );
+        }
+
+        Ok(VecDeque::from(vec![types::Type::Integer(result)]))
+    });
+
+    native::register(&mut env, "swap", |_, mut args| {
+        let first = args.pop_front().unwrap_or(types::Type::Nil);
+        let second = args.pop_front().unwrap_or(types::Type::Nil);
+
+        Ok(VecDeque::from(vec![second, first]))
+    });
+
+    native::register(&mut env, "set", |env, mut args| {
+        let value = args.pop_front().unwrap_or(types::Type::Nil);
+        env.borrow_mut().add_variable("result".to_string(), value);
+
+        Ok(VecDeque::new())
+    });
+
+    utils::Shared::new(env)
+}
+
+
+#[test]
+fn test_native_call() {
+    let mut env = native_env();
+
+    let (val, _) = interpret_rule_env("sum(1, 2, 3)", rules::exp, &mut env);
+    assert_eq!(val, "Integer(6)");
+
+    let (_, env) = interpret_rule_env("set(sum(2, 3))", rules::block, &mut env);
+    assert_eq!(variable(&env, "result"), "Integer(5)");
+}
+
+#[test]
+fn test_native_multiple_values() {
+    let mut env = native_env();
+
+    let (_, env) = interpret_rule_env("x, y = swap(1, 2)", rules::block, &mut env);
+    assert_eq!(variable(&env, "x"), "Integer(2)");
+    assert_eq!(variable(&env, "y"), "Integer(1)");
+}
+
+#[test]
+fn test_native_method() {
+    let mut env = native_env();
+
+    let (_, env) = interpret_rule_env("t = { swap = swap } x, y = t:swap(3)", rules::block, &mut env);
+    assert_eq!(variable(&env, "x"), "Integer(3)");
+    assert!(variable(&env, "y").starts_with("Table { id: 4,"));
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: Cannot sum Boolean(true)")]
+fn test_native_error() {
+    let mut env = native_env();
+
+    interpret_rule_env("sum(1, true)", rules::stat, &mut env);
+}
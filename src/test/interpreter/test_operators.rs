@@ -1,15 +1,15 @@
 use crate::ast::rules;
 
-use super::utils::interpret_rule;
+use super::utils::{interpret_rule, interpret_stdlib, variable};
 
 #[test]
 fn test_unop_minus() {
     let (val, mut _env) = interpret_rule("-7", rules::exp);
-    assert_eq!(val, "Number(-7.0)");
+    assert_eq!(val, "Integer(-7)");
 }
 
 #[test]
-#[should_panic(expected = "Runtime error: Can't negate Boolean(true) value")]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform arithmetic on a boolean value")]
 fn test_unop_minus_invalid() {
     interpret_rule("-true", rules::exp);
 }
@@ -32,7 +32,7 @@ fn test_unop_not() {
 #[test]
 fn test_unop_len() {
     let (val, mut _env) = interpret_rule(r#"#"Hello world""#, rules::exp);
-    assert_eq!(val, "Number(11.0)");
+    assert_eq!(val, "Integer(11)");
 
     // TODO
     /*let (val, mut _env) = interpret_rule(r#"{1, 2, 3}"#, rules::exp);
@@ -40,7 +40,17 @@ fn test_unop_len() {
 }
 
 #[test]
-#[should_panic(expected = "Runtime error: Can't get length of Number(7.0) value")]
+fn test_unop_len_binary() {
+    let (val, mut _env) = interpret_rule(r#"#"\xff""#, rules::exp);
+    assert_eq!(val, "Integer(1)");
+    let (val, mut _env) = interpret_rule(r#"#"\u{110000}""#, rules::exp);
+    assert_eq!(val, "Integer(4)");
+    let (val, mut _env) = interpret_rule(r#"#"\0\200\xC3""#, rules::exp);
+    assert_eq!(val, "Integer(3)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to get length of a number value")]
 fn test_unop_len_invalid() {
     interpret_rule(r#"#7"#, rules::exp);
 }
@@ -48,50 +58,146 @@ fn test_unop_len_invalid() {
 #[test]
 fn test_unop_bitwise_not() {
     let (val, mut _env) = interpret_rule("~1100", rules::exp);
-    assert_eq!(val, "Number(-1101.0)");
+    assert_eq!(val, "Integer(-1101)");
 }
 
 #[test]
-#[should_panic(expected = "Runtime error: Can't apply bitwise not to Boolean(true) value")]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform bitwise operation on a boolean value")]
 fn test_unop_bitwise_not_invalid() {
     interpret_rule("~true", rules::exp);
 }
 
-// TODO: Test metatables operators
 // Keyword::PLUS | Keyword::MINUS | Keyword::MUL | Keyword::DIV | Keyword::FLOORDIV | Keyword::MOD | Keyword::POW
 #[test]
 fn test_binop_arithmetic() {
     let (val, mut _env) = interpret_rule("1 + 3", rules::exp);
-    assert_eq!(val, "Number(4.0)");
+    assert_eq!(val, "Integer(4)");
     let (val, mut _env) = interpret_rule("1 - 3", rules::exp);
-    assert_eq!(val, "Number(-2.0)");
+    assert_eq!(val, "Integer(-2)");
     let (val, mut _env) = interpret_rule("0 * 3", rules::exp);
-    assert_eq!(val, "Number(0.0)");
+    assert_eq!(val, "Integer(0)");
     let (val, mut _env) = interpret_rule("1 / 4", rules::exp);
     assert_eq!(val, "Number(0.25)");
     let (val, mut _env) = interpret_rule("11 // 3", rules::exp);
-    assert_eq!(val, "Number(3.0)");
+    assert_eq!(val, "Integer(3)");
     let (val, mut _env) = interpret_rule("8 % 3", rules::exp);
-    assert_eq!(val, "Number(2.0)");
+    assert_eq!(val, "Integer(2)");
     let (val, mut _env) = interpret_rule("2 ^ 3", rules::exp);
     assert_eq!(val, "Number(8.0)");
 }
 
+#[test]
+fn test_binop_integer_arithmetic() {
+    // Integer arithmetic wraps around
+    let (val, mut _env) = interpret_rule("9223372036854775807 + 1", rules::exp);
+    assert_eq!(val, "Integer(-9223372036854775808)");
+    let (val, mut _env) = interpret_rule("0x7fffffffffffffff * 2", rules::exp);
+    assert_eq!(val, "Integer(-2)");
+    // Large integers keep precision
+    let (val, mut _env) = interpret_rule("9007199254740993 - 1", rules::exp);
+    assert_eq!(val, "Integer(9007199254740992)");
+
+    // Floor division and modulo round toward minus infinity
+    let (val, mut _env) = interpret_rule("-7 // 2", rules::exp);
+    assert_eq!(val, "Integer(-4)");
+    let (val, mut _env) = interpret_rule("-7 % 3", rules::exp);
+    assert_eq!(val, "Integer(2)");
+    let (val, mut _env) = interpret_rule("7 % -3", rules::exp);
+    assert_eq!(val, "Integer(-2)");
+    let (val, mut _env) = interpret_rule("-7.5 % 2", rules::exp);
+    assert_eq!(val, "Number(0.5)");
+    let (val, mut _env) = interpret_rule("7 // 2.0", rules::exp);
+    assert_eq!(val, "Number(3.0)");
+    let (val, mut _env) = interpret_rule("(-9223372036854775807 - 1) // -1", rules::exp);
+    assert_eq!(val, "Integer(-9223372036854775808)");
+
+    // Mixed operations give floats
+    let (val, mut _env) = interpret_rule("1 + 2.0", rules::exp);
+    assert_eq!(val, "Number(3.0)");
+    let (val, mut _env) = interpret_rule(r#""10" + 1"#, rules::exp);
+    assert_eq!(val, "Integer(11)");
+    let (val, mut _env) = interpret_rule(r#""1e1" + 1"#, rules::exp);
+    assert_eq!(val, "Number(11.0)");
+    let (val, mut _env) = interpret_rule("-(-9223372036854775807 - 1)", rules::exp);
+    assert_eq!(val, "Integer(-9223372036854775808)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform 'n//0'")]
+fn test_binop_integer_division_by_zero() {
+    interpret_rule("1 // 0", rules::exp);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform 'n%0'")]
+fn test_binop_integer_modulo_by_zero() {
+    interpret_rule("1 % 0", rules::exp);
+}
+
+#[test]
+fn test_binop_number_comparison() {
+    let (val, mut _env) = interpret_rule("3 == 3.0", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("1 < 1.5", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("2 <= 1.5", rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    // 2^53 + 1 can't be represented with float, but compared exactly
+    let (val, mut _env) = interpret_rule("9007199254740993 > 2^53", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("9007199254740993 == 2^53", rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    let (val, mut _env) = interpret_rule("9223372036854775807 < 2^63", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+}
+
+#[test]
+fn test_binop_number_equality() {
+    // Numbers are compared exactly, without any tolerance
+    let (val, mut _env) = interpret_rule("1e20 == 1e20 + 1", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("0.1 + 0.2 == 0.3", rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    let (val, mut _env) = interpret_rule("1 + 2^-52 == 1", rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    let (val, mut _env) = interpret_rule("2^-1074 == 0", rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    let (val, mut _env) = interpret_rule("-0.0 == 0", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("1/0 == 2^1024", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+}
+
+#[test]
+fn test_binop_nan_equality() {
+    let env = interpret_stdlib(
+        r#"nan = 0/0
+           a = nan == nan
+           b = nan ~= nan
+           c = nan == 1
+           d = nan < nan or nan >= nan"#,
+    );
+    assert_eq!(variable(&env, "a"), "Boolean(false)");
+    assert_eq!(variable(&env, "b"), "Boolean(true)");
+    assert_eq!(variable(&env, "c"), "Boolean(false)");
+    assert_eq!(variable(&env, "d"), "Boolean(false)");
+}
+
 #[test]
 fn test_binop_arithmetic_conversion() {
     let (val, mut _env) = interpret_rule(r#"1 + "3""#, rules::exp);
-    assert_eq!(val, "Number(4.0)");
+    assert_eq!(val, "Integer(4)");
     let (val, mut _env) = interpret_rule(r#""1" - 3"#, rules::exp);
-    assert_eq!(val, "Number(-2.0)");
+    assert_eq!(val, "Integer(-2)");
     let (val, mut _env) = interpret_rule(r#""0.8" / "4""#, rules::exp);
     assert_eq!(val, "Number(0.2)");
 }
 
 #[test]
-#[should_panic(expected = r#"Runtime error: Can't convert string "Hello" to apply + operator"#)]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform arithmetic on a string value")]
 fn test_binop_arithmetic_invalid_conversion() {
     let (val, mut _env) = interpret_rule(r#"1 + "Hello""#, rules::exp);
-    assert_eq!(val, "Number(4.0)");
+    assert_eq!(val, "Integer(4)");
 }
 
 // Keyword::LESS | Keyword::LEQ | Keyword::GREATER | Keyword::GEQ | Keyword::EQ | Keyword::NEQ
@@ -184,44 +290,76 @@ fn test_binop_comparison_strings() {
 #[test]
 fn test_binop_comparison_diff_types() {
     let (val, mut _env) = interpret_rule(r#""1" ~= 1"#, rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule(r#""1" == 1"#, rules::exp);
+    assert_eq!(val, "Boolean(false)");
+    let (val, mut _env) = interpret_rule("nil == nil", rules::exp);
+    assert_eq!(val, "Boolean(true)");
+    let (val, mut _env) = interpret_rule("nil == false", rules::exp);
     assert_eq!(val, "Boolean(false)");
 }
 
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to compare number with string")]
+fn test_binop_comparison_diff_types_invalid() {
+    interpret_rule(r#"1 < "2""#, rules::exp);
+}
+
 // Keyword::SOR | Keyword::TILDA | Keyword::SAND | Keyword::SHRIGHT | Keyword::SHLEFT
 #[test]
 fn test_binop_bitwise() {
     let (val, mut _env) = interpret_rule("1 | 3", rules::exp);
-    assert_eq!(val, "Number(3.0)");
+    assert_eq!(val, "Integer(3)");
     let (val, mut _env) = interpret_rule("1 | 2", rules::exp);
-    assert_eq!(val, "Number(3.0)");
+    assert_eq!(val, "Integer(3)");
 
     let (val, mut _env) = interpret_rule("1 & 3", rules::exp);
-    assert_eq!(val, "Number(1.0)");
+    assert_eq!(val, "Integer(1)");
     let (val, mut _env) = interpret_rule("1 & 2", rules::exp);
-    assert_eq!(val, "Number(0.0)");
+    assert_eq!(val, "Integer(0)");
 
     let (val, mut _env) = interpret_rule("1 ~ 3", rules::exp);
-    assert_eq!(val, "Number(2.0)");
+    assert_eq!(val, "Integer(2)");
     let (val, mut _env) = interpret_rule("1 ~ 2", rules::exp);
-    assert_eq!(val, "Number(3.0)");
+    assert_eq!(val, "Integer(3)");
 
     let (val, mut _env) = interpret_rule("1 << 3", rules::exp);
-    assert_eq!(val, "Number(8.0)");
+    assert_eq!(val, "Integer(8)");
     let (val, mut _env) = interpret_rule("1 << 2", rules::exp);
-    assert_eq!(val, "Number(4.0)");
+    assert_eq!(val, "Integer(4)");
 
     let (val, mut _env) = interpret_rule("8 >> 3", rules::exp);
-    assert_eq!(val, "Number(1.0)");
+    assert_eq!(val, "Integer(1)");
     let (val, mut _env) = interpret_rule("8 >> 2", rules::exp);
-    assert_eq!(val, "Number(2.0)");
+    assert_eq!(val, "Integer(2)");
+
+    let (val, mut _env) = interpret_rule("1 << 64", rules::exp);
+    assert_eq!(val, "Integer(0)");
+    let (val, mut _env) = interpret_rule("8 >> -1", rules::exp);
+    assert_eq!(val, "Integer(16)");
+    let (val, mut _env) = interpret_rule("-1 >> 60", rules::exp);
+    assert_eq!(val, "Integer(15)");
 }
 
 #[test]
-#[should_panic(
-    expected = r#"Runtime error: Bitwise operator can be applied only to numbers. Got String("1") and Number(3.0)"#
-)]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to perform bitwise operation on a boolean value")]
 fn test_binop_bitwise_invalid() {
-    interpret_rule(r#""1" | 3"#, rules::exp);
+    interpret_rule("true | 3", rules::exp);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: number has no integer representation")]
+fn test_binop_bitwise_float() {
+    interpret_rule("1.5 | 3", rules::exp);
+}
+
+#[test]
+fn test_binop_bitwise_conversion() {
+    // Floats with integer values and numeric strings are converted to integers
+    let (val, mut _env) = interpret_rule(r#""1" | 2.0"#, rules::exp);
+    assert_eq!(val, "Integer(3)");
+    let (val, mut _env) = interpret_rule("~0x10.0p0", rules::exp);
+    assert_eq!(val, "Integer(-17)");
 }
 
 // Keyword::OR | Keyword::AND
@@ -239,12 +377,12 @@ fn test_binop_boolean() {
     let (val, mut _env) = interpret_rule("nil or false", rules::exp);
     assert_eq!(val, "Boolean(false)");
     let (val, mut _env) = interpret_rule("false or nil", rules::exp);
-    assert_eq!(val, "Boolean(false)");
+    assert_eq!(val, "Nil");
 
     let (val, mut _env) = interpret_rule("1 or false", rules::exp);
-    assert_eq!(val, "Boolean(true)");
+    assert_eq!(val, "Integer(1)");
     let (val, mut _env) = interpret_rule(r#"false or "nil""#, rules::exp);
-    assert_eq!(val, "Boolean(true)");
+    assert_eq!(val, r#"String("nil")"#);
 
     let (val, mut _env) = interpret_rule("true and false", rules::exp);
     assert_eq!(val, "Boolean(false)");
@@ -256,14 +394,50 @@ fn test_binop_boolean() {
     assert_eq!(val, "Boolean(false)");
 
     let (val, mut _env) = interpret_rule("nil and true", rules::exp);
-    assert_eq!(val, "Boolean(false)");
+    assert_eq!(val, "Nil");
     let (val, mut _env) = interpret_rule("true and nil", rules::exp);
-    assert_eq!(val, "Boolean(false)");
+    assert_eq!(val, "Nil");
 
     let (val, mut _env) = interpret_rule("1 and true", rules::exp);
     assert_eq!(val, "Boolean(true)");
     let (val, mut _env) = interpret_rule(r#"true and "nil""#, rules::exp);
-    assert_eq!(val, "Boolean(true)");
+    assert_eq!(val, r#"String("nil")"#);
+
+    let (val, mut _env) = interpret_rule("nil and 1 or 2", rules::exp);
+    assert_eq!(val, "Integer(2)");
+    let (val, mut _env) = interpret_rule("1 and 2 or 3", rules::exp);
+    assert_eq!(val, "Integer(2)");
+}
+
+#[test]
+fn test_binop_boolean_lazy() {
+    // Right operand would fail, if evaluated
+    let (val, mut _env) = interpret_rule("nil and nil + 1", rules::exp);
+    assert_eq!(val, "Nil");
+    let (val, mut _env) = interpret_rule("1 or nil + 1", rules::exp);
+    assert_eq!(val, "Integer(1)");
+
+    let env = interpret_stdlib(
+        r#"calls = 0
+           function touch() calls = calls + 1 return true end
+
+           t = nil
+           field = t and t.field
+           t = t or {field = 5}
+           t = t or {field = 7}
+           other = t and t.field
+
+           a = false and touch()
+           b = true or touch()
+           c = nil or touch()"#,
+    );
+
+    assert_eq!(variable(&env, "field"), "Nil");
+    assert_eq!(variable(&env, "other"), "Integer(5)");
+    assert_eq!(variable(&env, "a"), "Boolean(false)");
+    assert_eq!(variable(&env, "b"), "Boolean(true)");
+    assert_eq!(variable(&env, "c"), "Boolean(true)");
+    assert_eq!(variable(&env, "calls"), "Integer(1)");
 }
 
 // Keyword::DOT2
@@ -277,4 +451,7 @@ fn test_binop_concat() {
     assert_eq!(val, r#"String("1 world")"#);
     let (val, mut _env) = interpret_rule("1 .. 2", rules::exp);
     assert_eq!(val, r#"String("12")"#);
+    // Halves of a UTF-8 sequence make a valid character
+    let (val, mut _env) = interpret_rule(r#""\xC3".."\xA9\xff""#, rules::exp);
+    assert_eq!(val, r#"String("é\xff")"#);
 }
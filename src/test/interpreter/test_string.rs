@@ -0,0 +1,384 @@
+use super::utils::{interpret_stdlib, variable};
+
+#[test]
+fn test_string_basic_functions() {
+    let env = interpret_stdlib(
+        r#"a, b, c = string.len("hello"), string.upper("Hello!"), string.lower("HeLLo")
+           d, e, f = string.rep("ab", 3), string.rep("ab", 3, ", "), string.rep("ab", 0)
+           g, h = string.reverse("abc"), string.char(72, 105)
+           i, j = string.len(""), string.len(123)"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(5)");
+    assert_eq!(variable(&env, "b"), r#"String("HELLO!")"#);
+    assert_eq!(variable(&env, "c"), r#"String("hello")"#);
+    assert_eq!(variable(&env, "d"), r#"String("ababab")"#);
+    assert_eq!(variable(&env, "e"), r#"String("ab, ab, ab")"#);
+    assert_eq!(variable(&env, "f"), r#"String("")"#);
+    assert_eq!(variable(&env, "g"), r#"String("cba")"#);
+    assert_eq!(variable(&env, "h"), r#"String("Hi")"#);
+    assert_eq!(variable(&env, "i"), "Integer(0)");
+    assert_eq!(variable(&env, "j"), "Integer(3)");
+}
+
+#[test]
+fn test_string_sub() {
+    let env = interpret_stdlib(
+        r#"s = "hello"
+           a, b, c = s:sub(2, 4), s:sub(-3), s:sub(2)
+           d, e, f = s:sub(0), s:sub(4, 2), s:sub(-100, 100)
+           g = s:sub(2, -2)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("ell")"#);
+    assert_eq!(variable(&env, "b"), r#"String("llo")"#);
+    assert_eq!(variable(&env, "c"), r#"String("ello")"#);
+    assert_eq!(variable(&env, "d"), r#"String("hello")"#);
+    assert_eq!(variable(&env, "e"), r#"String("")"#);
+    assert_eq!(variable(&env, "f"), r#"String("hello")"#);
+    assert_eq!(variable(&env, "g"), r#"String("ell")"#);
+}
+
+#[test]
+fn test_string_byte() {
+    let env = interpret_stdlib(
+        r##"a = string.byte("A")
+           b, c, d = string.byte("abc", 1, -1)
+           e = select("#", string.byte("abc", 3, 2))
+           f = string.byte("abc", -1)"##,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(65)");
+    assert_eq!(variable(&env, "b"), "Integer(97)");
+    assert_eq!(variable(&env, "c"), "Integer(98)");
+    assert_eq!(variable(&env, "d"), "Integer(99)");
+    assert_eq!(variable(&env, "e"), "Integer(0)");
+    assert_eq!(variable(&env, "f"), "Integer(99)");
+}
+
+#[test]
+fn test_string_rep_large() {
+    let env = interpret_stdlib(
+        r#"a, b = string.rep("", 1e9), string.rep("", 1e9, "")
+           c = #string.rep("abc", 100000, ",")
+           d = pcall(string.rep, "x", 1e10)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("")"#);
+    assert_eq!(variable(&env, "b"), r#"String("")"#);
+    assert_eq!(variable(&env, "c"), "Integer(399999)");
+    assert_eq!(variable(&env, "d"), "Boolean(false)");
+}
+
+#[test]
+fn test_string_binary_data() {
+    let env = interpret_stdlib(
+        r#"a, b = string.byte(string.char(200)), #string.char(255)
+           s = string.char(255, 0, 200)
+           c, d = s:reverse():byte(1), s:sub(3) == "\200"
+           e, f = string.format("%s!", s) == s .. "!", s:gsub("\0", "")
+           g = ("\xC3\xA9"):upper()"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(200)");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+    assert_eq!(variable(&env, "s"), r#"String("\xff\0\xc8")"#);
+    assert_eq!(variable(&env, "c"), "Integer(200)");
+    assert_eq!(variable(&env, "d"), "Boolean(true)");
+    assert_eq!(variable(&env, "e"), "Boolean(true)");
+    assert_eq!(variable(&env, "f"), r#"String("\xff\xc8")"#);
+    assert_eq!(variable(&env, "g"), r#"String("é")"#);
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: bad argument #2 to 'char' (value out of range)")]
+fn test_string_char_out_of_range() {
+    interpret_stdlib("s = string.char(65, 256)");
+}
+
+#[test]
+fn test_string_methods() {
+    let env = interpret_stdlib(
+        r#"s = "Hello"
+           a, b = s:upper(), ("x"):rep(3)
+           c = getmetatable("").__index == string
+           d = #s:lower()"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("HELLO")"#);
+    assert_eq!(variable(&env, "b"), r#"String("xxx")"#);
+    assert_eq!(variable(&env, "c"), "Boolean(true)");
+    assert_eq!(variable(&env, "d"), "Integer(5)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to call a nil value (method 'unknown')")]
+fn test_string_unknown_method() {
+    interpret_stdlib(r#"s = "abc" s:unknown()"#);
+}
+
+#[test]
+fn test_string_find() {
+    let env = interpret_stdlib(
+        r#"a, b = string.find("hello world", "wor")
+           c, d = string.find("hello world", "o", 6)
+           e, f = string.find("a.b", ".", 1, true)
+           g = string.find("hello", "xyz")
+           h, i, j, k = string.find("hello world", "(o)%s(w)")
+           l, m = string.find("hello", "", 10)
+           n, o = string.find("hello", "l+")"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(7)");
+    assert_eq!(variable(&env, "b"), "Integer(9)");
+    assert_eq!(variable(&env, "c"), "Integer(8)");
+    assert_eq!(variable(&env, "d"), "Integer(8)");
+    assert_eq!(variable(&env, "e"), "Integer(2)");
+    assert_eq!(variable(&env, "f"), "Integer(2)");
+    assert_eq!(variable(&env, "g"), "Nil");
+    assert_eq!(variable(&env, "h"), "Integer(5)");
+    assert_eq!(variable(&env, "i"), "Integer(7)");
+    assert_eq!(variable(&env, "j"), r#"String("o")"#);
+    assert_eq!(variable(&env, "k"), r#"String("w")"#);
+    assert_eq!(variable(&env, "l"), "Nil");
+    assert_eq!(variable(&env, "n"), "Integer(3)");
+    assert_eq!(variable(&env, "o"), "Integer(4)");
+}
+
+#[test]
+fn test_string_match_classes() {
+    let env = interpret_stdlib(
+        r#"a = string.match("  trim me  ", "^%s*(.-)%s*$")
+           b, c = string.match("key = value", "(%w+)%s*=%s*(%w+)")
+           d = string.match("abc123def", "%d+")
+           e = string.match("abc123def", "%D+$")
+           f = string.match("x = [[long]]", "%[(%[.-%])%]")
+           g = string.match("hello", "[^aeiou]+")
+           h = string.match("2024-01-15", "^(%d%d%d%d)%-")
+           i = string.match("hello", "^ell")
+           j = string.match("a]b", "[]]")"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("trim me")"#);
+    assert_eq!(variable(&env, "b"), r#"String("key")"#);
+    assert_eq!(variable(&env, "c"), r#"String("value")"#);
+    assert_eq!(variable(&env, "d"), r#"String("123")"#);
+    assert_eq!(variable(&env, "e"), r#"String("def")"#);
+    assert_eq!(variable(&env, "f"), r#"String("[long]")"#);
+    assert_eq!(variable(&env, "g"), r#"String("h")"#);
+    assert_eq!(variable(&env, "h"), r#"String("2024")"#);
+    assert_eq!(variable(&env, "i"), "Nil");
+    assert_eq!(variable(&env, "j"), r#"String("]")"#);
+}
+
+#[test]
+fn test_string_match_special_items() {
+    let env = interpret_stdlib(
+        r#"a = string.match("f(a(b)c)d", "%b()")
+           b = string.match("THE (quick) fox", "%f[%a]%a+", 5)
+           c, d = string.match("hello", "()ll()")
+           e = string.match("say 'hi' or \"bye\"", "([\"'])(.-)%1")
+           f = string.match("aaa", "a-b")
+           g = string.match("aaab", "a-b")
+           h = string.match("color colour", "colou?r", 2)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("(a(b)c)")"#);
+    assert_eq!(variable(&env, "b"), r#"String("quick")"#);
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), "Integer(5)");
+    assert_eq!(variable(&env, "e"), r#"String("'")"#);
+    assert_eq!(variable(&env, "f"), "Nil");
+    assert_eq!(variable(&env, "g"), r#"String("aaab")"#);
+    assert_eq!(variable(&env, "h"), r#"String("colour")"#);
+}
+
+#[test]
+fn test_string_pattern_errors() {
+    let env = interpret_stdlib(
+        r#"a, b = pcall(string.find, "abc", "%")
+           c, d = pcall(string.find, "abc", "[a")
+           e, f = pcall(string.match, "abc", "(a")
+           g, h = pcall(string.match, "abc", "a)")
+           i, j = pcall(string.match, "abc", "%1")
+           k, l = pcall(string.match, "abc", "%b")"#,
+    );
+    assert_eq!(variable(&env, "b"), r#"String("malformed pattern (ends with '%')")"#);
+    assert_eq!(variable(&env, "d"), r#"String("malformed pattern (missing ']')")"#);
+    assert_eq!(variable(&env, "f"), r#"String("unfinished capture")"#);
+    assert_eq!(variable(&env, "h"), r#"String("invalid pattern capture")"#);
+    assert_eq!(variable(&env, "j"), r#"String("invalid capture index %1")"#);
+    assert_eq!(variable(&env, "l"), r#"String("malformed pattern (missing arguments to '%b')")"#);
+}
+
+#[test]
+fn test_string_gmatch() {
+    let env = interpret_stdlib(
+        r#"next_pair = string.gmatch("a=1, b=2", "(%w+)=(%w+)")
+           a, b = next_pair()
+           c, d = next_pair()
+           e = next_pair()
+           next_word = ("one two"):gmatch("%a*")
+           f, g, h = next_word(), next_word(), next_word()"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("a")"#);
+    assert_eq!(variable(&env, "b"), r#"String("1")"#);
+    assert_eq!(variable(&env, "c"), r#"String("b")"#);
+    assert_eq!(variable(&env, "d"), r#"String("2")"#);
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "f"), r#"String("one")"#);
+    assert_eq!(variable(&env, "g"), r#"String("two")"#);
+    assert_eq!(variable(&env, "h"), "Nil");
+}
+
+#[test]
+fn test_string_gsub() {
+    let env = interpret_stdlib(
+        r#"a, b = string.gsub("hello world", "o", "0")
+           c = string.gsub("hello world", "(%w+)", "<%1>")
+           d = string.gsub("hello world", "%w+", "%0 %0", 1)
+           e, f = string.gsub("abc", "", "-")
+           g = string.gsub("  x  ", "^%s+", "")
+           h = string.gsub("50%", "%%", " percent")"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("hell0 w0rld")"#);
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), r#"String("<hello> <world>")"#);
+    assert_eq!(variable(&env, "d"), r#"String("hello hello world")"#);
+    assert_eq!(variable(&env, "e"), r#"String("-a-b-c-")"#);
+    assert_eq!(variable(&env, "f"), "Integer(4)");
+    assert_eq!(variable(&env, "g"), r#"String("x  ")"#);
+    assert_eq!(variable(&env, "h"), r#"String("50 percent")"#);
+}
+
+#[test]
+fn test_string_gsub_table_and_function() {
+    let env = interpret_stdlib(
+        r#"vars = { name = "Bob", age = 42 }
+           a = string.gsub("$name is $age, $unknown", "%$(%w+)", vars)
+           b = string.gsub("hello world", "%w+", function(word) return word:upper() end)
+           c = string.gsub("a,b", "(%w)", function(letter) if letter == "a" then return false end return "B" end)
+           d = string.gsub("key=value", "(%w+)=(%w+)", function(k, v) return v .. "=" .. k end)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("Bob is 42, $unknown")"#);
+    assert_eq!(variable(&env, "b"), r#"String("HELLO WORLD")"#);
+    assert_eq!(variable(&env, "c"), r#"String("a,B")"#);
+    assert_eq!(variable(&env, "d"), r#"String("value=key")"#);
+}
+
+#[test]
+fn test_string_gsub_errors() {
+    let env = interpret_stdlib(
+        r#"a, b = pcall(string.gsub, "abc", "b", "%2")
+           c, d = pcall(string.gsub, "abc", "b", "%x")
+           e, f = pcall(string.gsub, "abc", "b", function() return {} end)
+           g, h = pcall(string.gsub, "abc", "b", true)"#,
+    );
+    assert_eq!(variable(&env, "b"), r#"String("invalid capture index %2")"#);
+    assert_eq!(variable(&env, "d"), r#"String("invalid use of '%' in replacement string")"#);
+    assert_eq!(variable(&env, "f"), r#"String("invalid replacement value (a table)")"#);
+    assert_eq!(
+        variable(&env, "h"),
+        r#"String("bad argument #3 to 'gsub' (string/function/table expected, got boolean)")"#
+    );
+}
+
+#[test]
+fn test_string_format_integers() {
+    let env = interpret_stdlib(
+        r#"a = string.format("[%5d][%-5d][%05d][%+d][% d]", 42, 42, 42, 42, 42)
+           b = string.format("[%.3d][%.0d][%i][%d]", 7, 0, -3, 3.0)
+           c = string.format("[%x][%X][%#x][%08.3x][%o][%#o]", 255, 255, 255, 255, 8, 8)
+           d = string.format("[%u][%x][%c%c][%-3c]", -1, -1, 72, 105, 65)"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("[   42][42   ][00042][+42][ 42]")"#);
+    assert_eq!(variable(&env, "b"), r#"String("[007][][-3][3]")"#);
+    assert_eq!(variable(&env, "c"), r#"String("[ff][FF][0xff][     0ff][10][010]")"#);
+    assert_eq!(
+        variable(&env, "d"),
+        r#"String("[18446744073709551615][ffffffffffffffff][Hi][A  ]")"#
+    );
+}
+
+#[test]
+fn test_string_format_floats() {
+    let env = interpret_stdlib(
+        r#"a = string.format("[%f][%.2f][%10.3f][%-8.1f][%+.1f][%#.0f][%08.2f]", 3.14159, 3.14159, 3.14159, 2.5, 2.5, 3, -3.14159)
+           b = string.format("[%e][%.2E][%.0e]", 12345.678, 0.000123, 15)
+           c = string.format("[%g][%g][%g][%g][%#g][%G][%.3g]", 100000, 1e20, 0.0001, 123456789, 1.5, 1e-10, 2 / 3)
+           d = string.format("[%a][%A][%.2a][%.0a][%a][%a]", 1, 3, 1 / 3, 1.5, 0.0, 5e-324)
+           e = string.format("[%f][%5.1f][%E]", 1 / 0, -1 / 0, 1 / 0)"#,
+    );
+    assert_eq!(
+        variable(&env, "a"),
+        r#"String("[3.141590][3.14][     3.142][2.5     ][+2.5][3.][-0003.14]")"#
+    );
+    assert_eq!(variable(&env, "b"), r#"String("[1.234568e+04][1.23E-04][2e+01]")"#);
+    assert_eq!(
+        variable(&env, "c"),
+        r#"String("[100000][1e+20][0.0001][1.23457e+08][1.50000][1E-10][0.667]")"#
+    );
+    assert_eq!(
+        variable(&env, "d"),
+        r#"String("[0x1p+0][0X1.8P+1][0x1.55p-2][0x2p+0][0x0p+0][0x0.0000000000001p-1022]")"#
+    );
+    assert_eq!(variable(&env, "e"), r#"String("[inf][ -inf][INF]")"#);
+}
+
+#[test]
+fn test_string_format_strings() {
+    let env = interpret_stdlib(
+        r#"a = string.format("[%s][%10s][%-10s][%.2s][%5.1s][%%]", "hi", "hi", "hi", "hello", "hello")
+           b = string.format("%s %s %s", 1, 2.5, nil)
+           point = setmetatable({}, {__tostring = function() return "point" end})
+           c = string.format("[%s][%7s]", point, point)"#,
+    );
+    assert_eq!(
+        variable(&env, "a"),
+        r#"String("[hi][        hi][hi        ][he][    h][%]")"#
+    );
+    assert_eq!(variable(&env, "b"), r#"String("1 2.5 nil")"#);
+    assert_eq!(variable(&env, "c"), r#"String("[point][  point]")"#);
+}
+
+#[test]
+fn test_string_format_quoted() {
+    let env = interpret_stdlib(
+        r#"a = string.format("%q", 'say "hi"\n\\ \0 1\0009 \r')
+           b = string.format("%q %q %q %q", 42, math.mininteger, 1.5, 2 ^ 63)
+           c = string.format("%q %q %q %q %q", 1 / 0, -1 / 0, 0 / 0 ~= 0 / 0, nil, false)"#,
+    );
+    assert_eq!(
+        variable(&env, "a"),
+        r#"String("\"say \\\"hi\\\"\\\n\\\\ \\0 1\\0009 \\13\"")"#
+    );
+    assert_eq!(variable(&env, "b"), r#"String("42 0x8000000000000000 0x1.8p+0 0x1p+63")"#);
+    assert_eq!(variable(&env, "c"), r#"String("1e9999 -1e9999 true nil false")"#);
+}
+
+#[test]
+fn test_string_format_errors() {
+    let env = interpret_stdlib(
+        r#"a, b = pcall(string.format, "%10q", 1)
+           c, d = pcall(string.format, "%#d", 1)
+           e, f = pcall(string.format, "%123d", 1)
+           g, h = pcall(string.format, "%y", 1)
+           i, j = pcall(string.format, "%d", 1.5)
+           k, l = pcall(string.format, "%d")
+           m, n = pcall(string.format, "%q", {})
+           o, p = pcall(string.format, "%5s", "a\0b")
+           q, r = pcall(string.format, "%5%")"#,
+    );
+    assert_eq!(variable(&env, "b"), r#"String("specifier '%q' cannot have modifiers")"#);
+    assert_eq!(variable(&env, "d"), r#"String("invalid option '%#d' to 'format'")"#);
+    assert_eq!(variable(&env, "f"), r#"String("invalid option '%123' to 'format'")"#);
+    assert_eq!(variable(&env, "h"), r#"String("invalid option '%y' to 'format'")"#);
+    assert_eq!(variable(&env, "r"), r#"String("invalid option '%5' to 'format'")"#);
+    assert_eq!(
+        variable(&env, "j"),
+        r#"String("bad argument #2 to 'format' (number has no integer representation)")"#
+    );
+    assert_eq!(
+        variable(&env, "l"),
+        r#"String("bad argument #2 to 'format' (number expected, got no value)")"#
+    );
+    assert_eq!(
+        variable(&env, "n"),
+        r#"String("bad argument #2 to 'format' (value has no literal form)")"#
+    );
+    assert_eq!(
+        variable(&env, "p"),
+        r#"String("bad argument #2 to 'format' (string contains zeros)")"#
+    );
+}
@@ -1,54 +1,56 @@
-use std::ops::Deref;
-
 use crate::ast::rules;
 use crate::interpreter::types::Type::{self, Table};
 
-use super::utils::interpret_rule;
+use super::utils::{interpret_rule, interpret_stdlib, variable};
 
 // tableconstructor ::= ‘{’ [fieldlist] ‘}’
 // fieldlist ::= field [fieldlist_prefix]
 // field ::= ‘[’ exp ‘]’ ‘=’ exp | Name ‘=’ exp | exp
 #[test]
 fn test_simple_table() {
-    if let (Table { border, .. }, _) = interpret_rule("{}", rules::tableconstructor) {
-        assert_eq!(border, 0);
+    if let (Table(table), _) = interpret_rule("{}", rules::tableconstructor) {
+        let table = table.borrow();
+        assert_eq!(table.border(), 0);
     } else {
         panic!()
     }
 
-    if let (Table { border, map, .. }, _) = interpret_rule("{1}", rules::tableconstructor) {
-        assert_eq!(border, 1);
+    if let (Table(table), _) = interpret_rule("{1}", rules::tableconstructor) {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(1)
         );
     } else {
         panic!()
     }
 
-    if let (Table { border, map, .. }, _) = interpret_rule("{1, 2}", rules::tableconstructor) {
-        assert_eq!(border, 2);
+    if let (Table(table), _) = interpret_rule("{1, 2}", rules::tableconstructor) {
+        let table = table.borrow();
+        assert_eq!(table.border(), 2);
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(1)
         );
         assert_eq!(
-            map.get(&Type::Number(2f64)).unwrap().borrow().deref(),
-            &Type::Number(2f64)
+            &table.get(&Type::Integer(2)),
+            &Type::Integer(2)
         );
     } else {
         panic!()
     }
 
-    if let (Table { border, map, .. }, _) = interpret_rule("{1; 3}", rules::tableconstructor) {
-        assert_eq!(border, 2);
+    if let (Table(table), _) = interpret_rule("{1; 3}", rules::tableconstructor) {
+        let table = table.borrow();
+        assert_eq!(table.border(), 2);
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(1)
         );
         assert_eq!(
-            map.get(&Type::Number(2f64)).unwrap().borrow().deref(),
-            &Type::Number(3f64)
+            &table.get(&Type::Integer(2)),
+            &Type::Integer(3)
         );
     } else {
         panic!()
@@ -59,14 +61,12 @@ fn test_simple_table() {
 fn test_name_table() {
     let (val, _) = interpret_rule(r#"{Hello = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 0);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 0);
         assert_eq!(
-            map.get(&Type::String("Hello".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::String("Hello".into())),
+            &Type::Integer(1)
         );
     } else {
         panic!()
@@ -74,18 +74,16 @@ fn test_name_table() {
 
     let (val, _) = interpret_rule(r#"{Hello = 1, 2}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 1);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
         assert_eq!(
-            map.get(&Type::String("Hello".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::String("Hello".into())),
+            &Type::Integer(1)
         );
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(2f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(2)
         );
     } else {
         panic!()
@@ -93,20 +91,15 @@ fn test_name_table() {
 
     let (val, _) = interpret_rule(r#"{Hello = 1; world = false}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 0);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 0);
         assert_eq!(
-            map.get(&Type::String("Hello".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::String("Hello".into())),
+            &Type::Integer(1)
         );
         assert_eq!(
-            map.get(&Type::String("world".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
+            &table.get(&Type::String("world".into())),
             &Type::Boolean(false)
         );
     } else {
@@ -119,11 +112,12 @@ fn test_name_table() {
 fn test_bracket_table() {
     let (val, _) = interpret_rule(r#"{[1] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 1);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(1)
         );
     } else {
         panic!()
@@ -131,18 +125,16 @@ fn test_bracket_table() {
 
     let (val, _) = interpret_rule(r#"{["Hello"] = 1, 2}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 1);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
         assert_eq!(
-            map.get(&Type::String("Hello".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
-            &Type::Number(1f64)
+            &table.get(&Type::String("Hello".into())),
+            &Type::Integer(1)
         );
         assert_eq!(
-            map.get(&Type::Number(1f64)).unwrap().borrow().deref(),
-            &Type::Number(2f64)
+            &table.get(&Type::Integer(1)),
+            &Type::Integer(2)
         );
     } else {
         panic!()
@@ -150,13 +142,11 @@ fn test_bracket_table() {
 
     let (val, _) = interpret_rule(r#"{[{}] = 1; world = false}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, map, .. } = val {
-        assert_eq!(border, 0);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 0);
         assert_eq!(
-            map.get(&Type::String("world".to_string()))
-                .unwrap()
-                .borrow()
-                .deref(),
+            &table.get(&Type::String("world".into())),
             &Type::Boolean(false)
         );
     } else {
@@ -168,47 +158,278 @@ fn test_bracket_table() {
 fn test_table_border() {
     let (val, _) = interpret_rule(r#"{[1] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, .. } = val {
-        assert_eq!(border, 1);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
     } else {
         panic!()
     }
 
     let (val, _) = interpret_rule(r#"{[1] = 1, [2] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, .. } = val {
-        assert_eq!(border, 2);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 2);
     } else {
         panic!()
     }
 
     let (val, _) = interpret_rule(r#"{[2] = 1, [1] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, .. } = val {
-        assert_eq!(border, 2);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 2);
     } else {
         panic!()
     }
 
     let (val, _) = interpret_rule(r#"{[1] = 1, [3] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, .. } = val {
-        assert_eq!(border, 1);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 1);
     } else {
         panic!()
     }
 
     let (val, _) = interpret_rule(r#"{[1] = 1, [3] = 1, [2] = 1}"#, rules::tableconstructor);
     println!("{:?}", val);
-    if let Table { border, .. } = val {
-        assert_eq!(border, 3);
+    if let Table(table) = val {
+        let table = table.borrow();
+        assert_eq!(table.border(), 3);
     } else {
         panic!()
     }
 }
 
 #[test]
-#[should_panic(expected = "Runtime error: Cannot use `nil` as a table key")]
+#[should_panic(expected = "Runtime error: chunk:1: Cannot use `nil` as a table key")]
 fn test_table_invalid_id() {
     interpret_rule(r#"{[hello] = 1}"#, rules::tableconstructor);
 }
+
+#[test]
+fn test_table_number_keys() {
+    // Float keys with integer values are the same keys as integers
+    let env = interpret_stdlib(
+        r#"t = {10, 20}
+           t[3.0] = 30
+           t[1.5] = "half"
+           a, b, c, d = t[1.0], t[3], #t, t[1.5]
+           e = rawget(t, 2.0)"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(10)");
+    assert_eq!(variable(&env, "b"), "Integer(30)");
+    assert_eq!(variable(&env, "c"), "Integer(3)");
+    assert_eq!(variable(&env, "d"), r#"String("half")"#);
+    assert_eq!(variable(&env, "e"), "Integer(20)");
+}
+
+#[test]
+fn test_table_self_key() {
+    // Table used as a key in itself is hashed and compared without borrowing
+    let env = interpret_stdlib(
+        r#"t = {}
+           t[t] = 1
+           a = t[t]
+           rawset(t, t, 2)
+           b, c = rawget(t, t), t[{}]"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(1)");
+    assert_eq!(variable(&env, "b"), "Integer(2)");
+    assert_eq!(variable(&env, "c"), "Nil");
+}
+
+#[test]
+fn test_table_float_keys() {
+    // Zero keys are the same regardless of sign, large floats don't collide with close integers
+    let env = interpret_stdlib(
+        r#"t = {}
+           t[0] = "zero"
+           t[2^53] = "float"
+           t[9007199254740993] = "integer"
+           t[1e100] = "huge"
+           t[-1/0] = "-inf"
+           a, b, c = t[-0.0], t[0.0], t[9007199254740992]
+           d, e, f = t[9007199254740993], t[1e100], t[-1/0]
+           g = t[0/0]"#,
+    );
+    assert_eq!(variable(&env, "a"), r#"String("zero")"#);
+    assert_eq!(variable(&env, "b"), r#"String("zero")"#);
+    assert_eq!(variable(&env, "c"), r#"String("float")"#);
+    assert_eq!(variable(&env, "d"), r#"String("integer")"#);
+    assert_eq!(variable(&env, "e"), r#"String("huge")"#);
+    assert_eq!(variable(&env, "f"), r#"String("-inf")"#);
+    assert_eq!(variable(&env, "g"), "Nil");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: table index is NaN")]
+fn test_table_nan_key() {
+    interpret_stdlib("t = {}; t[0/0] = 1");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: table index is NaN")]
+fn test_table_constructor_nan_key() {
+    interpret_stdlib("t = {[0/0] = 1}");
+}
+
+#[test]
+#[should_panic(expected = "table index is NaN")]
+fn test_table_rawset_nan_key() {
+    interpret_stdlib("rawset({}, 0/0, 1)");
+}
+
+#[test]
+fn test_table_read_missing_field() {
+    let env = interpret_stdlib(
+        r#"t = {1, 2, x = 3}
+           if t.missing or t[10] or t[3] then end
+           count = 0
+           for _ in pairs(t) do count = count + 1 end
+           length = #t"#,
+    );
+    assert_eq!(variable(&env, "count"), "Integer(3)");
+    assert_eq!(variable(&env, "length"), "Integer(2)");
+
+    let env = interpret_stdlib("t = {} x = t.y");
+    let value = env.borrow_mut().get("t").unwrap().borrow().clone();
+    if let Table(table) = value {
+        assert!(format!("{:?}", table.borrow()).ends_with("array: [], hash: {}, metatable: None }"));
+    } else {
+        panic!()
+    }
+}
+
+#[test]
+fn test_table_remove_field() {
+    let env = interpret_stdlib(
+        r#"t = {1, 2, 3, x = 1}
+           t.x = nil
+           t[3] = nil
+           a, b = #t, next({x = 1, y = nil})
+           t[2] = nil
+           c = #t
+           t[2], t[3] = 2, 3
+           d = #t
+           u = {x = 1}
+           u.x = nil
+           e = next(u)"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(2)");
+    assert_eq!(variable(&env, "b"), r#"String("x")"#);
+    assert_eq!(variable(&env, "c"), "Integer(1)");
+    assert_eq!(variable(&env, "d"), "Integer(3)");
+    assert_eq!(variable(&env, "e"), "Nil");
+}
+
+#[test]
+fn test_table_clear_during_traversal() {
+    let env = interpret_stdlib(
+        r#"t = {1, 2, 3, a = 1, b = 2, c = 3}
+           count = 0
+           for key in pairs(t) do t[key] = nil count = count + 1 end
+           empty = next(t) == nil
+           t.d = 4
+           length = #t"#,
+    );
+    assert_eq!(variable(&env, "count"), "Integer(6)");
+    assert_eq!(variable(&env, "empty"), "Boolean(true)");
+    assert_eq!(variable(&env, "length"), "Integer(0)");
+}
+
+
+#[test]
+fn test_table_array_part() {
+    // Sequences live in the array part, sparse keys stay in the hash part
+    let env = interpret_stdlib(
+        r#"t = {}
+           for i = 1, 100 do t[#t + 1] = i end
+           s = {[1000000] = 1, [2000000] = 2}
+           a, b, c = #t, t[100], #s"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(100)");
+    assert_eq!(variable(&env, "b"), "Integer(100)");
+    assert_eq!(variable(&env, "c"), "Integer(0)");
+
+    let table = env.borrow_mut().get("t").unwrap().borrow().clone();
+    if let Table(table) = table {
+        let table = format!("{:?}", table.borrow());
+        assert!(table.contains("array: [Integer(1), Integer(2), "));
+        assert!(table.ends_with("hash: {}, metatable: None }"));
+    } else {
+        panic!()
+    }
+
+    let table = env.borrow_mut().get("s").unwrap().borrow().clone();
+    if let Table(table) = table {
+        assert!(format!("{:?}", table.borrow()).contains("array: [], hash: {Integer(1000000)"));
+    } else {
+        panic!()
+    }
+}
+
+#[test]
+fn test_table_rehash() {
+    // Fields move to the hash part when the array part becomes too sparse
+    let env = interpret_stdlib(
+        r#"t = {}
+           for i = 1, 64 do t[i] = i end
+           for i = 1, 60 do t[i] = nil end
+           a = #t
+           t.x, t.y, t.z = 1, 2, 3
+           count, sum = 0, 0
+           for key, value in pairs(t) do
+               count = count + 1
+               if math.type(key) == "integer" then sum = sum + value end
+           end
+           b = t[62]"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(64)");
+    assert_eq!(variable(&env, "count"), "Integer(7)");
+    assert_eq!(variable(&env, "sum"), "Integer(250)");
+    assert_eq!(variable(&env, "b"), "Integer(62)");
+
+    let table = env.borrow_mut().get("t").unwrap().borrow().clone();
+    if let Table(table) = table {
+        assert!(format!("{:?}", table.borrow()).contains("array: [], hash: {"));
+    } else {
+        panic!()
+    }
+}
+
+#[test]
+fn test_table_border_in_hash() {
+    let env = interpret_stdlib(
+        r#"t = {x = 1}
+           t[3], t[2], t[1] = 3, 2, 1
+           a = #t
+           u = {10, 20, 30}
+           u[5], u[4] = 50, 40
+           b = #u
+           v = {}
+           v[1] = 1
+           v[2] = nil
+           c = #v"#,
+    );
+    assert_eq!(variable(&env, "a"), "Integer(3)");
+    assert_eq!(variable(&env, "b"), "Integer(5)");
+    assert_eq!(variable(&env, "c"), "Integer(1)");
+}
+
+#[test]
+fn test_table_next_order() {
+    // Array part is traversed first, then hash part in insertion order
+    let env = interpret_stdlib(
+        r#"t = {10, 20, 30, x = 1, y = 2}
+           keys = ""
+           for key in pairs(t) do keys = keys .. key end
+           a, b = next(t, 3)
+           c = next(t, "y")"#,
+    );
+    assert_eq!(variable(&env, "keys"), r#"String("123xy")"#);
+    assert_eq!(variable(&env, "a"), r#"String("x")"#);
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+    assert_eq!(variable(&env, "c"), "Nil");
+}
@@ -2,6 +2,8 @@
 
 use crate::interpreter::types;
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 #[test]
@@ -57,3 +59,26 @@ fn test_multiple_type_match() {
 
     assert_eq!(val, 50f64);
 }
+
+fn hash(value: &types::Type) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_number_hash() {
+    // Equal values must have equal hashes
+    let zero = types::Type::Integer(0);
+    assert_eq!(zero, types::Type::Number(0f64));
+    assert_eq!(zero, types::Type::Number(-0f64));
+    assert_eq!(hash(&zero), hash(&types::Type::Number(0f64)));
+    assert_eq!(hash(&zero), hash(&types::Type::Number(-0f64)));
+
+    let big = types::Type::Number(2f64.powi(62));
+    assert_eq!(big, types::Type::Integer(1 << 62));
+    assert_eq!(hash(&big), hash(&types::Type::Integer(1 << 62)));
+
+    assert_ne!(types::Type::Number(0.5), types::Type::Number(0.5 + f64::EPSILON));
+    assert_ne!(types::Type::Number(f64::NAN), types::Type::Number(f64::NAN));
+}
@@ -1,25 +1,25 @@
 use crate::ast::rules;
 
-use super::utils::{interpret_rule, interpret_rule_env};
+use super::utils::{interpret_rule, interpret_rule_env, interpret_stdlib, variable};
 
 #[test]
 fn test_variable_simple() {
     let (_val, env) = interpret_rule("x = 3", rules::stat);
-    assert_eq!(env, r#"{"x": RefCell { value: Number(3.0) }}"#);
+    assert_eq!(env, r#"{"x": RefCell { value: Integer(3) }}"#);
 
     let (_val, env) = interpret_rule("x = 3, 2", rules::stat);
-    assert_eq!(env, r#"{"x": RefCell { value: Number(3.0) }}"#);
+    assert_eq!(env, r#"{"x": RefCell { value: Integer(3) }}"#);
 
     let (_val, env) = interpret_rule("x, y = 3, false", rules::stat);
     assert_eq!(
         env,
-        r#"{"x": RefCell { value: Number(3.0) }, "y": RefCell { value: Boolean(false) }}"#
+        r#"{"x": RefCell { value: Integer(3) }, "y": RefCell { value: Boolean(false) }}"#
     );
 
     let (_val, env) = interpret_rule("x, y = 3", rules::stat);
     assert_eq!(
         env,
-        r#"{"x": RefCell { value: Number(3.0) }, "y": RefCell { value: Nil }}"#
+        r#"{"x": RefCell { value: Integer(3) }, "y": RefCell { value: Nil }}"#
     );
 }
 
@@ -28,16 +28,16 @@ fn test_variable_table() {
     let (_val, env) = interpret_rule("x = {}", rules::stat);
     assert_eq!(
         env,
-        r#"{"x": RefCell { value: Table { id: 1, map: {}, metatable: {}, border: 0 } }}"#
+        r#"{"x": RefCell { value: Table { id: 1, array: [], hash: {}, metatable: None } }}"#
     );
 
     let (_val, mut env) = interpret_rule("x = {y = 5, [5] = false}", rules::stat);
 
     let (val, mut env) = interpret_rule_env("x.y", rules::var, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Number(5.0) })");
+    assert_eq!(val, "Integer(5)");
 
     let (val, _env) = interpret_rule_env("x[5]", rules::var, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Boolean(false) })");
+    assert_eq!(val, "Boolean(false)");
 }
 
 #[test]
@@ -45,15 +45,84 @@ fn test_variable_table_change() {
     let (_val, mut env) = interpret_rule("x = {y = 5}", rules::stat);
 
     let (val, mut env) = interpret_rule_env("x.y", rules::var, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Number(5.0) })");
+    assert_eq!(val, "Integer(5)");
 
     let (_val, mut env) = interpret_rule_env("x.y = 7", rules::stat, &mut env);
     let (val, _env) = interpret_rule_env("x.y", rules::var, &mut env);
-    assert_eq!(val, "Reference(RefCell { value: Number(7.0) })");
+    assert_eq!(val, "Integer(7)");
 }
 
 #[test]
-#[should_panic(expected = "Runtime error: Attempt to index `Nil` value")]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to index a nil value (global 'x')")]
 fn test_variable_non_id() {
     let (_val, _env) = interpret_rule("x.y", rules::var);
 }
+
+#[test]
+fn test_variable_global_assignment_in_function() {
+    let env = interpret_stdlib(
+        r#"function set() counter = (counter or 0) + 1 end
+           set() set()
+           do created = true end"#,
+    );
+    assert_eq!(variable(&env, "counter"), "Integer(2)");
+    assert_eq!(variable(&env, "created"), "Boolean(true)");
+}
+
+#[test]
+fn test_variable_read_undefined() {
+    let env = interpret_stdlib(
+        r#"function read() local before = value value = 1 return before end
+           a = read()
+           b = value"#,
+    );
+    assert_eq!(variable(&env, "a"), "Nil");
+    assert_eq!(variable(&env, "b"), "Integer(1)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to call a nil value (local 'f')")]
+fn test_variable_nil_local_description() {
+    interpret_stdlib("function g() local f f() end g()");
+}
+
+
+#[test]
+fn test_variable_assignment_order() {
+    // Tables and keys of the targets are evaluated before the assigned values
+    let env = interpret_stdlib(
+        r#"i = 3
+           a = {}
+           i, a[i] = i + 1, 20
+           b = a
+           b, b.x = {}, 1
+           c, d, e = i, a[3], a[4]
+           f = a.x"#,
+    );
+    assert_eq!(variable(&env, "c"), "Integer(4)");
+    assert_eq!(variable(&env, "d"), "Integer(20)");
+    assert_eq!(variable(&env, "e"), "Nil");
+    assert_eq!(variable(&env, "f"), "Integer(1)");
+}
+
+#[test]
+fn test_variable_local_function() {
+    let env = interpret_stdlib(
+        r#"do local function f() end end
+           leaked = f
+           local g = 1
+           do local function g() end end
+           outer = g
+           local function fact(n) if n <= 1 then return 1 end return n * fact(n - 1) end
+           result = fact(5)"#,
+    );
+    assert_eq!(variable(&env, "leaked"), "Nil");
+    assert_eq!(variable(&env, "outer"), "Integer(1)");
+    assert_eq!(variable(&env, "result"), "Integer(120)");
+}
+
+#[test]
+#[should_panic(expected = "Runtime error: chunk:1: attempt to call a nil value (global 'f')")]
+fn test_variable_local_function_scope() {
+    interpret_stdlib("do local function f() end end f()");
+}
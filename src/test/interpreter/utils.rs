@@ -1,11 +1,36 @@
+use crate::ast::expressions;
 use crate::ast::parser;
 use crate::ast::rules;
 use crate::ast::stack;
 use crate::utils;
 
 use crate::interpreter::environment;
+use crate::interpreter::stdlib;
 use crate::interpreter::types;
 
+/// Fail on syntax error or tokens left after parsing
+fn check_parser(parser: &mut parser::Parser) {
+    if let Some(error) = parser.error() {
+        panic!("{}", error);
+    }
+
+    assert!(
+        parser.peek().is_none(),
+        "Parser contains tokens after parsing: {:?}",
+        parser
+    );
+}
+
+/// Evaluate expression. Runtime errors fail the test
+fn eval(
+    expression: Box<dyn expressions::Expression>,
+    env: &mut utils::Shared<environment::Environment>,
+) -> types::Type {
+    expression
+        .eval(env)
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
 #[allow(dead_code)]
 pub fn interpret(source_code: &str) -> (types::Type, utils::Shared<environment::Environment>) {
     interpret_rule(source_code, rules::chunk)
@@ -22,18 +47,14 @@ where
     let mut stack = stack::Stack::default();
 
     func(&mut parser, &mut stack);
-
-    assert!(
-        parser.peek().is_none(),
-        format!("Parser contains tokens after parsing: {:?}", parser)
-    );
+    check_parser(&mut parser);
 
     let mut env = utils::Shared::new(environment::Environment::new(
         None,
         environment::BreakFlag::None,
     ));
 
-    (stack.pop_single().eval(&mut env), env)
+    (eval(stack.pop_single(), &mut env), env)
 }
 
 pub fn interpret_rule_env<F>(
@@ -48,12 +69,30 @@ where
     let mut stack = stack::Stack::default();
 
     func(&mut parser, &mut stack);
+    check_parser(&mut parser);
 
-    assert!(
-        parser.peek().is_none(),
-        format!("Parser contains tokens after parsing: {:?}", parser)
-    );
-    let result = stack.pop_single().eval(env);
+    let result = eval(stack.pop_single(), env);
 
     (result, env.clone())
 }
+
+/// Interpret chunk with standard library loaded into the environment
+#[allow(dead_code)]
+pub fn interpret_stdlib(source_code: &str) -> utils::Shared<environment::Environment> {
+    let mut env = utils::Shared::new(environment::Environment::new(
+        None,
+        environment::BreakFlag::None,
+    ));
+    stdlib::load(&mut env.borrow_mut());
+
+    interpret_rule_env(source_code, rules::chunk, &mut env).1
+}
+
+/// Debug representation of the variable value
+#[allow(dead_code)]
+pub fn variable(env: &utils::Shared<environment::Environment>, name: &str) -> String {
+    match env.borrow_mut().get(name) {
+        Some(value) => format!("{:?}", value.borrow().unref()),
+        None => "Nil".to_string(),
+    }
+}
@@ -1,2 +1,5 @@
 mod ast;
 mod interpreter;
+mod test_cli;
+mod test_error;
+mod test_repl;
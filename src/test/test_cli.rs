@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+use super::interpreter::utils::variable;
+use crate::ast::AST;
+use crate::cli::{self, Action, Options};
+use crate::interpreter::types;
+
+fn args(line: &str) -> Vec<String> {
+    line.split(' ').map(str::to_string).collect()
+}
+
+#[test]
+fn test_parse_script() {
+    let options = Options::parse(&args("maul -t script.lua -e x")).unwrap();
+    assert!(options.timing);
+    assert!(!options.version);
+    assert!(!options.interactive);
+    assert_eq!(options.actions, vec![]);
+    assert_eq!(options.script, Some(2));
+}
+
+#[test]
+fn test_parse_actions() {
+    let options = Options::parse(&args("maul -e x=1 -lmodule -v -eprint(x)")).unwrap();
+    assert!(options.version);
+    assert_eq!(
+        options.actions,
+        vec![
+            Action::Execute("x=1".to_string()),
+            Action::Require("module".to_string()),
+            Action::Execute("print(x)".to_string()),
+        ]
+    );
+    assert_eq!(options.script, None);
+}
+
+#[test]
+fn test_parse_interactive() {
+    let options = Options::parse(&args("maul -i script.lua")).unwrap();
+    assert!(options.interactive);
+    // Interactive mode shows version, as the reference interpreter does
+    assert!(options.version);
+    assert_eq!(options.script, Some(2));
+}
+
+#[test]
+fn test_parse_only() {
+    let options = Options::parse(&args("maul -p script.lua")).unwrap();
+    assert!(options.parse_only);
+    assert_eq!(options.script, Some(2));
+}
+
+#[test]
+fn test_parse_stop_options() {
+    assert_eq!(Options::parse(&args("maul -- -v")).unwrap().script, Some(2));
+    assert_eq!(Options::parse(&args("maul --")).unwrap().script, None);
+    assert_eq!(Options::parse(&args("maul - -v")).unwrap().script, Some(1));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Options::parse(&args("maul -e")),
+        Err("'-e' needs argument".to_string())
+    );
+    assert_eq!(
+        Options::parse(&args("maul -x script.lua")),
+        Err("unrecognized option '-x'".to_string())
+    );
+}
+
+#[test]
+fn test_arg_table() {
+    let mut env = AST::environment();
+    cli::set_arg_table(&mut env, &args("maul -e x script.lua a b"), Some(3));
+    cli::execute(
+        &mut env,
+        "a, b, c, d, e = arg[-3], arg[-1], arg[0], arg[2], #arg".to_string(),
+        "test",
+        VecDeque::new(),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(variable(&env, "a"), r#"String("maul")"#);
+    assert_eq!(variable(&env, "b"), r#"String("x")"#);
+    assert_eq!(variable(&env, "c"), r#"String("script.lua")"#);
+    assert_eq!(variable(&env, "d"), r#"String("b")"#);
+    assert_eq!(variable(&env, "e"), "Integer(2)");
+}
+
+#[test]
+fn test_execute_chunks() {
+    let mut env = AST::environment();
+    let args = VecDeque::from(vec![types::Type::Integer(1), types::Type::Integer(2)]);
+
+    // Chunks share globals, receive arguments as varargs and may return values
+    cli::execute(&mut env, "x = 10".to_string(), "test", VecDeque::new(), false).unwrap();
+    let result = cli::execute(
+        &mut env,
+        r#"local a, b = ... if a then return x + a + b end error("unreachable")"#.to_string(),
+        "test",
+        args,
+        false,
+    );
+
+    assert_eq!(result, Ok(VecDeque::from(vec![types::Type::Integer(13)])));
+}
+
+#[test]
+fn test_execute_chunk_locals() {
+    let mut env = AST::environment();
+    let mut execute = |source: &str| {
+        cli::execute(&mut env, source.to_string(), "test", VecDeque::new(), false)
+    };
+
+    // Locals of a chunk are not visible in the next chunks and don't shadow globals
+    execute("local print, secret = 5, 42").unwrap();
+    execute("x, y = secret, type(print)").unwrap();
+
+    let error = execute("local t = nil t.x = 1").expect_err("Execution must fail");
+    assert_eq!(
+        error.to_string(),
+        "Runtime error: test:1: attempt to index a nil value (local 't')"
+    );
+    assert_eq!(variable(&env, "x"), "Nil");
+    assert_eq!(variable(&env, "y"), r#"String("function")"#);
+}
+
+#[test]
+fn test_execute_error() {
+    let mut env = AST::environment();
+    let error = cli::execute(&mut env, "x = nil + 1".to_string(), "test", VecDeque::new(), false)
+        .expect_err("Execution must fail");
+
+    assert_eq!(
+        error.to_string(),
+        "Runtime error: test:1: attempt to perform arithmetic on a nil value"
+    );
+}
+
+#[test]
+fn test_require_not_found() {
+    let mut env = AST::environment();
+    let error = cli::require(&mut env, "no.such_module", false).expect_err("Module must be missing");
+
+    assert_eq!(
+        error.to_string(),
+        "Runtime error: module 'no.such_module' not found: no file 'no/such_module.lua', \
+         no file 'no/such_module/init.lua'"
+    );
+}
+
+#[test]
+fn test_run_exit_code() {
+    assert_eq!(cli::run(&args("maul -e x=1")), 0);
+    assert_eq!(cli::run(&args("maul -e error()")), 1);
+    assert_eq!(cli::run(&args("maul -e")), 1);
+    assert_eq!(cli::run(&args("maul no_such_script.lua")), 1);
+}
+
+#[test]
+fn test_check_syntax() {
+    assert_eq!(cli::check("x = 1 return x", "maul"), 0);
+    assert_eq!(cli::check("x = = 1\ny = = 2", "maul"), 1);
+    assert_eq!(cli::check("x = \"unfinished", "maul"), 1);
+}
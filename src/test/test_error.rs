@@ -0,0 +1,220 @@
+use crate::ast::AST;
+use crate::error::Error;
+use crate::interpreter::types;
+
+fn parse_error(source: &str) -> Error {
+    AST::new(source.to_string()).expect_err("Source code must be invalid")
+}
+
+fn runtime_error(source: &str) -> Error {
+    AST::new(source.to_string())
+        .expect("Source code must be valid")
+        .eval()
+        .expect_err("Evaluation must fail")
+}
+
+/// Runtime error with position of the failed expression
+fn located(message: &str) -> Error {
+    Error::runtime_value(types::Type::String(message.into()))
+}
+
+#[test]
+fn test_valid_chunk() {
+    let ast = AST::new("x = 1 while x < 10 do x = x + 1 end".to_string()).unwrap();
+    assert_eq!(ast.eval(), Ok(()));
+}
+
+#[test]
+fn test_lexical_error() {
+    assert_eq!(
+        parse_error("x = \"Hello"),
+        Error::Lexical {
+            message: "Unmatched double quotes".to_string(),
+            row: 1,
+            column: 4
+        }
+    );
+
+    match parse_error("x = 1\ny = $") {
+        Error::Lexical { message, row, .. } => {
+            assert_eq!(message, "Unexpected symbol '$'");
+            assert_eq!(row, 2);
+        }
+        error => panic!("Expected lexical error, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_syntax_error() {
+    match parse_error("while x do\n  x = 1\nx = 2") {
+        Error::Syntax { message, row, .. } => {
+            assert_eq!(message, "'end' expected near <eof>");
+            assert_eq!(row, 3);
+        }
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+
+    match parse_error("x = 1 + ") {
+        Error::Syntax { message, .. } => {
+            assert_eq!(message, "expression expected near <eof>")
+        }
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+
+    match parse_error("x = 1 )") {
+        Error::Syntax { message, row, column } => {
+            assert_eq!(message, "statement expected near ')'");
+            assert_eq!((row, column), (1, 7));
+        }
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+
+    // Named rules and terminals are described in the message
+    match parse_error("for 1 = 2, 3 do end") {
+        Error::Syntax { message, .. } => assert_eq!(message, "<name> expected near '1'"),
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+
+    match parse_error("local t = {1, 2") {
+        Error::Syntax { message, .. } => assert_eq!(message, "'}' expected near <eof>"),
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+
+    match parse_error("f() = 1") {
+        Error::Syntax { message, .. } => assert!(message.starts_with("Cannot assign to Funcall")),
+        error => panic!("Expected syntax error, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_runtime_error() {
+    assert_eq!(
+        runtime_error("x = 1 y = x + nil"),
+        located("chunk:1: attempt to perform arithmetic on a nil value")
+    );
+
+    assert_eq!(
+        runtime_error("goto skip"),
+        located("chunk:1: No visible label 'skip' for `goto`")
+    );
+
+    // Errors cross function boundaries
+    assert_eq!(
+        runtime_error("function f() error(\"Failure\") end f()"),
+        located("chunk:1: Failure")
+    );
+}
+
+#[test]
+fn test_runtime_error_position() {
+    let error = AST::with_name("local t = {}\n\nlocal y = t.x.z".to_string(), "script.lua")
+        .unwrap()
+        .eval()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        located("script.lua:3: attempt to index a nil value (field 'x')")
+    );
+
+    // Error is reported at the innermost expression, not at the enclosing call
+    assert_eq!(
+        runtime_error("function f(a)\n  return 1 +\n    a.x\nend\nf(1)"),
+        located("chunk:3: attempt to index a number value (local 'a')")
+    );
+}
+
+#[test]
+fn test_runtime_error_description() {
+    assert_eq!(
+        runtime_error("undefined()"),
+        located("chunk:1: attempt to call a nil value (global 'undefined')")
+    );
+    assert_eq!(
+        runtime_error("local function f() local n = 1 n() end f()"),
+        located("chunk:1: attempt to call a number value (local 'n')")
+    );
+    assert_eq!(
+        runtime_error("t = {} t.field.x = 1"),
+        located("chunk:1: attempt to index a nil value (field 'field')")
+    );
+    assert_eq!(
+        runtime_error("t = {} t:method()"),
+        located("chunk:1: attempt to call a nil value (method 'method')")
+    );
+    assert_eq!(
+        runtime_error("t = {} t[1]()"),
+        located("chunk:1: attempt to call a nil value")
+    );
+    assert_eq!(
+        runtime_error("local t = {} x = t + 1"),
+        located("chunk:1: attempt to perform arithmetic on a table value (local 't')")
+    );
+    assert_eq!(
+        runtime_error("x = 1 .. y"),
+        located("chunk:1: attempt to concatenate a nil value (global 'y')")
+    );
+    assert_eq!(
+        runtime_error("t = {} x = 1 & t.mask"),
+        located("chunk:1: attempt to perform bitwise operation on a nil value (field 'mask')")
+    );
+    assert_eq!(
+        runtime_error("local n x = -n"),
+        located("chunk:1: attempt to perform arithmetic on a nil value (local 'n')")
+    );
+    assert_eq!(
+        runtime_error("x = {} < {}"),
+        located("chunk:1: attempt to compare two table values")
+    );
+    assert_eq!(
+        runtime_error("x = 1 <= nil"),
+        located("chunk:1: attempt to compare number with nil")
+    );
+}
+
+#[test]
+fn test_runtime_error_traceback() {
+    let error = runtime_error(
+        r#"local function check(value)
+             if not value then error("invalid value") end
+           end
+           t = { validate = function(self, value) check(value) end }
+           t:validate(false)"#,
+    );
+    assert_eq!(
+        error.traceback(),
+        Some(
+            "stack traceback:
+	[C]: in function 'error'
+	chunk:2: in local 'check'
+	chunk:4: in method 'validate'
+	chunk:5: in main chunk
+	[C]: in ?"
+        )
+    );
+
+    // Traceback is not a part of the error
+    assert_eq!(error, located("chunk:2: invalid value"));
+
+    let error = runtime_error("x = 1\ny = x .. {}");
+    assert_eq!(
+        error.traceback(),
+        Some("stack traceback:\n\tchunk:2: in main chunk\n\t[C]: in ?")
+    );
+    assert_eq!(parse_error("x = ").traceback(), None);
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        Error::runtime("Failure".to_string()).to_string(),
+        "Runtime error: Failure"
+    );
+    assert_eq!(
+        parse_error("x = \"Hello").to_string(),
+        "Lexical error at 1:4: Unmatched double quotes"
+    );
+    assert_eq!(
+        parse_error("x = 1 )").to_string(),
+        "Syntax error at 1:7: statement expected near ')'"
+    );
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::fs;
+
+use super::interpreter::utils::variable;
+use crate::ast::AST;
+use crate::interpreter::types;
+use crate::repl::{History, Repl, CONTINUATION_PROMPT, PROMPT};
+
+fn values(repl: &mut Repl, line: &str) -> VecDeque<types::Type> {
+    repl.eval_line(line)
+        .expect("Line must be complete")
+        .expect("Line must be valid")
+}
+
+#[test]
+fn test_expression_values() {
+    let mut repl = Repl::new(AST::environment());
+
+    assert_eq!(values(&mut repl, "x = 20"), VecDeque::new());
+    assert_eq!(
+        values(&mut repl, "x + 1, nil"),
+        VecDeque::from(vec![types::Type::Integer(21), types::Type::Nil])
+    );
+    assert_eq!(variable(repl.env(), "x"), "Integer(20)");
+}
+
+#[test]
+fn test_continuation() {
+    let mut repl = Repl::new(AST::environment());
+
+    assert_eq!(repl.prompt(), PROMPT);
+    assert!(repl.eval_line("function add(a, b)").is_none());
+    assert_eq!(repl.prompt(), CONTINUATION_PROMPT);
+    assert!(repl.eval_line("  return a +").is_none());
+    assert!(repl.eval_line("    b").is_none());
+    assert_eq!(values(&mut repl, "end"), VecDeque::new());
+    assert_eq!(repl.prompt(), PROMPT);
+
+    assert!(repl.eval_line("s = [[multi").is_none());
+    assert_eq!(values(&mut repl, "line]]"), VecDeque::new());
+    assert!(repl.eval_line("add(1,").is_none());
+    assert_eq!(
+        values(&mut repl, "2)"),
+        VecDeque::from(vec![types::Type::Integer(3)])
+    );
+    assert_eq!(variable(repl.env(), "s"), r#"String("multi\nline")"#);
+}
+
+#[test]
+fn test_errors_keep_globals() {
+    let mut repl = Repl::new(AST::environment());
+
+    values(&mut repl, "x = 1");
+    assert!(matches!(repl.eval_line("x = 2 error(\"failure\")"), Some(Err(_))));
+    // Syntax error discards incomplete input
+    assert!(repl.eval_line("if x then").is_none());
+    assert!(matches!(repl.eval_line(") end"), Some(Err(_))));
+    assert_eq!(repl.prompt(), PROMPT);
+
+    assert_eq!(
+        values(&mut repl, "x"),
+        VecDeque::from(vec![types::Type::Integer(2)])
+    );
+}
+
+#[test]
+fn test_incomplete_errors() {
+    let incomplete = |source: &str| AST::new(source.to_string()).err().unwrap().is_incomplete();
+
+    assert!(incomplete("while true do"));
+    assert!(incomplete("x = "));
+    assert!(incomplete("x = \"string"));
+    assert!(incomplete("x = [==[long ]] string"));
+    assert!(incomplete("--[[ comment"));
+    assert!(!incomplete("x = \"line\nbreak\""));
+    assert!(!incomplete("x = )"));
+    assert!(!incomplete("end"));
+}
+
+#[test]
+fn test_history() {
+    let path = std::env::temp_dir().join(format!("maul_history_{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut history = History::load(path.clone());
+    history.add("x = 1");
+    history.add("   ");
+    history.add("print(x)");
+
+    let history = History::load(path.clone());
+    assert_eq!(
+        history.entries(),
+        &VecDeque::from(vec!["x = 1".to_string(), "print(x)".to_string()])
+    );
+
+    fs::remove_file(&path).unwrap();
+}
@@ -96,11 +96,90 @@ impl<T> Shared<T> {
         }
     }
 
-    pub fn borrow(&self) -> Ref<T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
         self.data.borrow()
     }
 
-    pub fn borrow_mut(&self) -> RefMut<T> {
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
         self.data.borrow_mut()
     }
+
+    /// Check if both values share the same data. Doesn't borrow, so data may be mutably borrowed
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    /// Address of the shared data, which identifies it without borrowing
+    pub fn address(&self) -> usize {
+        Rc::as_ptr(&self.data) as usize
+    }
+}
+
+/// Lua string. Lua strings are byte strings, which may contain any bytes, not only valid UTF-8
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteString(Vec<u8>);
+
+impl ByteString {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for ByteString {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteString {
+    fn from(bytes: Vec<u8>) -> Self {
+        ByteString(bytes)
+    }
+}
+
+impl From<&[u8]> for ByteString {
+    fn from(bytes: &[u8]) -> Self {
+        ByteString(bytes.to_vec())
+    }
+}
+
+impl From<String> for ByteString {
+    fn from(string: String) -> Self {
+        ByteString(string.into_bytes())
+    }
+}
+
+impl From<&str> for ByteString {
+    fn from(string: &str) -> Self {
+        ByteString(string.as_bytes().to_vec())
+    }
+}
+
+/// Invalid UTF-8 sequences are replaced, so display only in messages
+impl std::fmt::Display for ByteString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        String::from_utf8_lossy(&self.0).fmt(f)
+    }
+}
+
+/// Debug prints string literal like Rust strings. Bytes, which are not valid UTF-8, are escaped
+impl std::fmt::Debug for ByteString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\"")?;
+        for chunk in self.0.utf8_chunks() {
+            let valid = format!("{:?}", chunk.valid());
+            write!(f, "{}", &valid[1..valid.len() - 1])?;
+
+            for byte in chunk.invalid() {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        write!(f, "\"")
+    }
 }